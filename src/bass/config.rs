@@ -0,0 +1,348 @@
+use std::{
+	ffi::{c_void, CStr},
+	sync::Mutex,
+	time::Duration,
+};
+
+use bass_sys::*;
+use widestring::U16CString;
+
+use super::error::{BassError, BassErrorCode};
+use crate::BassResult;
+
+/// A global BASS configuration option, without a value.
+///
+/// Used to read the current value of an option with `Bass::config`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConfigOption {
+	UpdatePeriod,
+	UpdateThreads,
+	Buffer,
+	DeviceBuffer,
+	NetTimeout,
+	NetBuffer,
+	NetPrebuffer,
+	NetAgent,
+	NetProxy,
+	SrcQuality,
+	FloatDsp,
+	OggPrescan,
+	Handles,
+	#[cfg(feature = "mixer")]
+	MixerBuffer,
+	#[cfg(feature = "mixer")]
+	SplitBuffer,
+}
+
+/// A global BASS configuration option, along with its value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Config {
+	/// The update period of playback buffers. `Duration::ZERO` disables automatic updating, otherwise 5-100ms.
+	UpdatePeriod(Duration),
+	/// The number of threads used to update playback buffers. `0` disables automatic updating.
+	UpdateThreads(u32),
+	/// The playback buffer length of streams and MOD musics. Up to 5 seconds.
+	Buffer(Duration),
+	/// The output device buffer length. Must be set before the device is initialised.
+	DeviceBuffer(Duration),
+	/// The time to wait for a server to respond to a connection request.
+	NetTimeout(Duration),
+	/// The internet download buffer length.
+	NetBuffer(Duration),
+	/// The amount of the download buffer (as a percentage, 0-100) to fill before playing a stream.
+	NetPrebuffer(u32),
+	/// The "User-Agent" request header sent to servers.
+	NetAgent(String),
+	/// The proxy server settings, in the form of "user:pass@server:port". `None` means don't use a proxy.
+	NetProxy(Option<String>),
+	/// The default sample rate conversion quality, 0 (linear interpolation) to 4 (64 point sinc).
+	SrcQuality(u32),
+	/// Pass 32-bit floating-point sample data to all DSP functions.
+	FloatDsp(bool),
+	/// Pre-scan chained OGG files.
+	OggPrescan(bool),
+	/// The number of existing handles. This is read-only.
+	Handles(u32),
+	/// The length of a mixer's source channel buffer, in update periods (1-5).
+	#[cfg(feature = "mixer")]
+	MixerBuffer(u32),
+	/// The length of a splitter's source buffer.
+	#[cfg(feature = "mixer")]
+	SplitBuffer(Duration),
+}
+
+impl ConfigOption {
+	pub(crate) fn raw(self) -> DWORD {
+		match self {
+			ConfigOption::UpdatePeriod => BASS_CONFIG_UPDATEPERIOD,
+			ConfigOption::UpdateThreads => BASS_CONFIG_UPDATETHREADS,
+			ConfigOption::Buffer => BASS_CONFIG_BUFFER,
+			ConfigOption::DeviceBuffer => BASS_CONFIG_DEV_BUFFER,
+			ConfigOption::NetTimeout => BASS_CONFIG_NET_TIMEOUT,
+			ConfigOption::NetBuffer => BASS_CONFIG_NET_BUFFER,
+			ConfigOption::NetPrebuffer => BASS_CONFIG_NET_PREBUF,
+			ConfigOption::NetAgent => BASS_CONFIG_NET_AGENT,
+			ConfigOption::NetProxy => BASS_CONFIG_NET_PROXY,
+			ConfigOption::SrcQuality => BASS_CONFIG_SRC,
+			ConfigOption::FloatDsp => BASS_CONFIG_FLOATDSP,
+			ConfigOption::OggPrescan => BASS_CONFIG_OGG_PRESCAN,
+			ConfigOption::Handles => BASS_CONFIG_HANDLES,
+			#[cfg(feature = "mixer")]
+			ConfigOption::MixerBuffer => BASS_CONFIG_MIXER_BUFFER,
+			#[cfg(feature = "mixer")]
+			ConfigOption::SplitBuffer => BASS_CONFIG_SPLIT_BUFFER,
+		}
+	}
+
+	/// Whether the option is set with `BASS_SetConfigPtr` rather than `BASS_SetConfig`.
+	fn is_pointer(self) -> bool {
+		matches!(self, ConfigOption::NetAgent | ConfigOption::NetProxy)
+	}
+}
+
+impl Config {
+	pub fn option(&self) -> ConfigOption {
+		match self {
+			Config::UpdatePeriod(_) => ConfigOption::UpdatePeriod,
+			Config::UpdateThreads(_) => ConfigOption::UpdateThreads,
+			Config::Buffer(_) => ConfigOption::Buffer,
+			Config::DeviceBuffer(_) => ConfigOption::DeviceBuffer,
+			Config::NetTimeout(_) => ConfigOption::NetTimeout,
+			Config::NetBuffer(_) => ConfigOption::NetBuffer,
+			Config::NetPrebuffer(_) => ConfigOption::NetPrebuffer,
+			Config::NetAgent(_) => ConfigOption::NetAgent,
+			Config::NetProxy(_) => ConfigOption::NetProxy,
+			Config::SrcQuality(_) => ConfigOption::SrcQuality,
+			Config::FloatDsp(_) => ConfigOption::FloatDsp,
+			Config::OggPrescan(_) => ConfigOption::OggPrescan,
+			Config::Handles(_) => ConfigOption::Handles,
+			#[cfg(feature = "mixer")]
+			Config::MixerBuffer(_) => ConfigOption::MixerBuffer,
+			#[cfg(feature = "mixer")]
+			Config::SplitBuffer(_) => ConfigOption::SplitBuffer,
+		}
+	}
+
+	/// Checks the value is within the range BASS accepts for the option.
+	pub fn validate(&self) -> BassResult<()> {
+		let valid = match self {
+			Config::UpdatePeriod(period) => period.is_zero() || (5..=100).contains(&period.as_millis()),
+			Config::Buffer(length) => (1..=5000).contains(&length.as_millis()),
+			Config::DeviceBuffer(length)
+			| Config::NetTimeout(length)
+			| Config::NetBuffer(length) => length.as_millis() <= u32::MAX as u128,
+			Config::NetPrebuffer(percent) => *percent <= 100,
+			Config::NetAgent(agent) => !agent.contains('\0'),
+			Config::NetProxy(proxy) => proxy.as_ref().map_or(true, |proxy| !proxy.contains('\0')),
+			Config::SrcQuality(quality) => *quality <= 4,
+			Config::UpdateThreads(_) | Config::FloatDsp(_) | Config::OggPrescan(_) => true,
+			// Read-only.
+			Config::Handles(_) => return Err(BassError::new(BassErrorCode::BassErrorNotAvailable, "Bass::set_config")),
+			#[cfg(feature = "mixer")]
+			Config::MixerBuffer(periods) => (1..=5).contains(periods),
+			#[cfg(feature = "mixer")]
+			Config::SplitBuffer(length) => length.as_millis() <= u32::MAX as u128,
+		};
+		if valid {
+			Ok(())
+		} else {
+			Err(BassError::new(BassErrorCode::BassErrorIllParam, "Bass::set_config").with_context(format!("{self:?}")))
+		}
+	}
+
+	fn to_dword(&self) -> DWORD {
+		match self {
+			Config::UpdatePeriod(duration)
+			| Config::Buffer(duration)
+			| Config::DeviceBuffer(duration)
+			| Config::NetTimeout(duration)
+			| Config::NetBuffer(duration) => DWORD(duration.as_millis() as u32),
+			#[cfg(feature = "mixer")]
+			Config::SplitBuffer(duration) => DWORD(duration.as_millis() as u32),
+			Config::UpdateThreads(value)
+			| Config::NetPrebuffer(value)
+			| Config::SrcQuality(value)
+			| Config::Handles(value) => DWORD(*value),
+			#[cfg(feature = "mixer")]
+			Config::MixerBuffer(value) => DWORD(*value),
+			Config::FloatDsp(value) | Config::OggPrescan(value) => DWORD(*value as u32),
+			Config::NetAgent(_) | Config::NetProxy(_) => DWORD(0),
+		}
+	}
+
+	fn from_dword(option: ConfigOption, value: DWORD) -> Self {
+		let millis = || Duration::from_millis(value.0 as u64);
+		match option {
+			ConfigOption::UpdatePeriod => Config::UpdatePeriod(millis()),
+			ConfigOption::UpdateThreads => Config::UpdateThreads(value.0),
+			ConfigOption::Buffer => Config::Buffer(millis()),
+			ConfigOption::DeviceBuffer => Config::DeviceBuffer(millis()),
+			ConfigOption::NetTimeout => Config::NetTimeout(millis()),
+			ConfigOption::NetBuffer => Config::NetBuffer(millis()),
+			ConfigOption::NetPrebuffer => Config::NetPrebuffer(value.0),
+			ConfigOption::SrcQuality => Config::SrcQuality(value.0),
+			ConfigOption::FloatDsp => Config::FloatDsp(value.0 != 0),
+			ConfigOption::OggPrescan => Config::OggPrescan(value.0 != 0),
+			ConfigOption::Handles => Config::Handles(value.0),
+			#[cfg(feature = "mixer")]
+			ConfigOption::MixerBuffer => Config::MixerBuffer(value.0),
+			#[cfg(feature = "mixer")]
+			ConfigOption::SplitBuffer => Config::SplitBuffer(millis()),
+			ConfigOption::NetAgent | ConfigOption::NetProxy => unreachable!("pointer options are not read as DWORDs"),
+		}
+	}
+}
+
+/// BASS doesn't copy the strings given to `BASS_SetConfigPtr`, so they have to live here for as long as they're set.
+static NET_AGENT: Mutex<Option<(String, U16CString)>> = Mutex::new(None);
+static NET_PROXY: Mutex<Option<(String, U16CString)>> = Mutex::new(None);
+
+fn string_storage(option: ConfigOption) -> &'static Mutex<Option<(String, U16CString)>> {
+	match option {
+		ConfigOption::NetAgent => &NET_AGENT,
+		_ => &NET_PROXY,
+	}
+}
+
+pub(crate) fn get(option: ConfigOption) -> BassResult<Config> {
+	if option.is_pointer() {
+		let storage = string_storage(option).lock().unwrap_or_else(|e| e.into_inner());
+		let value = match storage.as_ref() {
+			Some((value, _)) => Some(value.clone()),
+			// Nothing has been set by us, so this is whatever BASS defaults to.
+			None => {
+				let ptr = BASS_GetConfigPtr(option.raw());
+				if ptr.is_null() {
+					None
+				} else {
+					Some(unsafe { CStr::from_ptr(ptr as *const _) }.to_string_lossy().into_owned())
+				}
+			}
+		};
+		return match option {
+			ConfigOption::NetAgent => Ok(Config::NetAgent(value.unwrap_or_default())),
+			_ => Ok(Config::NetProxy(value)),
+		};
+	}
+	let value = BASS_GetConfig(option.raw());
+	if value != -1 {
+		Ok(Config::from_dword(option, value))
+	} else {
+		Err(BassError::get("Bass::config"))
+	}
+}
+
+pub(crate) fn set(config: Config) -> BassResult<()> {
+	config.validate()?;
+	let ok = match &config {
+		Config::NetAgent(value) => set_string(config.option(), Some(value)),
+		Config::NetProxy(value) => set_string(config.option(), value.as_deref()),
+		_ => BASS_SetConfig(config.option().raw(), config.to_dword()),
+	};
+	if ok {
+		Ok(())
+	} else {
+		Err(BassError::get("Bass::set_config"))
+	}
+}
+
+fn set_string(option: ConfigOption, value: Option<&str>) -> bool {
+	let mut storage = string_storage(option).lock().unwrap_or_else(|e| e.into_inner());
+	match value {
+		Some(value) => {
+			let wide = U16CString::from_vec_truncate(value.encode_utf16().collect::<Vec<u16>>());
+			let ok = unsafe { BASS_SetConfigPtr(option.raw() | BASS_UNICODE, wide.as_ptr() as *const c_void) };
+			if ok {
+				// The previous string is only dropped once BASS has stopped pointing at it.
+				*storage = Some((value.to_owned(), wide));
+			}
+			ok
+		}
+		None => {
+			let ok = unsafe { BASS_SetConfigPtr(option.raw(), std::ptr::null::<c_void>()) };
+			if ok {
+				*storage = None;
+			}
+			ok
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dword_round_trip() {
+		let configs = [
+			Config::UpdatePeriod(Duration::ZERO),
+			Config::UpdatePeriod(Duration::from_millis(10)),
+			Config::UpdateThreads(2),
+			Config::Buffer(Duration::from_millis(500)),
+			Config::DeviceBuffer(Duration::from_millis(30)),
+			Config::NetTimeout(Duration::from_secs(5)),
+			Config::NetBuffer(Duration::from_secs(5)),
+			Config::NetPrebuffer(75),
+			Config::SrcQuality(4),
+			Config::FloatDsp(true),
+			Config::FloatDsp(false),
+			Config::OggPrescan(true),
+			Config::Handles(12),
+			#[cfg(feature = "mixer")]
+			Config::MixerBuffer(2),
+			#[cfg(feature = "mixer")]
+			Config::SplitBuffer(Duration::from_millis(2000)),
+		];
+		for config in configs {
+			assert!(!config.option().is_pointer());
+			assert_eq!(Config::from_dword(config.option(), config.to_dword()), config);
+		}
+	}
+
+	#[test]
+	fn validate_ranges() {
+		let valid = [
+			Config::UpdatePeriod(Duration::ZERO),
+			Config::UpdatePeriod(Duration::from_millis(5)),
+			Config::UpdatePeriod(Duration::from_millis(100)),
+			Config::Buffer(Duration::from_millis(1)),
+			Config::Buffer(Duration::from_secs(5)),
+			Config::DeviceBuffer(Duration::from_millis(u32::MAX as u64)),
+			Config::NetPrebuffer(100),
+			Config::NetAgent("bass-rs".into()),
+			Config::NetProxy(None),
+			Config::NetProxy(Some("user:pass@server:8080".into())),
+			Config::SrcQuality(0),
+			Config::UpdateThreads(0),
+			#[cfg(feature = "mixer")]
+			Config::MixerBuffer(5),
+		];
+		for config in valid {
+			assert_eq!(config.validate(), Ok(()), "{config:?}");
+		}
+
+		let invalid = [
+			Config::UpdatePeriod(Duration::from_millis(4)),
+			Config::UpdatePeriod(Duration::from_millis(101)),
+			Config::Buffer(Duration::ZERO),
+			Config::Buffer(Duration::from_millis(5001)),
+			Config::DeviceBuffer(Duration::from_millis(u32::MAX as u64 + 1)),
+			Config::NetTimeout(Duration::from_millis(u32::MAX as u64 + 1)),
+			Config::NetPrebuffer(101),
+			Config::NetAgent("bass\0rs".into()),
+			Config::NetProxy(Some("server\0:8080".into())),
+			Config::SrcQuality(5),
+			#[cfg(feature = "mixer")]
+			Config::MixerBuffer(0),
+			#[cfg(feature = "mixer")]
+			Config::MixerBuffer(6),
+		];
+		for config in invalid {
+			let error = config.validate().unwrap_err();
+			assert_eq!(error.code(), BassErrorCode::BassErrorIllParam, "{config:?}");
+		}
+
+		// Read-only.
+		assert_eq!(Config::Handles(0).validate().unwrap_err().code(), BassErrorCode::BassErrorNotAvailable);
+	}
+}
@@ -1,9 +1,385 @@
-use bass_sys::DWORD;
+use std::{
+	fmt::Debug,
+	marker::PhantomData,
+	os::raw::c_void,
+	ptr::null_mut,
+	sync::{Arc, Mutex, Weak},
+	time::Duration,
+};
 
-#[derive(Debug)]
+use bass_sys::*;
+
+use super::{
+	config::Config,
+	error::{BassError, BassErrorCode},
+	handles::{self, LeakReport},
+	init::InitOptions,
+};
+use crate::BassResult;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BassDeviceInfo {
+	/// The device's index, as passed to `Bass::init`/`Device::init` (or `BASS_RecordInit` for recording devices).
+	pub index: u32,
 	pub name: String,
 	pub driver: String,
-	#[allow(unused)]
+	/// The raw `BASS_DEVICE_*` flags, decoded into `status` and `device_type`.
+	pub flags: DWORD,
+	pub status: DeviceFlags,
+	pub device_type: DeviceType,
+}
+
+impl BassDeviceInfo {
+	pub(crate) fn new(index: u32, name: String, driver: String, flags: DWORD) -> Self {
+		BassDeviceInfo { index, name, driver, flags, status: flags.into(), device_type: flags.into() }
+	}
+}
+
+/// The state of a device, decoded from the `BASS_DEVICE_*` flags in `BASS_DEVICEINFO`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DeviceFlags {
+	/// The device is enabled. Devices that have been unplugged remain in the device list, but aren't enabled.
+	pub enabled: bool,
+	/// The device is the system default device.
+	pub default: bool,
+	/// The device is the system default communication device. Windows only.
+	pub default_communication: bool,
+	/// The device has been initialised.
+	pub initialised: bool,
+	/// The device is a loopback recording device, capturing the output of an output device.
+	pub loopback: bool,
+	/// The device is an input device.
+	pub input: bool,
+}
+
+impl From<DWORD> for DeviceFlags {
+	fn from(flags: DWORD) -> Self {
+		let has = |flag: DWORD| (flags & flag) == flag;
+		DeviceFlags {
+			enabled: has(BASS_DEVICE_ENABLED),
+			default: has(BASS_DEVICE_DEFAULT),
+			default_communication: has(BASS_DEVICE_DEFAULTCOM),
+			initialised: has(BASS_DEVICE_INIT),
+			loopback: has(BASS_DEVICE_LOOPBACK),
+			input: has(BASS_DEVICE_INPUT),
+		}
+	}
+}
+
+/// The type of a device, from the `BASS_DEVICE_TYPE_*` bits in `BASS_DEVICEINFO`.
+///
+/// This is only available on Windows (and partially on macOS); elsewhere it will be `Unknown`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DeviceType {
+	#[default]
+	Unknown,
+	Network,
+	Speakers,
+	Line,
+	Headphones,
+	Microphone,
+	Headset,
+	Handset,
+	Digital,
+	Spdif,
+	Hdmi,
+	DisplayPort,
+}
+
+impl From<DWORD> for DeviceType {
+	fn from(flags: DWORD) -> Self {
+		let device_type = flags & BASS_DEVICE_TYPE_MASK;
+		if device_type == BASS_DEVICE_TYPE_NETWORK {
+			DeviceType::Network
+		} else if device_type == BASS_DEVICE_TYPE_SPEAKERS {
+			DeviceType::Speakers
+		} else if device_type == BASS_DEVICE_TYPE_LINE {
+			DeviceType::Line
+		} else if device_type == BASS_DEVICE_TYPE_HEADPHONES {
+			DeviceType::Headphones
+		} else if device_type == BASS_DEVICE_TYPE_MICROPHONE {
+			DeviceType::Microphone
+		} else if device_type == BASS_DEVICE_TYPE_HEADSET {
+			DeviceType::Headset
+		} else if device_type == BASS_DEVICE_TYPE_HANDSET {
+			DeviceType::Handset
+		} else if device_type == BASS_DEVICE_TYPE_DIGITAL {
+			DeviceType::Digital
+		} else if device_type == BASS_DEVICE_TYPE_SPDIF {
+			DeviceType::Spdif
+		} else if device_type == BASS_DEVICE_TYPE_HDMI {
+			DeviceType::Hdmi
+		} else if device_type == BASS_DEVICE_TYPE_DISPLAYPORT {
+			DeviceType::DisplayPort
+		} else {
+			DeviceType::Unknown
+		}
+	}
+}
+
+/// Information about the current output device, from `BASS_GetInfo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputInfo {
+	/// The device's latency, if it was initialised with `InitOptions::latency`.
+	pub latency: Option<Duration>,
+	/// The minimum buffer length. Using a `Config::Buffer` below this can result in choppy output.
+	pub min_buffer: Duration,
+	/// The number of speakers the device supports.
+	pub speakers: u32,
+	/// The device's current output sample rate.
+	pub frequency: u32,
+	/// The device's capabilities (`DSCAPS_*`).
 	pub flags: DWORD,
-}
\ No newline at end of file
+	/// The `BASS_DEVICE_*` flags the device was initialised with.
+	pub init_flags: DWORD,
+}
+
+impl From<BASS_INFO> for OutputInfo {
+	fn from(info: BASS_INFO) -> Self {
+		let measured = (info.initflags & BASS_DEVICE_LATENCY) == BASS_DEVICE_LATENCY;
+		Self {
+			latency: measured.then(|| Duration::from_millis(info.latency.0 as u64)),
+			min_buffer: Duration::from_millis(info.minbuf.0 as u64),
+			speakers: info.speakers.0,
+			frequency: info.freq.0,
+			flags: info.flags,
+			init_flags: info.initflags,
+		}
+	}
+}
+
+/// Every output device initialised through this crate, so initialising a device twice hands out the same `Device`.
+static DEVICES: Mutex<Vec<(u32, Weak<DeviceInner>)>> = Mutex::new(Vec::new());
+
+/// A reference-counted handle to an initialised output device.
+///
+/// The device is freed when the last `Device` pointing at it is dropped. Channels created while a device is current
+/// hold a `Device` themselves, so the device will outlive them.
+#[derive(Clone)]
+pub struct Device(Arc<DeviceInner>);
+
+struct DeviceInner {
+	index: u32,
+}
+
+impl Device {
+	/// Initialises an output device, or returns the existing handle if it has already been initialised.
+	///
+	/// `device` is `-1` for the system default device, or an index from `Bass::devices`.
+	pub fn init(device: i32, frequency: u32, options: InitOptions) -> BassResult<Self> {
+		unsafe { Self::init_internal(device, frequency, options, null_mut()) }
+	}
+
+	/// # Safety
+	///
+	/// `window` must be a valid window handle (or null) for as long as the device is initialised.
+	pub unsafe fn init_window(
+		device: i32,
+		frequency: u32,
+		options: InitOptions,
+		window: *mut c_void,
+	) -> BassResult<Self> {
+		Self::init_internal(device, frequency, options, window)
+	}
+
+	unsafe fn init_internal(device: i32, frequency: u32, options: InitOptions, window: *mut c_void) -> BassResult<Self> {
+		let mut devices = DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+		let ok = BASS_Init(device, frequency, options.flags(), window, null_mut());
+		if !ok {
+			let error = BassError::get("Device::init").with_context(format!("device {device}"));
+			// Someone else already owns this device, so share it with them.
+			if error == BassErrorCode::BassErrorAlready {
+				let index = match device {
+					-1 => initialised_default(),
+					device => u32::try_from(device).ok(),
+				};
+				if let Some(existing) = index.and_then(|index| find(&devices, index)) {
+					return Ok(existing);
+				}
+			}
+			return Err(error);
+		}
+		// BASS_Init makes the new device current on this thread, which resolves -1 to the actual device.
+		let index = BASS_GetDevice().0;
+		BASS_SetConfig(BASS_CONFIG_UNICODE, TRUE);
+		if let Err(error) = config_after_init() {
+			// Nothing owns a newly initialised device yet, so it would never be freed.
+			if !devices.iter().any(|(i, weak)| *i == index && weak.strong_count() > 0) && !BASS_Free() {
+				BassError::consume();
+			}
+			return Err(error);
+		}
+		if let Some(existing) = find(&devices, index) {
+			// Reinitialised (`BASS_DEVICE_REINIT`) rather than newly initialised.
+			return Ok(existing);
+		}
+		let device = Device(Arc::new(DeviceInner { index }));
+		devices.retain(|(_, weak)| weak.strong_count() > 0);
+		devices.push((index, Arc::downgrade(&device.0)));
+		Ok(device)
+	}
+
+	/// The `Device` that is current on this thread, if it was initialised through this crate.
+	pub fn current() -> Option<Self> {
+		let index = BASS_GetDevice();
+		if index.0 as i32 == -1 {
+			BassError::consume();
+			return None;
+		}
+		find(&DEVICES.lock().unwrap_or_else(|e| e.into_inner()), index.0)
+	}
+
+	/// The `Device` a channel is playing on, if it was initialised through this crate.
+	///
+	/// Decoding channels aren't played on a device, so will return `None`.
+	pub(crate) fn of_channel(handle: impl Into<DWORD>) -> Option<Self> {
+		let index = BASS_ChannelGetDevice(handle.into());
+		if index.0 as i32 == -1 {
+			BassError::consume();
+			return None;
+		}
+		find(&DEVICES.lock().unwrap_or_else(|e| e.into_inner()), index.0)
+	}
+
+	pub fn index(&self) -> u32 {
+		self.0.index
+	}
+
+	/// Makes this device current on this thread until the returned guard is dropped,
+	/// at which point the previously current device is restored.
+	///
+	/// The current device is thread-local in BASS, so the guard can't be sent to another thread.
+	pub fn make_current(&self) -> BassResult<CurrentDevice<'_>> {
+		let previous = BASS_GetDevice().0;
+		if BASS_SetDevice(self.0.index) {
+			Ok(CurrentDevice { _device: self, previous, _not_send: PhantomData })
+		} else {
+			Err(BassError::get("Device::make_current").with_context(format!("device {}", self.0.index)))
+		}
+	}
+
+	/// The handles created through this crate that are still alive on this device.
+	///
+	/// The same report is printed (or logged, with the `log` feature) if any are left when the device is freed.
+	/// Handles are only recorded with the `track_handles` feature; without it the report is always empty.
+	pub fn leak_report(&self) -> LeakReport {
+		handles::report(self.0.index)
+	}
+
+	/// Information about this device.
+	pub fn info(&self) -> BassResult<OutputInfo> {
+		let _current = self.make_current()?;
+		let mut info = BASS_INFO::default();
+		let ok = BASS_GetInfo(&mut info);
+		if ok {
+			Ok(info.into())
+		} else {
+			Err(BassError::get("Device::info").with_context(format!("device {}", self.0.index)))
+		}
+	}
+}
+
+/// Applied to every device once it has been initialised.
+fn config_after_init() -> BassResult<()> {
+	super::config::set(Config::FloatDsp(true))
+}
+
+/// The device BASS initialised for `-1`. This isn't necessarily the current system default, which may have changed
+/// since, so BASS is asked to make it current and the index read back. The previously current device is restored.
+fn initialised_default() -> Option<u32> {
+	let previous = BASS_GetDevice().0;
+	if previous as i32 == -1 {
+		BassError::consume();
+	}
+	if !BASS_SetDevice(u32::MAX) {
+		BassError::consume();
+		return None;
+	}
+	let index = BASS_GetDevice().0;
+	if previous as i32 != -1 && previous != index && !BASS_SetDevice(previous) {
+		BassError::consume();
+	}
+	Some(index)
+}
+
+fn find(devices: &[(u32, Weak<DeviceInner>)], index: u32) -> Option<Device> {
+	devices.iter().filter(|(i, _)| *i == index).find_map(|(_, weak)| weak.upgrade()).map(Device)
+}
+
+impl Debug for Device {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_tuple("Device").field(&self.0.index).finish()
+	}
+}
+
+impl PartialEq for Device {
+	fn eq(&self, other: &Self) -> bool {
+		self.0.index == other.0.index
+	}
+}
+impl Eq for Device {}
+
+impl Drop for DeviceInner {
+	fn drop(&mut self) {
+		// Hold the registry so the device can't be handed out again while it's being freed.
+		let _devices = DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+		debug!("Freeing device {}", self.index);
+		handles::report_on_free(self.index);
+		let previous = BASS_GetDevice().0;
+		if BASS_SetDevice(self.index) {
+			if !BASS_Free() {
+				BassError::consume();
+			}
+			if previous != self.index && !BASS_SetDevice(previous) {
+				BassError::consume();
+			}
+		} else {
+			BassError::consume();
+		}
+	}
+}
+
+/// Guard returned by `Device::make_current`.
+#[derive(Debug)]
+pub struct CurrentDevice<'a> {
+	_device: &'a Device,
+	previous: u32,
+	/// `BASS_SetDevice` only affects the calling thread.
+	_not_send: PhantomData<*const ()>,
+}
+
+impl Drop for CurrentDevice<'_> {
+	fn drop(&mut self) {
+		if !BASS_SetDevice(self.previous) {
+			BassError::consume();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn flags() {
+		let status = DeviceFlags::from(BASS_DEVICE_ENABLED | BASS_DEVICE_DEFAULT | BASS_DEVICE_INIT);
+		assert_eq!(status, DeviceFlags { enabled: true, default: true, initialised: true, ..DeviceFlags::default() });
+		assert_eq!(DeviceFlags::from(DWORD(0)), DeviceFlags::default());
+		let status = DeviceFlags::from(BASS_DEVICE_ENABLED | BASS_DEVICE_LOOPBACK | BASS_DEVICE_INPUT);
+		assert!(status.loopback && status.input && !status.default);
+	}
+
+	#[test]
+	fn device_type() {
+		// The type is read from its own bits, whatever else is set.
+		let flags = BASS_DEVICE_TYPE_HEADPHONES | BASS_DEVICE_ENABLED | BASS_DEVICE_DEFAULT | BASS_DEVICE_INIT;
+		assert_eq!(DeviceType::from(flags), DeviceType::Headphones);
+		assert_eq!(DeviceType::from(BASS_DEVICE_TYPE_HDMI), DeviceType::Hdmi);
+		assert_eq!(DeviceType::from(BASS_DEVICE_TYPE_SPEAKERS | BASS_DEVICE_ENABLED), DeviceType::Speakers);
+		assert_eq!(DeviceType::from(BASS_DEVICE_ENABLED), DeviceType::Unknown);
+
+		let info = BassDeviceInfo::new(1, "Speakers".into(), "driver".into(), BASS_DEVICE_TYPE_SPEAKERS);
+		assert!(!info.status.enabled);
+		assert_eq!(info.device_type, DeviceType::Speakers);
+	}
+}
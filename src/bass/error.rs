@@ -5,7 +5,7 @@ use bass_sys::BASS_ErrorGetCode;
 use bass_sys::{BASS_ERROR_ALREADY, BASS_ERROR_BUFLOST, BASS_ERROR_BUSY, BASS_ERROR_CODEC, BASS_ERROR_CREATE, BASS_ERROR_DECODE, BASS_ERROR_DEVICE, BASS_ERROR_DRIVER, BASS_ERROR_DX, BASS_ERROR_EMPTY, BASS_ERROR_ENDED, BASS_ERROR_FILEFORM, BASS_ERROR_FILEOPEN, BASS_ERROR_FORMAT, BASS_ERROR_FREQ, BASS_ERROR_HANDLE, BASS_ERROR_ILLPARAM, BASS_ERROR_ILLTYPE, BASS_ERROR_INIT, BASS_ERROR_MEM, BASS_ERROR_NO3D, BASS_ERROR_NOCHAN, BASS_ERROR_NOEAX, BASS_ERROR_NOFX, BASS_ERROR_NOHW, BASS_ERROR_NONET, BASS_ERROR_NOPLAY, BASS_ERROR_NOTAUDIO, BASS_ERROR_NOTAVAIL, BASS_ERROR_NOTFILE, BASS_ERROR_POSITION, BASS_ERROR_PROTOCOL, BASS_ERROR_REINIT, BASS_ERROR_SPEAKER, BASS_ERROR_SSL, BASS_ERROR_START, BASS_ERROR_TIMEOUT, BASS_ERROR_UNKNOWN, BASS_ERROR_UNSTREAMABLE, BASS_ERROR_VERSION, BASS_OK, DWORD};
 use thiserror::Error;
 
-#[derive(Debug, Error, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq, Hash)]
 #[repr(u32)]
 pub enum BassErrorCode {
 	#[error("All is OK")]
@@ -93,8 +93,8 @@ pub enum BassErrorCode {
 }
 
 impl From<c_int> for BassErrorCode {
-    fn from(value: c_int) -> Self {
-        match value as u32 {
+	fn from(value: c_int) -> Self {
+		match value as u32 {
 			BASS_OK => Self::BassOk,
 			BASS_ERROR_MEM => Self::BassErrorMem,
 			BASS_ERROR_FILEOPEN => Self::BassErrorFileOpen,
@@ -138,55 +138,188 @@ impl From<c_int> for BassErrorCode {
 			// BASS_ERROR_UNKNOWN => Self::BassErrorUnknown,
 			_ => Self::BassErrorUnknown,
 		}
-    }
+	}
 }
 
 impl From<DWORD> for BassErrorCode {
-    fn from(value: DWORD) -> Self {
-        (value.0 as i32).into()
-    }
+	fn from(value: DWORD) -> Self {
+		(value.0 as i32).into()
+	}
+}
+
+impl BassErrorCode {
+	/// Takes the error code of the last BASS function called on this thread.
+	pub fn last() -> Self {
+		BASS_ErrorGetCode().into()
+	}
+
+	pub fn class(&self) -> ErrorClass {
+		match self {
+			BassErrorCode::BassErrorTimeout
+			| BassErrorCode::BassErrorNoNet
+			| BassErrorCode::BassErrorBusy
+			| BassErrorCode::BassErrorNoChan
+			| BassErrorCode::BassErrorNoHw
+			| BassErrorCode::BassErrorMem => ErrorClass::Retryable,
+			BassErrorCode::BassErrorReInit
+			| BassErrorCode::BassErrorBufferLost
+			| BassErrorCode::BassErrorInit
+			| BassErrorCode::BassErrorStart
+			| BassErrorCode::BassErrorDriver
+			| BassErrorCode::BassErrorDx => ErrorClass::NeedsReinit,
+			BassErrorCode::BassErrorHandle
+			| BassErrorCode::BassErrorIllParam
+			| BassErrorCode::BassErrorIllType
+			| BassErrorCode::BassErrorPosition
+			| BassErrorCode::BassErrorAlready
+			| BassErrorCode::BassErrorDecode
+			| BassErrorCode::BassErrorNotFile
+			| BassErrorCode::BassErrorDevice => ErrorClass::ProgrammerError,
+			_ => ErrorClass::Other,
+		}
+	}
+}
+
+/// A rough classification of errors, for deciding what to do about them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+	/// A temporary condition (eg. a network timeout or a busy device); trying again later may succeed.
+	Retryable,
+	/// The output device has been lost, or hasn't been (re)initialised/started.
+	NeedsReinit,
+	/// The call itself was wrong, eg. an invalid handle or parameter. Retrying won't help.
+	ProgrammerError,
+	/// Anything else, such as an unsupported file or missing feature.
+	Other,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// An error from BASS, along with which operation failed and what it was operating on.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BassError {
-	pub code: i32,
+	code: BassErrorCode,
+	operation: &'static str,
+	handle: Option<DWORD>,
+	context: Option<String>,
 }
 
 impl BassError {
-	pub fn get() -> BassErrorCode {
-		BASS_ErrorGetCode().into()
+	/// Takes the error code of the last BASS function called on this thread, for the failed `operation`.
+	pub fn get(operation: &'static str) -> Self {
+		Self::new(BassErrorCode::last(), operation)
+	}
+
+	pub fn new(code: BassErrorCode, operation: &'static str) -> Self {
+		BassError { code, operation, handle: None, context: None }
 	}
 
+	/// Discards the error code of the last BASS function called on this thread.
 	pub fn consume() {
 		let _ = BASS_ErrorGetCode();
 	}
 
-	pub fn result<T>() -> Result<T, BassError> {
-		Err(BassError::default())
+	/// The handle the operation was called on.
+	pub fn with_handle(mut self, handle: impl Into<DWORD>) -> Self {
+		self.handle = Some(handle.into());
+		self
 	}
 
-	pub fn unknown() -> BassError {
-		BassError { code: -1 }
+	/// Any other details of the call, such as the path of a file being opened.
+	pub fn with_context(mut self, context: impl Into<String>) -> Self {
+		self.context = Some(context.into());
+		self
+	}
+
+	pub fn code(&self) -> BassErrorCode {
+		self.code
+	}
+
+	pub fn operation(&self) -> &'static str {
+		self.operation
+	}
+
+	pub fn handle(&self) -> Option<DWORD> {
+		self.handle
 	}
-}
 
-impl Default for BassError {
-	fn default() -> Self {
-		let code = BASS_ErrorGetCode();
-		Self { code }
+	pub fn context(&self) -> Option<&str> {
+		self.context.as_deref()
+	}
+
+	pub fn class(&self) -> ErrorClass {
+		self.code.class()
+	}
+
+	pub fn is_retryable(&self) -> bool {
+		self.class() == ErrorClass::Retryable
+	}
+
+	pub fn needs_reinit(&self) -> bool {
+		self.class() == ErrorClass::NeedsReinit
+	}
+
+	pub fn is_programmer_error(&self) -> bool {
+		self.class() == ErrorClass::ProgrammerError
 	}
 }
 
 impl Display for BassError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "BASS Failed with code: {}", self.code)
+		write!(f, "{}", self.operation)?;
+		if let Some(handle) = self.handle {
+			write!(f, " (handle {:#x})", handle.0)?;
+		}
+		if let Some(context) = &self.context {
+			write!(f, " [{}]", context)?;
+		}
+		write!(f, " failed: {}", self.code)
 	}
 }
 
-impl Error for BassError {}
+impl Error for BassError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		Some(&self.code)
+	}
+}
+
+impl PartialEq<BassErrorCode> for BassError {
+	fn eq(&self, other: &BassErrorCode) -> bool {
+		self.code == *other
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn display() {
+		let error = BassError::new(BassErrorCode::BassErrorFileOpen, "Stream::create_file");
+		assert_eq!(error.to_string(), "Stream::create_file failed: Can't open the file");
+		let error = error.with_handle(DWORD(0x8000_0001)).with_context("song.mp3");
+		assert_eq!(error.to_string(), "Stream::create_file (handle 0x80000001) [song.mp3] failed: Can't open the file");
+		assert_eq!(error.source().map(|source| source.to_string()), Some(String::from("Can't open the file")));
+	}
+
+	#[test]
+	fn classes() {
+		let class = |code: BassErrorCode| BassError::new(code, "test").class();
+		assert_eq!(class(BassErrorCode::BassErrorTimeout), ErrorClass::Retryable);
+		assert_eq!(class(BassErrorCode::BassErrorBusy), ErrorClass::Retryable);
+		assert_eq!(class(BassErrorCode::BassErrorReInit), ErrorClass::NeedsReinit);
+		assert_eq!(class(BassErrorCode::BassErrorInit), ErrorClass::NeedsReinit);
+		assert_eq!(class(BassErrorCode::BassErrorHandle), ErrorClass::ProgrammerError);
+		assert_eq!(class(BassErrorCode::BassErrorIllParam), ErrorClass::ProgrammerError);
+		assert_eq!(class(BassErrorCode::BassErrorFileForm), ErrorClass::Other);
+		assert_eq!(class(BassErrorCode::BassErrorUnknown), ErrorClass::Other);
+
+		let error = BassError::new(BassErrorCode::BassErrorNoNet, "test");
+		assert!(error.is_retryable() && !error.needs_reinit() && !error.is_programmer_error());
+	}
 
-impl From<BassErrorCode> for BassError {
-	fn from(value: BassErrorCode) -> Self {
-		BassError { code: value as i32 }
+	#[test]
+	fn from_code() {
+		assert_eq!(BassErrorCode::from(BASS_ERROR_ILLPARAM as c_int), BassErrorCode::BassErrorIllParam);
+		assert_eq!(BassErrorCode::from(-1), BassErrorCode::BassErrorUnknown);
+		assert_eq!(BassErrorCode::from(DWORD(BASS_ERROR_ENDED)), BassErrorCode::BassErrorEnded);
 	}
 }
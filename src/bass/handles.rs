@@ -0,0 +1,168 @@
+use std::{backtrace::Backtrace, fmt::Display, sync::Arc};
+#[cfg(feature = "track_handles")]
+use std::sync::Mutex;
+
+use bass_sys::DWORD;
+#[cfg(feature = "track_handles")]
+use bass_sys::{BASS_ChannelGetDevice, BASS_NODEVICE};
+
+#[cfg(feature = "track_handles")]
+use super::error::BassError;
+
+/// Every handle created through this crate that hasn't been freed yet, in the order they were created.
+#[cfg(feature = "track_handles")]
+static LIVE: Mutex<Vec<LiveHandle>> = Mutex::new(Vec::new());
+
+/// The kinds of handle the crate creates and frees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+	Stream,
+	PushStream,
+	DummyStream,
+	Music,
+	Sample,
+	Mixer,
+	Splitter,
+	Sync,
+	Dsp,
+	Plugin,
+}
+
+impl HandleKind {
+	/// Whether the handle is a channel, which can be a mixer source.
+	#[cfg_attr(not(feature = "track_handles"), allow(dead_code))]
+	fn is_channel(self) -> bool {
+		!matches!(self, HandleKind::Sample | HandleKind::Sync | HandleKind::Dsp | HandleKind::Plugin)
+	}
+}
+
+impl Display for HandleKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		std::fmt::Debug::fmt(self, f)
+	}
+}
+
+/// A handle that was created through this crate and hasn't been freed yet.
+#[derive(Clone, Debug)]
+pub struct LiveHandle {
+	pub kind: HandleKind,
+	pub handle: DWORD,
+	/// The channel a sync or DSP is set on.
+	pub channel: Option<DWORD>,
+	/// The device the handle (or its channel) was created on. `None` for decoding channels and plugins.
+	pub device: Option<u32>,
+	/// Where the handle was created.
+	pub backtrace: Arc<Backtrace>,
+}
+
+impl Display for LiveHandle {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {:#x}", self.kind, self.handle.0)?;
+		if let Some(channel) = self.channel {
+			write!(f, " on channel {:#x}", channel.0)?;
+		}
+		if let Some(device) = self.device {
+			write!(f, " (device {device})")?;
+		}
+		write!(f, ", created at:\n{}", self.backtrace)
+	}
+}
+
+/// The handles still alive on a device, as it's being freed.
+#[derive(Clone, Debug)]
+pub struct LeakReport {
+	pub device: u32,
+	pub handles: Vec<LiveHandle>,
+}
+
+impl LeakReport {
+	pub fn is_empty(&self) -> bool {
+		self.handles.is_empty()
+	}
+}
+
+impl Display for LeakReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} handle(s) still alive on device {}", self.handles.len(), self.device)?;
+		for handle in &self.handles {
+			write!(f, "\n\n{handle}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Records a newly created handle. `channel` is the channel a sync or DSP was set on.
+pub(crate) fn register(kind: HandleKind, handle: impl Into<DWORD>, channel: Option<DWORD>) {
+	#[cfg(feature = "track_handles")]
+	{
+		let handle = handle.into();
+		let device = device_of(channel.unwrap_or(handle));
+		let backtrace = Arc::new(Backtrace::force_capture());
+		LIVE.lock().unwrap_or_else(|e| e.into_inner()).push(LiveHandle { kind, handle, channel, device, backtrace });
+	}
+	#[cfg(not(feature = "track_handles"))]
+	let _ = (kind, handle.into(), channel);
+}
+
+/// Forgets a handle that is about to be freed. This must be called before the handle is freed.
+pub(crate) fn release(kind: HandleKind, handle: impl Into<DWORD>) {
+	#[cfg(feature = "track_handles")]
+	{
+		let handle = handle.into();
+		#[cfg(feature = "mixer")]
+		if kind.is_channel() {
+			if bass_sys::BASS_Mixer_ChannelGetMixer(handle) != 0 {
+				complain(format_args!("{kind} {:#x} was freed while still a mixer source", handle.0));
+			} else {
+				BassError::consume();
+			}
+		}
+		let mut live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+		if let Some(index) = live.iter().position(|live| live.kind == kind && live.handle == handle) {
+			live.remove(index);
+		}
+	}
+	#[cfg(not(feature = "track_handles"))]
+	let _ = (kind, handle.into());
+}
+
+/// Every handle that is still alive. This is always empty without the `track_handles` feature.
+pub(crate) fn live() -> Vec<LiveHandle> {
+	#[cfg(feature = "track_handles")]
+	return LIVE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+	#[cfg(not(feature = "track_handles"))]
+	Vec::new()
+}
+
+pub(crate) fn report(device: u32) -> LeakReport {
+	LeakReport { device, handles: live().into_iter().filter(|live| live.device == Some(device)).collect() }
+}
+
+/// Reports anything still alive on a device that is being freed.
+pub(crate) fn report_on_free(device: u32) {
+	let report = report(device);
+	if !report.is_empty() {
+		complain(&report);
+	}
+}
+
+/// Leaks are reported even without the `log` feature, as tracking handles has to be asked for.
+fn complain(message: impl Display) {
+	#[cfg(feature = "log")]
+	log::warn!("{message}");
+	#[cfg(not(feature = "log"))]
+	eprintln!("{message}");
+}
+
+#[cfg(feature = "track_handles")]
+fn device_of(handle: DWORD) -> Option<u32> {
+	let device = BASS_ChannelGetDevice(handle);
+	if device.0 as i32 == -1 {
+		BassError::consume();
+		None
+	} else if device.0 == *BASS_NODEVICE {
+		None
+	} else {
+		Some(device.0)
+	}
+}
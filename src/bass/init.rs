@@ -0,0 +1,107 @@
+use bass_sys::*;
+
+/// Options passed to `BASS_Init` when initialising an output device.
+///
+/// ```ignore
+/// let bass = Bass::init(-1, 48000, InitOptions::new().stereo().latency())?;
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InitOptions {
+	flags: DWORD,
+}
+
+/// How BASS should treat the speaker arrangement reported by the device.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpeakerAssignment {
+	/// Use the speaker arrangement the device reports.
+	#[default]
+	Detect,
+	/// Force the enabling of speaker assignment, even if the device doesn't report any.
+	Force,
+	/// Ignore speaker arrangement entirely.
+	Ignore,
+	/// Take the number of speakers from the Windows control panel setting rather than from the device
+	/// (`BASS_DEVICE_CPSPEAKERS`). Only used on Windows.
+	CopySpeakers,
+}
+
+impl InitOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Mono output. Otherwise the device's own speaker count is used.
+	pub fn mono(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_MONO;
+		self
+	}
+
+	/// Limit the output to stereo.
+	pub fn stereo(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_STEREO;
+		self
+	}
+
+	/// Limit the output to 16-bit.
+	pub fn bits_16(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_16BITS;
+		self
+	}
+
+	/// Set the device's output rate to the `frequency` passed to `Bass::init`, rather than leaving it as it is.
+	pub fn exact_frequency(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_FREQ;
+		self
+	}
+
+	/// Measure the device's latency, which is then available from `Bass::info`. This will slow initialisation down.
+	pub fn latency(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_LATENCY;
+		self
+	}
+
+	pub fn speakers(mut self, assignment: SpeakerAssignment) -> Self {
+		self.flags = self.flags & !(BASS_DEVICE_SPEAKERS | BASS_DEVICE_NOSPEAKER | BASS_DEVICE_CPSPEAKERS);
+		self.flags = match assignment {
+			SpeakerAssignment::Detect => self.flags,
+			SpeakerAssignment::Force => self.flags | BASS_DEVICE_SPEAKERS,
+			SpeakerAssignment::Ignore => self.flags | BASS_DEVICE_NOSPEAKER,
+			SpeakerAssignment::CopySpeakers => self.flags | BASS_DEVICE_CPSPEAKERS,
+		};
+		self
+	}
+
+	/// Linux only. Initialise the device using the ALSA "dmix" plugin, so it can be shared with other applications.
+	pub fn dmix(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_DMIX;
+		self
+	}
+
+	/// Reinitialise a device that has already been initialised, keeping its channels.
+	pub fn reinit(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_REINIT;
+		self
+	}
+
+	/// Enable 3D functionality, which is needed by channels created with `BASS_SAMPLE_3D`.
+	pub fn enable_3d(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_3D;
+		self
+	}
+
+	/// Use BASS's own software mixing rather than the platform's.
+	pub fn software(mut self) -> Self {
+		self.flags = self.flags | BASS_DEVICE_SOFTWARE;
+		self
+	}
+
+	/// Any other `BASS_DEVICE_*` flags not covered by the other methods.
+	pub fn with_flags(mut self, flags: DWORD) -> Self {
+		self.flags = self.flags | flags;
+		self
+	}
+
+	pub fn flags(&self) -> DWORD {
+		self.flags
+	}
+}
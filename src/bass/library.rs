@@ -0,0 +1,338 @@
+use std::{
+	env::consts::{DLL_PREFIX, DLL_SUFFIX},
+	error::Error,
+	fmt::Display,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+use bass_sys::*;
+
+/// The BASS version this crate is written against.
+pub const TARGET_VERSION: Version = Version { major: 2, minor: 4, revision: 17, build: 0 };
+
+/// Directories the BASS libraries and plugins are in, set with `Bass::set_library_search_path`. Plugins are looked up
+/// in it, and the linked libraries are checked against it.
+static SEARCH_PATH: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// A BASS library version, as returned by `BASS_GetVersion` and the add-on equivalents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+	pub major: u8,
+	pub minor: u8,
+	pub revision: u8,
+	pub build: u8,
+}
+
+impl From<DWORD> for Version {
+	fn from(value: DWORD) -> Self {
+		let [major, minor, revision, build] = value.0.to_be_bytes();
+		Version { major, minor, revision, build }
+	}
+}
+
+impl Display for Version {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}.{}.{}.{}", self.major, self.minor, self.revision, self.build)
+	}
+}
+
+/// The libraries this crate links against, depending on which features are enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Library {
+	Bass,
+	#[cfg(feature = "mixer")]
+	Mixer,
+	#[cfg(feature = "loudness")]
+	Loudness,
+	#[cfg(feature = "cd")]
+	Cd,
+}
+
+impl Library {
+	/// Every library linked with the enabled features.
+	pub fn linked() -> Vec<Library> {
+		#[allow(unused_mut)]
+		let mut libraries = vec![Library::Bass];
+		#[cfg(feature = "mixer")]
+		libraries.push(Library::Mixer);
+		#[cfg(feature = "loudness")]
+		libraries.push(Library::Loudness);
+		#[cfg(feature = "cd")]
+		libraries.push(Library::Cd);
+		libraries
+	}
+
+	/// The library's name, without the platform's prefix and suffix.
+	pub fn name(self) -> &'static str {
+		match self {
+			Library::Bass => "bass",
+			#[cfg(feature = "mixer")]
+			Library::Mixer => "bassmix",
+			#[cfg(feature = "loudness")]
+			Library::Loudness => "bassloud",
+			#[cfg(feature = "cd")]
+			Library::Cd => "basscd",
+		}
+	}
+
+	/// The library's file name on this platform, eg. "libbass.so" or "bass.dll".
+	pub fn file_name(self) -> String {
+		format!("{DLL_PREFIX}{}{DLL_SUFFIX}", self.name())
+	}
+
+	/// A function exported by the library, used to find where it was loaded from.
+	#[cfg_attr(not(unix), allow(dead_code))]
+	fn symbol(self) -> &'static [u8] {
+		match self {
+			Library::Bass => b"BASS_GetVersion\0",
+			#[cfg(feature = "mixer")]
+			Library::Mixer => b"BASS_Mixer_GetVersion\0",
+			#[cfg(feature = "loudness")]
+			Library::Loudness => b"BASS_Loudness_GetVersion\0",
+			#[cfg(feature = "cd")]
+			Library::Cd => b"BASS_CD_GetVersion\0",
+		}
+	}
+
+	pub fn version(self) -> Version {
+		match self {
+			Library::Bass => BASS_GetVersion().into(),
+			#[cfg(feature = "mixer")]
+			Library::Mixer => BASS_Mixer_GetVersion().into(),
+			#[cfg(feature = "loudness")]
+			Library::Loudness => BASS_Loudness_GetVersion().into(),
+			#[cfg(feature = "cd")]
+			Library::Cd => BASS_CD_GetVersion().into(),
+		}
+	}
+
+	/// Whether `version` of this library can be used with this crate.
+	///
+	/// BASS and its add-ons are compatible across a major.minor version. BASS itself also has to be at least the
+	/// revision this crate targets, as earlier revisions are missing functions the crate uses.
+	pub fn is_compatible(self, version: Version) -> bool {
+		let same_series = version.major == TARGET_VERSION.major && version.minor == TARGET_VERSION.minor;
+		match self {
+			Library::Bass => same_series && version.revision >= TARGET_VERSION.revision,
+			#[allow(unreachable_patterns)]
+			_ => same_series,
+		}
+	}
+}
+
+impl Display for Library {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// A library that has been loaded, and where it was loaded from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LibraryInfo {
+	pub library: Library,
+	pub version: Version,
+	/// `None` if the path couldn't be determined on this platform.
+	pub path: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LibraryError {
+	/// The library that was loaded isn't a version this crate can use.
+	VersionMismatch { found: LibraryInfo, expected: Version },
+	/// A search path has been set, but the library was loaded from somewhere else.
+	UnexpectedLocation { found: LibraryInfo, search_path: Vec<PathBuf> },
+}
+
+impl Display for LibraryError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			LibraryError::VersionMismatch { found, expected } => write!(
+				f,
+				"{} {} loaded from {} is not compatible with the targeted version {}",
+				found.library,
+				found.version,
+				display_path(&found.path),
+				expected
+			),
+			LibraryError::UnexpectedLocation { found, search_path } => write!(
+				f,
+				"{} {} was loaded from {}, which is not in the search path {:?}",
+				found.library,
+				found.version,
+				display_path(&found.path),
+				search_path
+			),
+		}
+	}
+}
+
+impl Error for LibraryError {}
+
+fn display_path(path: &Option<PathBuf>) -> String {
+	match path {
+		Some(path) => path.display().to_string(),
+		None => String::from("an unknown location"),
+	}
+}
+
+pub(crate) fn set_search_path(paths: Vec<PathBuf>) {
+	*SEARCH_PATH.lock().unwrap_or_else(|e| e.into_inner()) = paths;
+}
+
+pub(crate) fn search_path() -> Vec<PathBuf> {
+	SEARCH_PATH.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Looks for a relative path in each directory of the search path, returning the first that exists, or the path
+/// unchanged if none does (or it's absolute).
+pub(crate) fn locate(path: &Path) -> PathBuf {
+	if path.is_relative() {
+		if let Some(found) = search_path().iter().map(|directory| directory.join(path)).find(|path| path.is_file()) {
+			return found;
+		}
+	}
+	path.to_path_buf()
+}
+
+pub(crate) fn info(library: Library) -> LibraryInfo {
+	LibraryInfo { library, version: library.version(), path: loaded_path(library) }
+}
+
+pub(crate) fn validate() -> Result<Vec<LibraryInfo>, LibraryError> {
+	let search_path = search_path();
+	Library::linked()
+		.into_iter()
+		.map(|library| {
+			let found = info(library);
+			if !library.is_compatible(found.version) {
+				return Err(LibraryError::VersionMismatch { found, expected: TARGET_VERSION });
+			}
+			if let Some(path) = &found.path {
+				let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+				let in_search_path = search_path.iter().any(|directory| {
+					canonical.starts_with(directory.canonicalize().unwrap_or_else(|_| directory.clone()))
+				});
+				if !search_path.is_empty() && !in_search_path {
+					return Err(LibraryError::UnexpectedLocation { found, search_path: search_path.clone() });
+				}
+			}
+			Ok(found)
+		})
+		.collect()
+}
+
+/// Finds the file a library was actually loaded from, by looking up one of its exported functions.
+#[cfg(unix)]
+fn loaded_path(library: Library) -> Option<PathBuf> {
+	use std::{
+		ffi::{c_char, c_int, c_void, CStr, OsStr},
+		os::unix::ffi::OsStrExt,
+	};
+
+	#[repr(C)]
+	struct DlInfo {
+		dli_fname: *const c_char,
+		dli_fbase: *mut c_void,
+		dli_sname: *const c_char,
+		dli_saddr: *mut c_void,
+	}
+
+	extern "C" {
+		fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+		fn dladdr(address: *const c_void, info: *mut DlInfo) -> c_int;
+	}
+
+	#[cfg(any(target_os = "linux", target_os = "android"))]
+	let default_handle = std::ptr::null_mut::<c_void>();
+	#[cfg(not(any(target_os = "linux", target_os = "android")))]
+	let default_handle = -2isize as *mut c_void;
+
+	unsafe {
+		let address = dlsym(default_handle, library.symbol().as_ptr() as *const c_char);
+		if address.is_null() {
+			return None;
+		}
+		let mut info = DlInfo {
+			dli_fname: std::ptr::null(),
+			dli_fbase: std::ptr::null_mut(),
+			dli_sname: std::ptr::null(),
+			dli_saddr: std::ptr::null_mut(),
+		};
+		if dladdr(address, &mut info) == 0 || info.dli_fname.is_null() {
+			return None;
+		}
+		Some(PathBuf::from(OsStr::from_bytes(CStr::from_ptr(info.dli_fname).to_bytes())))
+	}
+}
+
+/// Finds the file a library was actually loaded from, by looking up the loaded module with its file name.
+#[cfg(windows)]
+fn loaded_path(library: Library) -> Option<PathBuf> {
+	use std::{
+		ffi::{c_void, OsString},
+		os::windows::ffi::OsStringExt,
+	};
+
+	use widestring::U16CString;
+
+	extern "system" {
+		fn GetModuleHandleW(name: *const u16) -> *mut c_void;
+		fn GetModuleFileNameW(module: *mut c_void, file_name: *mut u16, size: u32) -> u32;
+	}
+
+	let name = U16CString::from_str(library.file_name()).ok()?;
+	unsafe {
+		let module = GetModuleHandleW(name.as_ptr());
+		if module.is_null() {
+			return None;
+		}
+		// The path is truncated if it doesn't fit, so grow the buffer until it does.
+		let mut buffer = vec![0u16; 260];
+		loop {
+			let length = GetModuleFileNameW(module, buffer.as_mut_ptr(), buffer.len() as u32) as usize;
+			if length == 0 {
+				return None;
+			}
+			if length < buffer.len() {
+				return Some(PathBuf::from(OsString::from_wide(&buffer[..length])));
+			}
+			buffer.resize(buffer.len() * 2, 0);
+		}
+	}
+}
+
+#[cfg(not(any(unix, windows)))]
+fn loaded_path(_library: Library) -> Option<PathBuf> {
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn version_from_dword() {
+		// BASS_GetVersion packs the version as 0xAABBCCDD for "A.B.C.D".
+		let version = Version::from(DWORD(0x0204_1102));
+		assert_eq!(version, Version { major: 2, minor: 4, revision: 17, build: 2 });
+		assert_eq!(version.to_string(), "2.4.17.2");
+		assert!(Version::from(DWORD(0x0204_1100)) < version);
+	}
+
+	#[test]
+	fn compatibility() {
+		let version = |revision: u8| Version { revision, ..TARGET_VERSION };
+		assert!(Library::Bass.is_compatible(TARGET_VERSION));
+		assert!(Library::Bass.is_compatible(Version { build: 9, ..version(18) }));
+		assert!(!Library::Bass.is_compatible(version(16)));
+		assert!(!Library::Bass.is_compatible(Version { minor: 5, ..TARGET_VERSION }));
+		assert!(!Library::Bass.is_compatible(Version { major: 3, minor: 4, revision: 17, build: 0 }));
+		#[cfg(feature = "mixer")]
+		{
+			// Add-ons only have to be in the same series.
+			assert!(Library::Mixer.is_compatible(version(0)));
+			assert!(!Library::Mixer.is_compatible(Version { minor: 3, ..TARGET_VERSION }));
+		}
+	}
+}
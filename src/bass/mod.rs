@@ -1,48 +1,149 @@
 use std::{
-	ffi::{CStr, CString},
+	ffi::CStr,
 	os::raw::c_void,
-	ptr::{null, null_mut},
-	slice,
+	path::{Path, PathBuf},
+	ptr::null,
 };
 
+pub mod config;
 pub mod device;
 pub mod error;
+pub mod handles;
+pub mod init;
+pub mod library;
+pub mod monitor;
 pub mod plugin;
 
 use bass_sys::*;
-use device::BassDeviceInfo;
+use config::{Config, ConfigOption};
+use device::{BassDeviceInfo, Device, OutputInfo};
 use error::{BassError, BassErrorCode};
-use plugin::{Plugin, PluginFormat, PluginInfo};
+use handles::{LeakReport, LiveHandle};
+use init::InitOptions;
+use library::{LibraryError, LibraryInfo};
+use plugin::{Plugin, PluginInfo};
 
-use crate::BassResult;
+use crate::{
+	spatial::{Factors3d, Listener},
+	BassResult,
+};
 
-#[derive(Debug)]
-pub struct Bass;
+/// The output device BASS was initialised with, along with functions that apply to BASS as a whole.
+///
+/// Dropping a `Bass` only frees its own device, and only once every other `Device` handle (and every channel) created
+/// on it has also been dropped.
+#[derive(Debug, Clone)]
+pub struct Bass {
+	device: Device,
+}
 
 impl Bass {
-	pub fn init(device: i32, frequency: u32, flags: Option<DWORD>) -> BassResult<Self> {
-		let ok = unsafe { BASS_Init(device, frequency, flags.unwrap_or(DWORD(0)), null_mut(), null_mut()) };
-		if ok {
-			BASS_SetConfig(BASS_CONFIG_UNICODE, TRUE);
-			BASS_SetConfig(BASS_CONFIG_FLOATDSP, TRUE);
-			Ok(Bass)
-		} else {
-			Err(BassError::get())
-		}
+	pub fn init(device: i32, frequency: u32, options: InitOptions) -> BassResult<Self> {
+		Ok(Bass { device: Device::init(device, frequency, options)? })
 	}
 
+	/// # Safety
+	///
+	/// `window` must be a valid window handle (or null) for as long as the device is initialised.
 	pub unsafe fn init_window(
 		device: i32,
 		frequency: u32,
-		flags: Option<DWORD>,
+		options: InitOptions,
 		window: *mut c_void,
 	) -> BassResult<Self> {
-		let ok = BASS_Init(device, frequency, flags.unwrap_or(DWORD(0)), window, null_mut());
-		if ok {
-			Ok(Bass)
-		} else {
-			Err(BassError::get())
-		}
+		Ok(Bass { device: Device::init_window(device, frequency, options, window)? })
+	}
+
+	/// The output device this `Bass` was initialised with.
+	pub fn output(&self) -> &Device {
+		&self.device
+	}
+
+	/// Information about the output device this `Bass` was initialised with.
+	pub fn info(&self) -> BassResult<OutputInfo> {
+		self.device.info()
+	}
+
+	/// Every handle created through this crate that hasn't been freed yet, with where it was created.
+	///
+	/// Handles are only recorded with the `track_handles` feature; without it this is always empty.
+	pub fn live_handles() -> Vec<LiveHandle> {
+		handles::live()
+	}
+
+	/// The handles still alive on this `Bass`'s output device. See `Device::leak_report`.
+	pub fn leak_report(&self) -> LeakReport {
+		self.device.leak_report()
+	}
+
+	/// Gets the current value of a global config option.
+	///
+	/// Config options are global, so this doesn't require BASS to have been initialised.
+	pub fn config(option: ConfigOption) -> BassResult<Config> {
+		config::get(option)
+	}
+
+	/// Sets a global config option, after checking the value is within the range BASS accepts.
+	///
+	/// Some options (such as `Config::DeviceBuffer` and `Config::UpdateThreads`) only take effect for devices initialised
+	/// afterwards, so this doesn't require BASS to have been initialised.
+	pub fn set_config(config: Config) -> BassResult<()> {
+		config::set(config)
+	}
+
+	/// Checks the BASS library (and the add-ons enabled by this crate's features) that have been loaded are versions
+	/// this crate can be used with, and are in the library search path if one has been set.
+	///
+	/// This is best called once at startup, so a mismatched library is reported clearly rather than through odd
+	/// runtime errors.
+	pub fn validate_libraries() -> Result<Vec<LibraryInfo>, LibraryError> {
+		library::validate()
+	}
+
+	/// The version and location of the BASS library and the add-ons enabled by this crate's features.
+	pub fn libraries() -> Vec<LibraryInfo> {
+		library::Library::linked().into_iter().map(library::info).collect()
+	}
+
+	/// Sets the directories the BASS libraries and plugins are in.
+	///
+	/// `PluginRegistry::load` looks for relative paths in these directories first. BASS and its add-ons are linked
+	/// when the program starts, before this can be called, so instead `Bass::validate_libraries` checks that they were
+	/// loaded from one of them. An empty search path means the libraries can be loaded from anywhere.
+	pub fn set_library_search_path(paths: impl IntoIterator<Item = impl Into<PathBuf>>) {
+		library::set_search_path(paths.into_iter().map(Into::into).collect())
+	}
+
+	/// The position and orientation of the listener on this `Bass`'s output device.
+	pub fn listener(&self) -> BassResult<Listener> {
+		let _current = self.device.make_current()?;
+		Listener::get()
+	}
+
+	/// Moves the listener. This only takes effect after `apply_3d`.
+	pub fn set_listener(&self, listener: Listener) -> BassResult<()> {
+		let _current = self.device.make_current()?;
+		listener.set()
+	}
+
+	pub fn factors_3d(&self) -> BassResult<Factors3d> {
+		let _current = self.device.make_current()?;
+		Factors3d::get()
+	}
+
+	/// Sets how distances, rolloff and the doppler effect are calculated. This only takes effect after `apply_3d`.
+	pub fn set_factors_3d(&self, factors: Factors3d) -> BassResult<()> {
+		let _current = self.device.make_current()?;
+		factors.set()
+	}
+
+	/// Applies the changes made to the listener, the 3D factors, and channels' 3D attributes and positions.
+	///
+	/// Changes are batched up until this is called, so call it once per update rather than after each change.
+	pub fn apply_3d(&self) -> BassResult<()> {
+		let _current = self.device.make_current()?;
+		BASS_Apply3D();
+		Ok(())
 	}
 
 	pub fn cpu(&self) -> f32 {
@@ -57,17 +158,17 @@ impl Bass {
 		if BASS_SetDevice(device) {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Bass::set_device").with_context(format!("device {device}")))
 		}
 	}
 
-	fn devices_internal(print: bool) -> Vec<BassDeviceInfo> {
+	fn devices_internal(get_info: impl Fn(u32, &mut BASS_DEVICEINFO) -> bool) -> Vec<BassDeviceInfo> {
 		let mut devices: Vec<BassDeviceInfo> = Vec::new();
 		for i in 0..*BASS_NODEVICE {
 			// let mut info = BASS_DEVICEINFO::new(NULL as *const c_char, NULL as *const c_char, 0);
 			let mut info = BASS_DEVICEINFO::new(null(), null(), 0);
 			// let infoptr = Box::into_raw(Box::new(info));
-			let ok = BASS_GetDeviceInfo(i, &mut info);
+			let ok = get_info(i, &mut info);
 			if !ok {
 				BassError::consume();
 				break;
@@ -76,21 +177,15 @@ impl Bass {
 			// Aaaahhh the intricacies of interacting with C...
 			unsafe {
 				// let info = *Box::from_raw(infoptr);
-				let mut better_info = BassDeviceInfo { name: String::new(), driver: String::new(), flags: info.flags };
+				let mut better_info = BassDeviceInfo::new(i, String::new(), String::new(), info.flags);
 				if !info.name.is_null() {
 					let name = CStr::from_ptr(info.name);
-					if print {
-						println!("Device: {:#?}", name)
-					}
 					if let Ok(name) = name.to_str() {
 						better_info.name = String::from(name);
 					}
 				}
 				if !info.driver.is_null() {
 					let driver = CStr::from_ptr(info.driver);
-					if print {
-						println!("Driver: {:#?}", driver)
-					}
 					if let Ok(driver) = driver.to_str() {
 						better_info.driver = String::from(driver);
 					}
@@ -98,68 +193,48 @@ impl Bass {
 				devices.push(better_info);
 			}
 		}
-		if print {
-			println!("Devices: {:#?}", devices)
-		}
 		return devices;
 	}
 
 	pub fn devices() -> Vec<BassDeviceInfo> {
-		Self::devices_internal(false)
+		Self::devices_internal(|i, info| BASS_GetDeviceInfo(i, info))
 	}
 
-	pub fn get_error(&self) -> BassErrorCode {
-		BassError::get()
+	pub fn recording_devices() -> Vec<BassDeviceInfo> {
+		Self::devices_internal(|i, info| BASS_RecordGetDeviceInfo(i, info))
 	}
 
-	fn free(&self) {
-		for i in 1..*BASS_NODEVICE {
-			println!("Freeing device: {}", i);
-			let ok = BASS_SetDevice(i);
-			if !ok {
-				let code = BassError::get();
-				match code {
-					// Device is invalid -> We've reached the end of the dvice list and can stop
-					BassErrorCode::BassErrorDevice => {
-						break;
-					}
-					// BASS_Init was never called on this device -> continue.
-					BassErrorCode::BassErrorInit => (),
-					_ => (),
-				}
-			}
-			let ok = BASS_Free();
-			if !ok {
-				let code = BassError::get();
-				match code {
-					BassErrorCode::BassErrorBusy => {
-						panic!("BASS attempted to free a device that was in the process of being reinitialised.");
-					}
-					BassErrorCode::BassErrorInit => (),
-					_ => (),
-				}
-				break;
-			}
-		}
+	/// The system default output device.
+	pub fn default_device() -> Option<BassDeviceInfo> {
+		Self::devices().into_iter().find(|device| device.status.default && device.status.enabled)
 	}
 
-	pub fn load_plugin(&self, plugin: &str) -> BassResult<Plugin> {
-		let file = CString::new(plugin);
-		match file {
-			Ok(file) => {
-				let ok = unsafe { BASS_PluginLoad(file.as_ptr(), 0) };
-				if ok == 0 {
-					Err(BassError::get())
-				} else {
-					Ok(plugin::plugin(ok))
-				}
-			}
-			Err(_) => Err(BassError::get()),
-		}
+	/// The system default recording device.
+	pub fn default_recording_device() -> Option<BassDeviceInfo> {
+		Self::recording_devices().into_iter().find(|device| device.status.default && device.status.enabled)
 	}
 
+	/// Finds an enabled output device by name, preferring an exact match over a case-insensitive partial match.
+	pub fn find_device(name: &str) -> Option<BassDeviceInfo> {
+		find_device_by_name(Self::devices(), name)
+	}
+
+	/// Finds an enabled recording device by name, preferring an exact match over a case-insensitive partial match.
+	pub fn find_recording_device(name: &str) -> Option<BassDeviceInfo> {
+		find_device_by_name(Self::recording_devices(), name)
+	}
+
+	pub fn get_error(&self) -> BassErrorCode {
+		BassErrorCode::last()
+	}
+
+	pub fn load_plugin(&self, plugin: impl AsRef<Path>) -> BassResult<Plugin> {
+		Plugin::load(plugin)
+	}
+
+	/// Equivalent to dropping `plugin`.
 	pub fn unload_plugin(&self, plugin: Plugin) {
-		BASS_PluginFree(plugin.0);
+		drop(plugin);
 	}
 
 	/// This doesn't require any checking because any `Plugin` that exists has a valid handle.
@@ -173,33 +248,49 @@ impl Bass {
 	}
 
 	pub fn plugin_info(&self, plugin: &Plugin) -> BassResult<PluginInfo> {
-		let mut info = PluginInfo::default();
-		let ok = BASS_PluginGetInfo(plugin.0);
-		if ok == null() {
-			Err(BassError::get())
-		} else {
-			let bass = unsafe { *ok };
-			info.version = bass.version.0;
-			// let formats = Vec::<PluginFormat>::with_capacity(bass.formatc.0 as usize);
-			let slice = unsafe { slice::from_raw_parts(bass.formats, bass.formatc.0 as usize) };
-			let vec: Vec<PluginFormat> = slice
-				.iter()
-				.map(|plugin| {
-					let name = String::from(unsafe { CStr::from_ptr(plugin.name) }.to_string_lossy());
-					let exts = String::from(unsafe { CStr::from_ptr(plugin.exts) }.to_string_lossy());
-					PluginFormat { channel_type: plugin.ctype, name, exts }
-				})
-				.collect();
-			info.formats = vec;
-			Ok(info)
+		plugin.info()
+	}
+}
+
+fn find_device_by_name(devices: Vec<BassDeviceInfo>, name: &str) -> Option<BassDeviceInfo> {
+	let needle = name.to_lowercase();
+	let mut partial = None;
+	for device in devices.into_iter().filter(|device| device.status.enabled) {
+		if device.name == name {
+			return Some(device);
+		}
+		if partial.is_none() && device.name.to_lowercase().contains(&needle) {
+			partial = Some(device);
 		}
 	}
+	partial
 }
 
-impl Drop for Bass {
-	fn drop(&mut self) {
-		#[cfg(debug_assertions)]
-		println!("Dropping BASS");
-		self.free();
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn device(index: u32, name: &str, enabled: bool) -> BassDeviceInfo {
+		let flags = if enabled { BASS_DEVICE_ENABLED } else { DWORD(0) };
+		BassDeviceInfo::new(index, name.into(), String::new(), flags)
+	}
+
+	#[test]
+	fn device_by_name() {
+		let devices = || {
+			vec![
+				device(1, "USB Speakers", true),
+				device(2, "Speakers", false),
+				device(3, "Speakers", true),
+				device(4, "speakers (HDMI)", true),
+			]
+		};
+		let index = |name: &str| find_device_by_name(devices(), name).map(|device| device.index);
+		// An exact match wins over an earlier partial one, and disabled devices are skipped.
+		assert_eq!(index("Speakers"), Some(3));
+		// Otherwise the first case-insensitive partial match.
+		assert_eq!(index("SPEAKERS"), Some(1));
+		assert_eq!(index("hdmi"), Some(4));
+		assert_eq!(index("Headphones"), None);
 	}
 }
@@ -0,0 +1,325 @@
+use std::{
+	collections::VecDeque,
+	fmt::Debug,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		mpsc::{self, Receiver},
+		Arc, Mutex, TryLockError,
+	},
+	thread::{self, JoinHandle},
+	time::Duration,
+};
+
+use bass_sys::{BASS_SYNC_DEV_FAIL, BASS_SYNC_DEV_FORMAT, BASS_SYNC_THREAD};
+
+use super::{
+	device::{BassDeviceInfo, Device},
+	Bass,
+};
+use crate::{channel::ChannelExt, stream::device::DeviceStream, sync::BassSync, BassResult};
+
+/// Whether a device is used for playback or recording.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceKind {
+	Output,
+	Recording,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeviceEvent {
+	/// A device has been plugged in or enabled.
+	Added { kind: DeviceKind, index: u32, info: BassDeviceInfo },
+	/// A device has been unplugged or disabled. BASS keeps it in the device list, so its index won't be reused.
+	Removed { kind: DeviceKind, index: u32, info: BassDeviceInfo },
+	/// The system default device has changed to this one.
+	DefaultChanged { kind: DeviceKind, index: u32, info: BassDeviceInfo },
+	/// An initialised output device has stopped unexpectedly (`BASS_SYNC_DEV_FAIL`).
+	Failed { index: u32 },
+	/// An initialised output device's sample format has changed (`BASS_SYNC_DEV_FORMAT`).
+	FormatChanged { index: u32 },
+}
+
+type Sink = Arc<EventSink>;
+
+/// Where events go. They are queued and passed on by whichever thread holds the callback, so the callback can poll
+/// (or be called from a sync while it's running) without deadlocking.
+struct EventSink {
+	queue: Mutex<VecDeque<DeviceEvent>>,
+	callback: Mutex<Box<dyn FnMut(DeviceEvent) + Send + 'static>>,
+}
+
+struct Snapshot {
+	output: Vec<BassDeviceInfo>,
+	recording: Vec<BassDeviceInfo>,
+}
+
+impl Snapshot {
+	fn take() -> Self {
+		Snapshot { output: Bass::devices(), recording: Bass::recording_devices() }
+	}
+}
+
+/// Watches for devices being added, removed or becoming the default, by comparing successive device enumerations.
+///
+/// Failures of initialised output devices can be added to the same stream of events with `DeviceMonitor::watch`.
+pub struct DeviceMonitor {
+	sink: Sink,
+	snapshot: Arc<Mutex<Snapshot>>,
+	stop: Arc<AtomicBool>,
+	thread: Option<JoinHandle<()>>,
+	watched: Vec<WatchedDevice>,
+}
+
+/// Holds the syncs for a watched device. The syncs are dropped before the stream they are set on.
+struct WatchedDevice {
+	_syncs: [BassSync<Sink>; 2],
+	_stream: DeviceStream,
+	device: Device,
+}
+
+impl DeviceMonitor {
+	/// Events are passed to `callback`, which may be called from a BASS or monitor thread.
+	pub fn new(callback: impl FnMut(DeviceEvent) + Send + 'static) -> Self {
+		DeviceMonitor {
+			sink: Arc::new(EventSink { queue: Mutex::new(VecDeque::new()), callback: Mutex::new(Box::new(callback)) }),
+			snapshot: Arc::new(Mutex::new(Snapshot::take())),
+			stop: Arc::new(AtomicBool::new(false)),
+			thread: None,
+			watched: Vec::new(),
+		}
+	}
+
+	/// Equivalent to `DeviceMonitor::new`, with events sent to the returned `Receiver`.
+	pub fn channel() -> (Self, Receiver<DeviceEvent>) {
+		let (tx, rx) = mpsc::channel();
+		let monitor = Self::new(move |event| {
+			tx.send(event).ok();
+		});
+		(monitor, rx)
+	}
+
+	/// Re-enumerates the devices immediately, emitting any changes since the last enumeration.
+	pub fn poll(&self) {
+		poll(&self.snapshot, &self.sink);
+	}
+
+	/// Re-enumerates the devices every `interval` on a background thread, until the monitor is dropped.
+	///
+	/// Calling this again replaces the previous thread.
+	pub fn start(&mut self, interval: Duration) {
+		self.stop_thread();
+		self.stop.store(false, Ordering::Release);
+		let snapshot = self.snapshot.clone();
+		let sink = self.sink.clone();
+		let stop = self.stop.clone();
+		self.thread = Some(thread::spawn(move || {
+			while !stop.load(Ordering::Acquire) {
+				thread::park_timeout(interval);
+				if stop.load(Ordering::Acquire) {
+					break;
+				}
+				poll(&snapshot, &sink);
+			}
+		}));
+	}
+
+	/// Emits `DeviceEvent::Failed` and `DeviceEvent::FormatChanged` for an initialised output device.
+	///
+	/// The monitor keeps the device initialised for as long as it's being watched.
+	pub fn watch(&mut self, device: &Device) -> BassResult<()> {
+		if self.watched.iter().any(|watched| watched.device == *device) {
+			return Ok(());
+		}
+		let stream = {
+			let _current = device.make_current()?;
+			DeviceStream::get()
+		};
+		let index = device.index();
+		let failed = stream.set_sync(
+			BASS_SYNC_DEV_FAIL | BASS_SYNC_THREAD,
+			0,
+			move |sink: &mut Sink, _, _, _| emit(sink, DeviceEvent::Failed { index }),
+			self.sink.clone(),
+		)?;
+		let format = stream.set_sync(
+			BASS_SYNC_DEV_FORMAT | BASS_SYNC_THREAD,
+			0,
+			move |sink: &mut Sink, _, _, _| emit(sink, DeviceEvent::FormatChanged { index }),
+			self.sink.clone(),
+		)?;
+		self.watched.push(WatchedDevice { _syncs: [failed, format], _stream: stream, device: device.clone() });
+		Ok(())
+	}
+
+	/// Stops emitting failure events for a device, allowing it to be freed.
+	pub fn unwatch(&mut self, device: &Device) {
+		self.watched.retain(|watched| watched.device != *device);
+	}
+
+	fn stop_thread(&mut self) {
+		if let Some(thread) = self.thread.take() {
+			self.stop.store(true, Ordering::Release);
+			thread.thread().unpark();
+			thread.join().ok();
+		}
+	}
+}
+
+impl Debug for DeviceMonitor {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("DeviceMonitor")
+			.field("running", &self.thread.is_some())
+			.field("watched", &self.watched.iter().map(|watched| watched.device.index()).collect::<Vec<_>>())
+			.finish()
+	}
+}
+
+impl Drop for DeviceMonitor {
+	fn drop(&mut self) {
+		self.stop_thread();
+	}
+}
+
+fn emit(sink: &EventSink, event: DeviceEvent) {
+	sink.queue.lock().unwrap_or_else(|e| e.into_inner()).push_back(event);
+	loop {
+		let mut callback = match sink.callback.try_lock() {
+			Ok(callback) => callback,
+			Err(TryLockError::Poisoned(e)) => e.into_inner(),
+			// Whoever holds it passes the event on.
+			Err(TryLockError::WouldBlock) => return,
+		};
+		loop {
+			// Taken in its own statement so the queue isn't locked while the callback runs and can add to it.
+			let next = sink.queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front();
+			let Some(event) = next else { break };
+			callback(event);
+		}
+		drop(callback);
+		// Another thread may have queued an event after the queue was emptied but before the callback was released.
+		if sink.queue.lock().unwrap_or_else(|e| e.into_inner()).is_empty() {
+			return;
+		}
+	}
+}
+
+fn poll(snapshot: &Mutex<Snapshot>, sink: &EventSink) {
+	let current = Snapshot::take();
+	let events = {
+		let mut previous = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+		let mut events = diff(DeviceKind::Output, &previous.output, &current.output);
+		events.extend(diff(DeviceKind::Recording, &previous.recording, &current.recording));
+		*previous = current;
+		events
+	};
+	// Emitted after releasing the snapshot, as a callback that polls takes it again. Its events are queued by `emit`.
+	for event in events {
+		emit(sink, event);
+	}
+}
+
+fn is_enabled(info: &BassDeviceInfo) -> bool {
+	info.status.enabled
+}
+
+fn is_default(info: &BassDeviceInfo) -> bool {
+	info.status.default && info.status.enabled
+}
+
+/// BASS never reorders its device list: removed devices stay where they were with `BASS_DEVICE_ENABLED` unset,
+/// and new devices are appended. So devices can be compared index by index.
+fn diff(kind: DeviceKind, before: &[BassDeviceInfo], after: &[BassDeviceInfo]) -> Vec<DeviceEvent> {
+	let mut events = Vec::new();
+	for (index, info) in after.iter().enumerate() {
+		let was_enabled = before.get(index).map_or(false, is_enabled);
+		if is_enabled(info) && !was_enabled {
+			events.push(DeviceEvent::Added { kind, index: index as u32, info: info.clone() });
+		} else if !is_enabled(info) && was_enabled {
+			events.push(DeviceEvent::Removed { kind, index: index as u32, info: info.clone() });
+		}
+	}
+	for (index, info) in before.iter().enumerate().skip(after.len()) {
+		if is_enabled(info) {
+			events.push(DeviceEvent::Removed { kind, index: index as u32, info: info.clone() });
+		}
+	}
+	let old_default = before.iter().position(is_default);
+	let new_default = after.iter().position(is_default);
+	if let Some(index) = new_default {
+		if old_default != new_default {
+			events.push(DeviceEvent::DefaultChanged { kind, index: index as u32, info: after[index].clone() });
+		}
+	}
+	events
+}
+
+#[cfg(test)]
+mod tests {
+	use bass_sys::{BASS_DEVICE_DEFAULT, BASS_DEVICE_ENABLED, DWORD};
+
+	use super::*;
+
+	fn devices(flags: &[DWORD]) -> Vec<BassDeviceInfo> {
+		flags
+			.iter()
+			.enumerate()
+			.map(|(index, flags)| BassDeviceInfo::new(index as u32, format!("Device {index}"), String::new(), *flags))
+			.collect()
+	}
+
+	fn summary(events: Vec<DeviceEvent>) -> Vec<(&'static str, u32)> {
+		events
+			.into_iter()
+			.map(|event| match event {
+				DeviceEvent::Added { index, .. } => ("added", index),
+				DeviceEvent::Removed { index, .. } => ("removed", index),
+				DeviceEvent::DefaultChanged { index, .. } => ("default", index),
+				DeviceEvent::Failed { index } => ("failed", index),
+				DeviceEvent::FormatChanged { index } => ("format", index),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn diff_devices() {
+		let (off, on, default) = (DWORD(0), BASS_DEVICE_ENABLED, BASS_DEVICE_ENABLED | BASS_DEVICE_DEFAULT);
+		let before = devices(&[default, on, on]);
+		let same = diff(DeviceKind::Output, &before, &before);
+		assert_eq!(same, []);
+
+		let after = devices(&[on, off, default, on]);
+		let events = diff(DeviceKind::Output, &before, &after);
+		assert!(events.iter().all(|event| matches!(
+			event,
+			DeviceEvent::Added { kind: DeviceKind::Output, .. }
+				| DeviceEvent::Removed { kind: DeviceKind::Output, .. }
+				| DeviceEvent::DefaultChanged { kind: DeviceKind::Output, .. }
+		)));
+		assert_eq!(summary(events), [("removed", 1), ("added", 3), ("default", 2)]);
+
+		// Re-enabled, and the list shrinking.
+		let events = diff(DeviceKind::Recording, &after, &devices(&[default, on]));
+		assert_eq!(summary(events), [("added", 1), ("removed", 2), ("removed", 3), ("default", 0)]);
+	}
+
+	#[test]
+	fn reentrant_emit() {
+		let sink: Sink =
+			Arc::new(EventSink { queue: Mutex::new(VecDeque::new()), callback: Mutex::new(Box::new(|_| {})) });
+		let (tx, rx) = mpsc::channel();
+		// Weak, so the callback doesn't keep its own sink alive.
+		let inner = Arc::downgrade(&sink);
+		*sink.callback.lock().unwrap() = Box::new(move |event| {
+			if let (DeviceEvent::Failed { index }, Some(inner)) = (&event, inner.upgrade()) {
+				emit(&inner, DeviceEvent::FormatChanged { index: *index });
+			}
+			tx.send(event).unwrap();
+		});
+		emit(&sink, DeviceEvent::Failed { index: 1 });
+		assert_eq!(
+			rx.try_iter().collect::<Vec<_>>(),
+			[DeviceEvent::Failed { index: 1 }, DeviceEvent::FormatChanged { index: 1 }]
+		);
+		assert_eq!(Arc::strong_count(&sink), 1);
+	}
+}
@@ -1,24 +1,82 @@
-use bass_sys::{BASS_PluginFree, DWORD, HPLUGIN};
+use std::{
+	collections::HashMap,
+	env::consts::DLL_SUFFIX,
+	ffi::CStr,
+	fs,
+	io,
+	os::raw::c_char,
+	path::{Path, PathBuf},
+	slice,
+};
 
+use bass_sys::{BASS_PluginFree, BASS_PluginGetInfo, BASS_PluginLoad, DWORD, HPLUGIN};
+
+use super::{
+	error::BassError,
+	handles::{self, HandleKind},
+	library,
+};
+use crate::{file_name::FileName, BassResult};
+
+/// A loaded plugin. The plugin is freed when this is dropped.
+#[derive(Debug)]
 pub struct Plugin(pub(crate) HPLUGIN);
 
-pub(crate) fn plugin(handle: HPLUGIN) -> Plugin {
-	Plugin(handle)
+impl Plugin {
+	/// Paths are passed to BASS as they are, so ones that aren't valid Unicode can be loaded too.
+	pub fn load(path: impl AsRef<Path>) -> BassResult<Self> {
+		let path = path.as_ref();
+		let file = FileName::new(path);
+		let handle = unsafe { BASS_PluginLoad(file.as_ptr() as *const c_char, file.flags()) };
+		if handle != 0 {
+			handles::register(HandleKind::Plugin, handle, None);
+			Ok(Plugin(handle))
+		} else {
+			Err(BassError::get("Plugin::load").with_context(path.display().to_string()))
+		}
+	}
+
+	pub fn info(&self) -> BassResult<PluginInfo> {
+		plugin_info(self.0)
+	}
+}
+
+/// Gets the info of any plugin handle, such as the one in `BASS_CHANNELINFO`, without taking ownership of it.
+pub(crate) fn plugin_info(handle: HPLUGIN) -> BassResult<PluginInfo> {
+	let mut info = PluginInfo::default();
+	let ok = BASS_PluginGetInfo(handle);
+	if ok.is_null() {
+		Err(BassError::get("Plugin::info").with_handle(handle))
+	} else {
+		let bass = unsafe { *ok };
+		info.version = bass.version.0;
+		let slice = unsafe { slice::from_raw_parts(bass.formats, bass.formatc.0 as usize) };
+		info.formats = slice
+			.iter()
+			.map(|plugin| {
+				let name = String::from(unsafe { CStr::from_ptr(plugin.name) }.to_string_lossy());
+				let exts = String::from(unsafe { CStr::from_ptr(plugin.exts) }.to_string_lossy());
+				PluginFormat { channel_type: plugin.ctype, name, exts }
+			})
+			.collect();
+		Ok(info)
+	}
 }
 
 impl Drop for Plugin {
-    fn drop(&mut self) {
-        BASS_PluginFree(self.0);
-    }
+	fn drop(&mut self) {
+		handles::release(HandleKind::Plugin, self.0);
+		BASS_PluginFree(self.0);
+	}
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct PluginInfo {
 	pub version: u32,
 	pub formats: Vec<PluginFormat>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct PluginFormat {
 	/// The channel type, as would appear in the BASS_CHANNELINFO structure.
 	pub channel_type: DWORD,
@@ -26,4 +84,191 @@ pub struct PluginFormat {
 	pub name: String,
 	/// File extension filter, in the form of "*.ext1;*.ext2;...".
 	pub exts: String,
-}
\ No newline at end of file
+}
+
+impl PluginFormat {
+	/// The file extensions in `exts`, lowercased and without the "*." prefix.
+	pub fn extensions(&self) -> Vec<String> {
+		self.exts
+			.split(';')
+			.map(|ext| ext.trim().trim_start_matches('*').trim_start_matches('.').to_lowercase())
+			.filter(|ext| !ext.is_empty())
+			.collect()
+	}
+}
+
+/// A plugin owned by a `PluginRegistry`.
+#[derive(Debug)]
+pub struct LoadedPlugin {
+	/// The canonical path the plugin was loaded from.
+	pub path: PathBuf,
+	pub info: PluginInfo,
+	plugin: Plugin,
+}
+
+impl LoadedPlugin {
+	pub fn plugin(&self) -> &Plugin {
+		&self.plugin
+	}
+}
+
+/// The plugin and format that would be used to open a file.
+#[derive(Clone, Copy, Debug)]
+pub struct PluginMatch<'a> {
+	pub plugin: &'a LoadedPlugin,
+	pub format: &'a PluginFormat,
+}
+
+/// Owns a set of loaded plugins, indexed by the file extensions of the formats they support.
+///
+/// Each plugin is freed exactly once, when it's unloaded from the registry or the registry is dropped.
+#[derive(Debug, Default)]
+pub struct PluginRegistry {
+	plugins: Vec<LoadedPlugin>,
+	/// Lowercase extension -> (index into `plugins`, index into that plugin's formats).
+	extensions: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl PluginRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Loads a single plugin. Loading a plugin that's already in the registry, by any path to it, does nothing.
+	///
+	/// A relative path is looked for in the library search path (see `Bass::set_library_search_path`) first.
+	pub fn load(&mut self, path: impl AsRef<Path>) -> BassResult<&LoadedPlugin> {
+		let path = library::locate(path.as_ref());
+		let canonical = canonical(&path);
+		if let Some(index) = self.plugins.iter().position(|loaded| loaded.path == canonical) {
+			return Ok(&self.plugins[index]);
+		}
+		let plugin = Plugin::load(&path)?;
+		let info = plugin.info()?;
+		self.plugins.push(LoadedPlugin { path: canonical, info, plugin });
+		self.reindex();
+		Ok(&self.plugins[self.plugins.len() - 1])
+	}
+
+	/// Loads every shared library in `directory` as a plugin.
+	///
+	/// Files that BASS refuses to load (eg. BASS itself, or add-ons that aren't plugins) are returned along with the
+	/// reason, rather than stopping the rest from loading.
+	pub fn load_directory(&mut self, directory: impl AsRef<Path>) -> io::Result<Vec<(PathBuf, BassError)>> {
+		let mut failed = Vec::new();
+		for path in library_files(directory.as_ref())? {
+			if let Err(error) = self.load(&path) {
+				failed.push((path, error));
+			}
+		}
+		Ok(failed)
+	}
+
+	/// Unloads (and frees) a plugin. Returns `false` if it wasn't in the registry.
+	pub fn unload(&mut self, path: impl AsRef<Path>) -> bool {
+		let path = canonical(&library::locate(path.as_ref()));
+		match self.plugins.iter().position(|loaded| loaded.path == path) {
+			Some(index) => {
+				self.plugins.remove(index);
+				self.reindex();
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub fn plugins(&self) -> &[LoadedPlugin] {
+		&self.plugins
+	}
+
+	/// The plugin in the registry with the given handle, such as the `plugin` of a channel's info.
+	pub fn find_by_handle(&self, handle: HPLUGIN) -> Option<&LoadedPlugin> {
+		self.plugins.iter().find(|loaded| loaded.plugin.0 == handle)
+	}
+
+	/// The plugin and format that would handle a file, based on its extension.
+	///
+	/// If several plugins claim the extension, the first one loaded wins, as that's the order BASS tries them in.
+	pub fn find_for(&self, path: impl AsRef<Path>) -> Option<PluginMatch<'_>> {
+		let extension = path.as_ref().extension()?.to_string_lossy().to_lowercase();
+		self.find_for_extension(&extension)
+	}
+
+	pub fn find_for_extension(&self, extension: &str) -> Option<PluginMatch<'_>> {
+		let extension = extension.trim_start_matches('.').to_lowercase();
+		let (plugin, format) = *self.extensions.get(&extension)?.first()?;
+		let plugin = &self.plugins[plugin];
+		Some(PluginMatch { plugin, format: &plugin.info.formats[format] })
+	}
+
+	/// Every supported extension, lowercase and without a leading ".", sorted.
+	pub fn extensions(&self) -> Vec<&str> {
+		let mut extensions = self.extensions.keys().map(String::as_str).collect::<Vec<_>>();
+		extensions.sort_unstable();
+		extensions
+	}
+
+	/// Each format's name with its extensions, eg. for building file picker filters.
+	pub fn file_filters(&self) -> Vec<(&str, Vec<String>)> {
+		self.plugins
+			.iter()
+			.flat_map(|loaded| loaded.info.formats.iter())
+			.map(|format| (format.name.as_str(), format.extensions()))
+			.collect()
+	}
+
+	fn reindex(&mut self) {
+		self.extensions.clear();
+		for (plugin, loaded) in self.plugins.iter().enumerate() {
+			for (format, plugin_format) in loaded.info.formats.iter().enumerate() {
+				for extension in plugin_format.extensions() {
+					self.extensions.entry(extension).or_default().push((plugin, format));
+				}
+			}
+		}
+	}
+}
+
+/// Resolves `.`, `..` and links, so that different paths to the same plugin are recognised. Paths that can't be
+/// resolved (like ones that don't exist) are left as they are.
+fn canonical(path: &Path) -> PathBuf {
+	path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The shared libraries in a directory, sorted. Extensions are matched case-insensitively, as "BASSFLAC.DLL" is as
+/// loadable as "bassflac.dll" on Windows.
+fn library_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+	let suffix = DLL_SUFFIX.to_lowercase();
+	let mut paths = fs::read_dir(directory)?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|path| path.is_file() && path.to_string_lossy().to_lowercase().ends_with(&suffix))
+		.collect::<Vec<_>>();
+	paths.sort();
+	Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn plugin_directories() {
+		let directory = std::env::temp_dir().join(format!("bass-rs-plugins-{}", std::process::id()));
+		fs::create_dir_all(directory.join(format!("folder{DLL_SUFFIX}"))).unwrap();
+		for name in [format!("b{DLL_SUFFIX}"), format!("a{}", DLL_SUFFIX.to_uppercase()), "c.txt".to_string()] {
+			fs::write(directory.join(name), []).unwrap();
+		}
+
+		let files = library_files(&directory);
+		let canonical_paths = (
+			canonical(&directory.join(format!("folder{DLL_SUFFIX}")).join("..").join(format!("b{DLL_SUFFIX}"))),
+			canonical(&directory.join(format!("b{DLL_SUFFIX}"))),
+		);
+		fs::remove_dir_all(&directory).unwrap();
+
+		let names: Vec<_> =
+			files.unwrap().iter().map(|path| path.file_name().unwrap().to_string_lossy().into_owned()).collect();
+		assert_eq!(names, [format!("a{}", DLL_SUFFIX.to_uppercase()), format!("b{DLL_SUFFIX}")]);
+		assert_eq!(canonical_paths.0, canonical_paths.1);
+	}
+}
@@ -0,0 +1,73 @@
+use bass_sys::*;
+
+use super::{data::channel_info, handle::HasHandle, info::ChannelType, Channel};
+use crate::{
+	bass::error::BassError,
+	music::MaybeMusic,
+	recording::RecordingStream,
+	sample::SampleChannel,
+	stream::maybe::MaybeStream,
+	BassResult,
+};
+#[cfg(feature = "mixer")]
+use crate::{mixer::MaybeMixer, split::MaybeSplitter};
+
+/// Any channel handle, classified into the typed wrapper for its kind, such as the handles from `Mixer::channels`
+/// or `Sample::channels`.
+///
+/// None of the wrappers own their channel, so dropping them doesn't free it.
+#[derive(Debug)]
+pub enum AnyChannel {
+	Sample(SampleChannel),
+	Recording(RecordingStream),
+	Music(MaybeMusic),
+	#[cfg(feature = "mixer")]
+	Mixer(MaybeMixer),
+	#[cfg(feature = "mixer")]
+	Splitter(MaybeSplitter),
+	/// Any other stream: a file, URL, user, push, dummy or plugin stream.
+	Stream(MaybeStream),
+}
+
+impl AnyChannel {
+	/// Classifies a handle by its channel type. Fails with `BASS_ERROR_HANDLE` if it isn't a channel.
+	pub fn classify(handle: impl Into<DWORD>) -> BassResult<Self> {
+		let handle = handle.into();
+		let info = channel_info(handle, "AnyChannel::classify")?;
+		Ok(match ChannelType::from_raw(info.ctype, info.plugin) {
+			ChannelType::Sample => AnyChannel::Sample(SampleChannel(HCHANNEL(handle))),
+			ChannelType::Recording => AnyChannel::Recording(RecordingStream(HRECORD(handle))),
+			ChannelType::Music { .. } => AnyChannel::Music(MaybeMusic(HMUSIC(handle))),
+			#[cfg(feature = "mixer")]
+			ChannelType::Mixer => AnyChannel::Mixer(MaybeMixer(HSTREAM(handle))),
+			#[cfg(feature = "mixer")]
+			ChannelType::Splitter => AnyChannel::Splitter(MaybeSplitter(HSTREAM(handle))),
+			_ => AnyChannel::Stream(MaybeStream(HSTREAM(handle))),
+		})
+	}
+}
+
+impl TryFrom<DWORD> for AnyChannel {
+	type Error = BassError;
+
+	fn try_from(value: DWORD) -> Result<Self, Self::Error> {
+		AnyChannel::classify(value)
+	}
+}
+
+impl HasHandle for AnyChannel {
+	fn handle(&self) -> DWORD {
+		match self {
+			AnyChannel::Sample(channel) => channel.handle(),
+			AnyChannel::Recording(channel) => channel.handle(),
+			AnyChannel::Music(channel) => channel.handle(),
+			#[cfg(feature = "mixer")]
+			AnyChannel::Mixer(channel) => channel.handle(),
+			#[cfg(feature = "mixer")]
+			AnyChannel::Splitter(channel) => channel.handle(),
+			AnyChannel::Stream(channel) => channel.handle(),
+		}
+	}
+}
+
+impl Channel for AnyChannel {}
@@ -0,0 +1,90 @@
+//! What `Automation`, `ChapterWatcher` and `LyricsDriver` have in common: state that syncs on a channel act on.
+
+use std::{
+	any::type_name,
+	fmt::Debug,
+	sync::{Arc, Mutex},
+};
+
+use bass_sys::*;
+
+use super::{Channel, ChannelExt};
+use crate::{sync::BassSync, BassResult};
+
+/// State shared with a set of syncs on a channel. The syncs are removed when this is dropped, so whatever owns it
+/// stops reacting to the channel at the same time.
+#[derive(Debug)]
+pub(crate) struct Attached<T: Send + Sync> {
+	state: Arc<T>,
+	syncs: Vec<BassSync<Arc<T>>>,
+}
+
+impl<T: Send + Sync + 'static> Attached<T> {
+	pub(crate) fn new(state: T) -> Self {
+		Attached { state: Arc::new(state), syncs: Vec::new() }
+	}
+
+	pub(crate) fn state(&self) -> &Arc<T> {
+		&self.state
+	}
+
+	/// Sets a sync on `channel` that calls `proc` with the state and the channel's handle.
+	pub(crate) fn set_sync(
+		&mut self,
+		channel: &(impl Channel + ?Sized),
+		sync_type: DWORD,
+		parameter: impl Into<QWORD>,
+		proc: impl Fn(&T, DWORD) + Send + Sync + 'static,
+	) -> BassResult<()> {
+		let sync = channel.set_sync(
+			sync_type,
+			parameter,
+			move |state: &mut Arc<T>, _, channel, _| proc(state, channel),
+			self.state.clone(),
+		)?;
+		self.syncs.push(sync);
+		Ok(())
+	}
+
+	/// The channel the syncs are set on.
+	pub(crate) fn channel(&self) -> Option<DWORD> {
+		self.syncs.first().map(BassSync::channel)
+	}
+}
+
+/// A function given by the user, which syncs call with events.
+pub(crate) struct Callback<A>(Mutex<Box<dyn FnMut(A) + Send + Sync>>);
+
+impl<A> Callback<A> {
+	pub(crate) fn new(callback: impl FnMut(A) + Send + Sync + 'static) -> Self {
+		Callback(Mutex::new(Box::new(callback)))
+	}
+
+	/// Calls the function. Events from syncs on different threads are passed on one at a time.
+	pub(crate) fn call(&self, event: A) {
+		// A panic in the function doesn't leave anything inconsistent here.
+		let mut callback = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		(*callback)(event)
+	}
+}
+
+impl<A> Debug for Callback<A> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Callback(Box<dyn FnMut({})>)", type_name::<A>())
+	}
+}
+
+/// A channel's position in seconds, read from a sync. Syncs have nowhere to return an error to, so this is `None` if
+/// BASS can't give one (like when the channel has just been freed).
+///
+/// Mixtime syncs are called as the data is decoded, ahead of playback, so after a loop or a seek that doesn't flush
+/// the buffer the playback position is still from before it. `mixtime` reads the decode position instead.
+pub(crate) fn position_seconds(channel: DWORD, mixtime: bool) -> Option<f64> {
+	let mode = if mixtime { BASS_POS_BYTE | BASS_POS_DECODE } else { BASS_POS_BYTE };
+	let bytes = BASS_ChannelGetPosition(channel, mode);
+	if bytes.0 as i64 == -1 {
+		return None;
+	}
+	let seconds = BASS_ChannelBytes2Seconds(channel, bytes.0);
+	(seconds >= 0.).then_some(seconds)
+}
@@ -0,0 +1,397 @@
+use std::time::Duration;
+
+use bass_sys::*;
+
+use super::data::channel_info;
+use crate::{
+	bass::error::{BassError, BassErrorCode},
+	BassResult,
+};
+
+/// A channel attribute, without a value.
+///
+/// Used to read an attribute with `Channel::get_attribute`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelAttribute {
+	Volume,
+	Pan,
+	Frequency,
+	EaxMix,
+	NoRamp,
+	Buffer,
+	Granule,
+	SrcQuality,
+	Tail,
+	NetResume,
+	PushLimit,
+	VolumeDsp,
+	Bitrate,
+	MusicAmplify,
+	MusicSpeed,
+	MusicBpm,
+	/// The volume of one of a MOD music's channels, numbered from 0.
+	MusicVolumeChannel(u32),
+	#[cfg(feature = "mixer")]
+	MixerLatency,
+	#[cfg(feature = "mixer")]
+	MixerThreads,
+	#[cfg(feature = "mixer")]
+	MixerVolume,
+}
+
+/// A channel attribute, along with its value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Attribute {
+	/// The volume level, 0 (silent) to 1 (full). Levels above 1 amplify the sound.
+	Volume(f32),
+	/// The panning position, -1 (full left) to 1 (full right).
+	Pan(f32),
+	/// The sample rate in Hz. `0` is the rate the channel was created with.
+	Frequency(f32),
+	/// The EAX wet/dry mix, 0 (full dry) to 1 (full wet). `None` lets BASS decide based on the channel's 3D distance.
+	EaxMix(Option<f32>),
+	/// Disables the short volume ramps BASS applies when starting, stopping or changing the volume.
+	NoRamp(bool),
+	/// The playback buffering length, up to 5 seconds. `Duration::ZERO` disables buffering.
+	Buffer(Duration),
+	/// The number of samples the channel is processed in at a time. `0` lets BASS decide.
+	Granule(u32),
+	/// The sample rate conversion quality, 0 (linear interpolation) to 4 (64 point sinc).
+	SrcQuality(u32),
+	/// The length of silence to play after the end of the channel.
+	Tail(Duration),
+	/// The amount of the download buffer (as a percentage, 0-100) to fill before resuming a stalled internet stream.
+	NetResume(u32),
+	/// The amount of data a push stream can queue, in bytes. `0` means no limit.
+	PushLimit(u32),
+	/// The volume applied in the DSP chain, rather than to the output. Levels above 1 amplify the sound.
+	VolumeDsp(f32),
+	/// The average bitrate in kilobits per second. This is read-only.
+	Bitrate(f32),
+	/// The amplification level of a MOD music, 0 to 100.
+	MusicAmplify(u32),
+	/// The speed of a MOD music, 0 to 255.
+	MusicSpeed(u32),
+	/// The BPM of a MOD music, 1 to 255.
+	MusicBpm(u32),
+	/// The volume of one of a MOD music's channels, 0 (silent) to 1 (full).
+	MusicVolumeChannel { channel: u32, volume: f32 },
+	/// The mixer's latency. This is read-only.
+	#[cfg(feature = "mixer")]
+	MixerLatency(Duration),
+	/// The number of threads the mixer processes its sources with.
+	#[cfg(feature = "mixer")]
+	MixerThreads(u32),
+	/// The volume of the mixer's output, 0 (silent) to 1 (full). Levels above 1 amplify the sound.
+	#[cfg(feature = "mixer")]
+	MixerVolume(f32),
+}
+
+/// The kinds of channel an attribute can be used with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Applies {
+	Any,
+	Music,
+	#[cfg(feature = "mixer")]
+	Mixer,
+}
+
+impl ChannelAttribute {
+	pub(crate) fn raw(self) -> DWORD {
+		match self {
+			ChannelAttribute::Volume => BASS_ATTRIB_VOL,
+			ChannelAttribute::Pan => BASS_ATTRIB_PAN,
+			ChannelAttribute::Frequency => BASS_ATTRIB_FREQ,
+			ChannelAttribute::EaxMix => BASS_ATTRIB_EAXMIX,
+			ChannelAttribute::NoRamp => BASS_ATTRIB_NORAMP,
+			ChannelAttribute::Buffer => BASS_ATTRIB_BUFFER,
+			ChannelAttribute::Granule => BASS_ATTRIB_GRANULE,
+			ChannelAttribute::SrcQuality => BASS_ATTRIB_SRC,
+			ChannelAttribute::Tail => BASS_ATTRIB_TAIL,
+			ChannelAttribute::NetResume => BASS_ATTRIB_NET_RESUME,
+			ChannelAttribute::PushLimit => BASS_ATTRIB_PUSH_LIMIT,
+			ChannelAttribute::VolumeDsp => BASS_ATTRIB_VOLDSP,
+			ChannelAttribute::Bitrate => BASS_ATTRIB_BITRATE,
+			ChannelAttribute::MusicAmplify => BASS_ATTRIB_MUSIC_AMPLIFY,
+			ChannelAttribute::MusicSpeed => BASS_ATTRIB_MUSIC_SPEED,
+			ChannelAttribute::MusicBpm => BASS_ATTRIB_MUSIC_BPM,
+			ChannelAttribute::MusicVolumeChannel(channel) => DWORD(BASS_ATTRIB_MUSIC_VOL_CHAN.0 + channel),
+			#[cfg(feature = "mixer")]
+			ChannelAttribute::MixerLatency => BASS_ATTRIB_MIXER_LATENCY,
+			#[cfg(feature = "mixer")]
+			ChannelAttribute::MixerThreads => BASS_ATTRIB_MIXER_THREADS,
+			#[cfg(feature = "mixer")]
+			ChannelAttribute::MixerVolume => BASS_ATTRIB_MIXER_VOL,
+		}
+	}
+
+	fn applies(self) -> Applies {
+		match self {
+			ChannelAttribute::MusicAmplify
+			| ChannelAttribute::MusicSpeed
+			| ChannelAttribute::MusicBpm
+			| ChannelAttribute::MusicVolumeChannel(_) => Applies::Music,
+			#[cfg(feature = "mixer")]
+			ChannelAttribute::MixerLatency | ChannelAttribute::MixerThreads | ChannelAttribute::MixerVolume => {
+				Applies::Mixer
+			}
+			_ => Applies::Any,
+		}
+	}
+
+	/// Checks the attribute can be used with a channel, so BASS's generic `BASS_ERROR_ILLTYPE` doesn't have to be
+	/// deciphered.
+	pub(crate) fn check_applies(self, handle: DWORD, operation: &'static str) -> BassResult<()> {
+		let applies = self.applies();
+		if applies == Applies::Any {
+			return Ok(());
+		}
+		let info = channel_info(handle, operation)?;
+		let (ok, kind) = match applies {
+			Applies::Music => ((info.ctype & BASS_CTYPE_MUSIC_MOD) == BASS_CTYPE_MUSIC_MOD, "MOD music"),
+			#[cfg(feature = "mixer")]
+			Applies::Mixer => (info.ctype == BASS_CTYPE_STREAM_MIXER, "mixers"),
+			Applies::Any => (true, ""),
+		};
+		if ok {
+			Ok(())
+		} else {
+			Err(BassError::new(BassErrorCode::BassErrorIllType, operation)
+				.with_handle(handle)
+				.with_context(format!("{self:?} only applies to {kind}")))
+		}
+	}
+}
+
+impl Attribute {
+	pub fn attribute(&self) -> ChannelAttribute {
+		match self {
+			Attribute::Volume(_) => ChannelAttribute::Volume,
+			Attribute::Pan(_) => ChannelAttribute::Pan,
+			Attribute::Frequency(_) => ChannelAttribute::Frequency,
+			Attribute::EaxMix(_) => ChannelAttribute::EaxMix,
+			Attribute::NoRamp(_) => ChannelAttribute::NoRamp,
+			Attribute::Buffer(_) => ChannelAttribute::Buffer,
+			Attribute::Granule(_) => ChannelAttribute::Granule,
+			Attribute::SrcQuality(_) => ChannelAttribute::SrcQuality,
+			Attribute::Tail(_) => ChannelAttribute::Tail,
+			Attribute::NetResume(_) => ChannelAttribute::NetResume,
+			Attribute::PushLimit(_) => ChannelAttribute::PushLimit,
+			Attribute::VolumeDsp(_) => ChannelAttribute::VolumeDsp,
+			Attribute::Bitrate(_) => ChannelAttribute::Bitrate,
+			Attribute::MusicAmplify(_) => ChannelAttribute::MusicAmplify,
+			Attribute::MusicSpeed(_) => ChannelAttribute::MusicSpeed,
+			Attribute::MusicBpm(_) => ChannelAttribute::MusicBpm,
+			Attribute::MusicVolumeChannel { channel, .. } => ChannelAttribute::MusicVolumeChannel(*channel),
+			#[cfg(feature = "mixer")]
+			Attribute::MixerLatency(_) => ChannelAttribute::MixerLatency,
+			#[cfg(feature = "mixer")]
+			Attribute::MixerThreads(_) => ChannelAttribute::MixerThreads,
+			#[cfg(feature = "mixer")]
+			Attribute::MixerVolume(_) => ChannelAttribute::MixerVolume,
+		}
+	}
+
+	/// Checks the value is within the range BASS accepts for the attribute.
+	pub(crate) fn validate(&self, operation: &'static str) -> BassResult<()> {
+		let level = |value: f32| value.is_finite() && value >= 0.;
+		let valid = match self {
+			Attribute::Volume(value) | Attribute::VolumeDsp(value) => level(*value),
+			#[cfg(feature = "mixer")]
+			Attribute::MixerVolume(value) => level(*value),
+			Attribute::Pan(pan) => (-1. ..=1.).contains(pan),
+			Attribute::Frequency(frequency) => level(*frequency),
+			Attribute::EaxMix(mix) => mix.map_or(true, |mix| (0. ..=1.).contains(&mix)),
+			Attribute::Buffer(length) => length.as_millis() <= 5000,
+			Attribute::SrcQuality(quality) => *quality <= 4,
+			Attribute::NetResume(percent) => *percent <= 100,
+			Attribute::MusicAmplify(amplify) => *amplify <= 100,
+			Attribute::MusicSpeed(speed) => *speed <= 255,
+			Attribute::MusicBpm(bpm) => (1..=255).contains(bpm),
+			Attribute::MusicVolumeChannel { volume, .. } => (0. ..=1.).contains(volume),
+			Attribute::NoRamp(_) | Attribute::Granule(_) | Attribute::Tail(_) | Attribute::PushLimit(_) => true,
+			#[cfg(feature = "mixer")]
+			Attribute::MixerThreads(threads) => *threads >= 1,
+			// Read-only.
+			Attribute::Bitrate(_) => return Err(BassError::new(BassErrorCode::BassErrorNotAvailable, operation)),
+			#[cfg(feature = "mixer")]
+			Attribute::MixerLatency(_) => return Err(BassError::new(BassErrorCode::BassErrorNotAvailable, operation)),
+		};
+		if valid {
+			Ok(())
+		} else {
+			Err(BassError::new(BassErrorCode::BassErrorIllParam, operation).with_context(format!("{self:?}")))
+		}
+	}
+
+	pub(crate) fn to_f32(&self) -> f32 {
+		match self {
+			Attribute::Volume(value)
+			| Attribute::Pan(value)
+			| Attribute::Frequency(value)
+			| Attribute::VolumeDsp(value)
+			| Attribute::Bitrate(value) => *value,
+			#[cfg(feature = "mixer")]
+			Attribute::MixerVolume(value) => *value,
+			Attribute::EaxMix(mix) => mix.unwrap_or(-1.),
+			Attribute::NoRamp(value) => *value as u32 as f32,
+			Attribute::Buffer(duration) | Attribute::Tail(duration) => duration.as_secs_f32(),
+			#[cfg(feature = "mixer")]
+			Attribute::MixerLatency(duration) => duration.as_secs_f32(),
+			Attribute::Granule(value)
+			| Attribute::SrcQuality(value)
+			| Attribute::NetResume(value)
+			| Attribute::PushLimit(value)
+			| Attribute::MusicAmplify(value)
+			| Attribute::MusicSpeed(value)
+			| Attribute::MusicBpm(value) => *value as f32,
+			#[cfg(feature = "mixer")]
+			Attribute::MixerThreads(value) => *value as f32,
+			Attribute::MusicVolumeChannel { volume, .. } => *volume,
+		}
+	}
+
+	pub(crate) fn from_f32(attribute: ChannelAttribute, value: f32) -> Self {
+		let seconds = || Duration::from_secs_f32(value.max(0.));
+		match attribute {
+			ChannelAttribute::Volume => Attribute::Volume(value),
+			ChannelAttribute::Pan => Attribute::Pan(value),
+			ChannelAttribute::Frequency => Attribute::Frequency(value),
+			ChannelAttribute::EaxMix => Attribute::EaxMix((value >= 0.).then_some(value)),
+			ChannelAttribute::NoRamp => Attribute::NoRamp(value != 0.),
+			ChannelAttribute::Buffer => Attribute::Buffer(seconds()),
+			ChannelAttribute::Granule => Attribute::Granule(value as u32),
+			ChannelAttribute::SrcQuality => Attribute::SrcQuality(value as u32),
+			ChannelAttribute::Tail => Attribute::Tail(seconds()),
+			ChannelAttribute::NetResume => Attribute::NetResume(value as u32),
+			ChannelAttribute::PushLimit => Attribute::PushLimit(value as u32),
+			ChannelAttribute::VolumeDsp => Attribute::VolumeDsp(value),
+			ChannelAttribute::Bitrate => Attribute::Bitrate(value),
+			ChannelAttribute::MusicAmplify => Attribute::MusicAmplify(value as u32),
+			ChannelAttribute::MusicSpeed => Attribute::MusicSpeed(value as u32),
+			ChannelAttribute::MusicBpm => Attribute::MusicBpm(value as u32),
+			ChannelAttribute::MusicVolumeChannel(channel) => Attribute::MusicVolumeChannel { channel, volume: value },
+			#[cfg(feature = "mixer")]
+			ChannelAttribute::MixerLatency => Attribute::MixerLatency(seconds()),
+			#[cfg(feature = "mixer")]
+			ChannelAttribute::MixerThreads => Attribute::MixerThreads(value as u32),
+			#[cfg(feature = "mixer")]
+			ChannelAttribute::MixerVolume => Attribute::MixerVolume(value),
+		}
+	}
+}
+
+/// How an attribute's value moves towards its target during a slide.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SlideCurve {
+	#[default]
+	Linear,
+	/// Logarithmic, which sounds more natural for volume and frequency. Slides to or from 0 are always linear.
+	Log,
+}
+
+/// Slides an attribute to a new value over a period of time. See `Channel::slide_attribute`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slide {
+	pub target: Attribute,
+	pub duration: Duration,
+	pub curve: SlideCurve,
+}
+
+impl Slide {
+	pub fn linear(target: Attribute, duration: Duration) -> Self {
+		Slide { target, duration, curve: SlideCurve::Linear }
+	}
+
+	pub fn log(target: Attribute, duration: Duration) -> Self {
+		Slide { target, duration, curve: SlideCurve::Log }
+	}
+
+	pub(crate) fn raw_attribute(&self) -> DWORD {
+		match self.curve {
+			SlideCurve::Linear => self.target.attribute().raw(),
+			SlideCurve::Log => self.target.attribute().raw() | BASS_SLIDE_LOG,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn f32_round_trip() {
+		let attributes = [
+			Attribute::Volume(0.5),
+			Attribute::Pan(-1.),
+			Attribute::Frequency(44100.),
+			Attribute::EaxMix(Some(0.25)),
+			Attribute::EaxMix(None),
+			Attribute::NoRamp(true),
+			Attribute::Buffer(Duration::from_millis(500)),
+			Attribute::Granule(512),
+			Attribute::SrcQuality(2),
+			Attribute::Tail(Duration::from_secs(2)),
+			Attribute::NetResume(50),
+			Attribute::PushLimit(65536),
+			Attribute::VolumeDsp(2.),
+			Attribute::Bitrate(320.),
+			Attribute::MusicAmplify(50),
+			Attribute::MusicSpeed(6),
+			Attribute::MusicBpm(125),
+			Attribute::MusicVolumeChannel { channel: 3, volume: 0.75 },
+			#[cfg(feature = "mixer")]
+			Attribute::MixerLatency(Duration::from_millis(250)),
+			#[cfg(feature = "mixer")]
+			Attribute::MixerThreads(4),
+			#[cfg(feature = "mixer")]
+			Attribute::MixerVolume(0.5),
+		];
+		for attribute in attributes {
+			assert_eq!(Attribute::from_f32(attribute.attribute(), attribute.to_f32()), attribute);
+		}
+		assert_eq!(ChannelAttribute::MusicVolumeChannel(3).raw(), DWORD(BASS_ATTRIB_MUSIC_VOL_CHAN.0 + 3));
+	}
+
+	#[test]
+	fn validation() {
+		let valid = [
+			Attribute::Volume(0.),
+			Attribute::Volume(3.),
+			Attribute::Pan(1.),
+			Attribute::EaxMix(None),
+			Attribute::Buffer(Duration::from_secs(5)),
+			Attribute::SrcQuality(4),
+			Attribute::MusicBpm(255),
+			Attribute::MusicVolumeChannel { channel: 0, volume: 1. },
+		];
+		for attribute in valid {
+			assert_eq!(attribute.validate("test"), Ok(()), "{attribute:?}");
+		}
+
+		let invalid = [
+			Attribute::Volume(-0.1),
+			Attribute::Volume(f32::NAN),
+			Attribute::VolumeDsp(f32::INFINITY),
+			Attribute::Pan(1.5),
+			Attribute::Frequency(-1.),
+			Attribute::EaxMix(Some(1.5)),
+			Attribute::Buffer(Duration::from_millis(5001)),
+			Attribute::SrcQuality(5),
+			Attribute::NetResume(101),
+			Attribute::MusicAmplify(101),
+			Attribute::MusicSpeed(256),
+			Attribute::MusicBpm(0),
+			Attribute::MusicVolumeChannel { channel: 0, volume: 1.5 },
+			#[cfg(feature = "mixer")]
+			Attribute::MixerThreads(0),
+		];
+		for attribute in invalid {
+			let error = attribute.validate("test").unwrap_err();
+			assert_eq!(error.code(), BassErrorCode::BassErrorIllParam, "{attribute:?}");
+		}
+
+		// Read-only.
+		assert_eq!(Attribute::Bitrate(128.).validate("test").unwrap_err().code(), BassErrorCode::BassErrorNotAvailable);
+	}
+}
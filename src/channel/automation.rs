@@ -0,0 +1,258 @@
+use std::time::Duration;
+
+use bass_sys::*;
+
+use super::{
+	attached::{self, Attached},
+	attribute::{Attribute, ChannelAttribute, SlideCurve},
+	Channel,
+};
+use crate::BassResult;
+
+/// A point an attribute should reach at a position in the channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Breakpoint {
+	pub position: Duration,
+	pub value: Attribute,
+	/// How the attribute moves from the previous breakpoint's value to this one.
+	pub curve: SlideCurve,
+}
+
+impl Breakpoint {
+	pub fn linear(position: Duration, value: Attribute) -> Self {
+		Breakpoint { position, value, curve: SlideCurve::Linear }
+	}
+
+	pub fn log(position: Duration, value: Attribute) -> Self {
+		Breakpoint { position, value, curve: SlideCurve::Log }
+	}
+}
+
+/// Moves attributes through a set of breakpoints as a channel plays, like automation lanes in a DAW.
+///
+/// Each breakpoint has a `BASS_SYNC_POS | BASS_SYNC_MIXTIME` sync, which slides the attribute towards the next
+/// breakpoint of the same attribute, so changes happen at exact positions in the channel rather than whenever a timer
+/// fires. A `BASS_SYNC_SETPOS` sync (which is also triggered by looping) recalculates every attribute's value for the
+/// new position. Before an attribute's first breakpoint it is held at that breakpoint's value, and after its last it
+/// is left at the last value.
+///
+/// Dropping the automation removes its syncs, leaving the attributes wherever they had got to.
+///
+/// ```ignore
+/// let automation = Automation::attach(&stream, [
+/// 	Breakpoint::linear(Duration::from_secs(10), Attribute::Volume(1.)),
+/// 	Breakpoint::log(Duration::from_secs(12), Attribute::Volume(0.2)),
+/// 	Breakpoint::log(Duration::from_secs(40), Attribute::Volume(1.)),
+/// ])?;
+/// ```
+///
+/// Slides run in playback time, so changing the channel's frequency or tempo during a slide stretches it past its
+/// breakpoint. The next breakpoint's sync corrects the value when it is reached.
+#[derive(Debug)]
+pub struct Automation {
+	timeline: Attached<Timeline>,
+}
+
+#[derive(Debug)]
+struct Timeline {
+	lanes: Vec<Lane>,
+}
+
+/// The breakpoints of a single attribute, in position order.
+#[derive(Debug)]
+struct Lane {
+	attribute: ChannelAttribute,
+	points: Vec<Point>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Point {
+	seconds: f64,
+	value: f32,
+	curve: SlideCurve,
+}
+
+impl Automation {
+	/// Attaches the breakpoints to a channel, and sets each attribute to its value at the channel's current position.
+	///
+	/// Breakpoints can be given in any order, and for any number of attributes. Attributes that don't apply to the
+	/// channel are rejected with `BASS_ERROR_ILLTYPE`.
+	pub fn attach(channel: &(impl Channel + ?Sized), breakpoints: impl IntoIterator<Item = Breakpoint>) -> BassResult<Self> {
+		let mut lanes: Vec<Lane> = Vec::new();
+		for breakpoint in breakpoints {
+			breakpoint.value.validate("Automation::attach")?;
+			let attribute = breakpoint.value.attribute();
+			let point = Point {
+				seconds: breakpoint.position.as_secs_f64(),
+				value: breakpoint.value.to_f32(),
+				curve: breakpoint.curve,
+			};
+			match lanes.iter_mut().find(|lane| lane.attribute == attribute) {
+				Some(lane) => lane.points.push(point),
+				None => {
+					attribute.check_applies(channel.handle(), "Automation::attach")?;
+					lanes.push(Lane { attribute, points: vec![point] });
+				}
+			}
+		}
+		for lane in &mut lanes {
+			lane.points.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+		}
+		let mut timeline = Attached::new(Timeline { lanes });
+
+		let state = timeline.state().clone();
+		for (lane, attribute) in state.lanes.iter().enumerate() {
+			for (point, at) in attribute.points.iter().enumerate() {
+				timeline.set_sync(
+					channel,
+					BASS_SYNC_POS | BASS_SYNC_MIXTIME,
+					channel.duration_to_bytes(Duration::from_secs_f64(at.seconds))?,
+					move |timeline, channel| timeline.reached(channel, lane, point),
+				)?;
+			}
+		}
+		timeline.set_sync(channel, BASS_SYNC_SETPOS | BASS_SYNC_MIXTIME, 0, Timeline::resync)?;
+
+		timeline.state().resync(channel.handle());
+		Ok(Automation { timeline })
+	}
+
+	/// The attributes being automated.
+	pub fn attributes(&self) -> impl Iterator<Item = ChannelAttribute> + '_ {
+		self.timeline.state().lanes.iter().map(|lane| lane.attribute)
+	}
+
+	/// The channel the automation is attached to.
+	pub fn channel(&self) -> Option<DWORD> {
+		self.timeline.channel()
+	}
+}
+
+impl Timeline {
+	/// Called from a breakpoint's sync: sets the breakpoint's value exactly, then slides towards the next one.
+	fn reached(&self, channel: DWORD, lane: usize, point: usize) {
+		let lane = &self.lanes[lane];
+		let at = lane.points[point];
+		set(channel, lane.attribute, at.value);
+		if let Some(next) = lane.points.get(point + 1) {
+			slide(channel, lane.attribute, next, next.seconds - at.seconds);
+		}
+	}
+
+	/// Sets every attribute to its value at the channel's decode position, and starts the slide towards the next
+	/// breakpoint. The syncs are all mixtime, so this is where they are up to, even straight after a loop.
+	fn resync(&self, channel: DWORD) {
+		let Some(now) = attached::position_seconds(channel, true) else { return };
+		for (attribute, value, next) in self.targets(now) {
+			set(channel, attribute, value);
+			if let Some(next) = next {
+				slide(channel, attribute, next, next.seconds - now);
+			}
+		}
+	}
+
+	/// Each attribute's value at a position, and the breakpoint it should slide towards from there.
+	fn targets(&self, seconds: f64) -> Vec<(ChannelAttribute, f32, Option<&Point>)> {
+		self.lanes
+			.iter()
+			.filter_map(|lane| lane.at(seconds).map(|(value, next)| (lane.attribute, value, next)))
+			.collect()
+	}
+}
+
+impl Lane {
+	/// The attribute's value at a position, and the breakpoint it should slide towards from there. Nothing slides
+	/// before the first breakpoint or after the last.
+	fn at(&self, seconds: f64) -> Option<(f32, Option<&Point>)> {
+		// The first breakpoint after the position.
+		let next = self.points.partition_point(|point| point.seconds <= seconds);
+		match (next.checked_sub(1).map(|index| &self.points[index]), self.points.get(next)) {
+			(Some(previous), Some(next)) => {
+				let progress = ((seconds - previous.seconds) / (next.seconds - previous.seconds)) as f32;
+				Some((interpolate(previous.value, next.value, progress, next.curve), Some(next)))
+			}
+			(Some(last), None) => Some((last.value, None)),
+			(None, Some(first)) => Some((first.value, None)),
+			(None, None) => None,
+		}
+	}
+}
+
+// These are called from syncs, which have nowhere to return an error to. BASS only fails here if the channel has been
+// freed, in which case the syncs are about to go too.
+
+fn set(channel: DWORD, attribute: ChannelAttribute, value: f32) {
+	// Setting an attribute also stops any slide of it that is in progress.
+	let _ = BASS_ChannelSetAttribute(channel, attribute.raw(), value);
+}
+
+fn slide(channel: DWORD, attribute: ChannelAttribute, to: &Point, seconds: f64) {
+	let raw = match to.curve {
+		SlideCurve::Linear => attribute.raw(),
+		SlideCurve::Log => attribute.raw() | BASS_SLIDE_LOG,
+	};
+	let milliseconds = (seconds * 1000.).clamp(0., u32::MAX as f64) as u32;
+	let _ = BASS_ChannelSlideAttribute(channel, raw, to.value, milliseconds);
+}
+
+/// The value `progress` (0 to 1) of the way from `from` to `to`, matching how BASS slides along `curve`.
+fn interpolate(from: f32, to: f32, progress: f32, curve: SlideCurve) -> f32 {
+	let progress = progress.clamp(0., 1.);
+	match curve {
+		// BASS slides to or from 0 linearly even when asked for a logarithmic slide.
+		SlideCurve::Log if from > 0. && to > 0. => from * (to / from).powf(progress),
+		_ => from + (to - from) * progress,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn close(a: f32, b: f32) -> bool {
+		(a - b).abs() < 1e-5
+	}
+
+	#[test]
+	fn interpolation() {
+		assert!(close(interpolate(0., 1., 0.25, SlideCurve::Linear), 0.25));
+		assert!(close(interpolate(2., 1., 0.5, SlideCurve::Linear), 1.5));
+		assert!(close(interpolate(0.1, 1., 0.5, SlideCurve::Log), 0.1f32.sqrt()));
+		assert!(close(interpolate(1., 0.01, 0.5, SlideCurve::Log), 0.1));
+		// Log slides to or from 0 are linear.
+		assert!(close(interpolate(0., 1., 0.25, SlideCurve::Log), 0.25));
+		assert!(close(interpolate(1., 0., 0.25, SlideCurve::Log), 0.75));
+		assert!(close(interpolate(0., 1., 2., SlideCurve::Linear), 1.));
+	}
+
+	#[test]
+	fn lane_segments() {
+		let point = |seconds: f64, value: f32| Point { seconds, value, curve: SlideCurve::Linear };
+		let lane = Lane { attribute: ChannelAttribute::Volume, points: vec![point(10., 1.), point(20., 0.)] };
+		let at = |seconds: f64| lane.at(seconds).map(|(value, next)| (value, next.map(|next| next.seconds)));
+		assert_eq!(at(5.), Some((1., None)));
+		assert_eq!(at(10.), Some((1., Some(20.))));
+		assert_eq!(at(15.), Some((0.5, Some(20.))));
+		assert_eq!(at(20.), Some((0., None)));
+		assert_eq!(at(30.), Some((0., None)));
+		assert_eq!(Lane { attribute: ChannelAttribute::Volume, points: Vec::new() }.at(0.), None);
+	}
+
+	#[test]
+	fn resync_after_loop() {
+		let point = |seconds: f64, value: f32| Point { seconds, value, curve: SlideCurve::Linear };
+		let timeline = Timeline {
+			lanes: vec![
+				Lane { attribute: ChannelAttribute::Volume, points: vec![point(0., 0.), point(10., 1.)] },
+				Lane { attribute: ChannelAttribute::Pan, points: vec![point(5., -1.)] },
+			],
+		};
+		// Looping from the end back to the start: the decode position is 0.5 s while playback is still near 10 s.
+		let targets: Vec<_> = timeline
+			.targets(0.5)
+			.into_iter()
+			.map(|(attribute, value, next)| (attribute, value, next.map(|next| (next.seconds, next.value))))
+			.collect();
+		assert_eq!(targets, [(ChannelAttribute::Volume, 0.05, Some((10., 1.))), (ChannelAttribute::Pan, -1., None)]);
+	}
+}
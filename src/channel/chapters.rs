@@ -0,0 +1,175 @@
+use std::{
+	sync::atomic::{AtomicUsize, Ordering},
+	time::Duration,
+};
+
+use bass_sys::*;
+
+use super::{
+	attached::{self, Attached, Callback},
+	Channel,
+};
+use crate::{tags::chapter::Chapter, BassResult};
+
+/// How far into a chapter `Channel::previous_chapter` restarts it, rather than going to the one before it.
+pub const RESTART_THRESHOLD: Duration = Duration::from_secs(3);
+
+/// The index of the chapter a position is in.
+pub fn chapter_at(chapters: &[Chapter], position: Duration) -> Option<usize> {
+	chapters.iter().position(|chapter| chapter.start <= position && chapter.end.map_or(true, |end| position < end))
+}
+
+/// The chapter `Channel::next_chapter` goes to from a position.
+pub(crate) fn next(chapters: &[Chapter], position: Duration) -> Option<usize> {
+	match chapter_at(chapters, position) {
+		Some(current) => Some(current + 1).filter(|next| *next < chapters.len()),
+		None => chapters.iter().position(|chapter| chapter.start > position),
+	}
+}
+
+/// The chapter `Channel::previous_chapter` goes to from a position.
+pub(crate) fn previous(chapters: &[Chapter], position: Duration) -> Option<usize> {
+	match chapter_at(chapters, position) {
+		Some(current) if position - chapters[current].start > RESTART_THRESHOLD => Some(current),
+		Some(current) => Some(current.saturating_sub(1)),
+		None => chapters.iter().rposition(|chapter| chapter.end.is_some_and(|end| end <= position)),
+	}
+}
+
+/// Calls a function whenever playback moves into another chapter, either by playing past the start of one or by
+/// seeking.
+///
+/// Each chapter's start has a `BASS_SYNC_POS` sync, and a `BASS_SYNC_SETPOS` sync catches seeks. These aren't mixtime
+/// syncs, so the function is called when the change is heard rather than when it is decoded. It is called on BASS's
+/// sync thread with the index of the chapter, so it should return quickly.
+///
+/// The function stops being called once the watcher is dropped.
+///
+/// ```ignore
+/// let chapters = stream.chapters()?;
+/// let watcher = ChapterWatcher::attach(&stream, &chapters, move |index| sender.send(index).unwrap())?;
+/// ```
+#[derive(Debug)]
+pub struct ChapterWatcher {
+	watch: Attached<Watch>,
+}
+
+#[derive(Debug)]
+struct Watch {
+	chapters: Vec<Chapter>,
+	/// The index of the current chapter, or `usize::MAX` if playback isn't in one.
+	current: AtomicUsize,
+	callback: Callback<usize>,
+}
+
+impl ChapterWatcher {
+	/// Attaches to a channel. The function isn't called for the chapter the channel is already in.
+	pub fn attach(
+		channel: &(impl Channel + ?Sized),
+		chapters: &[Chapter],
+		callback: impl FnMut(usize) + Send + Sync + 'static,
+	) -> BassResult<Self> {
+		let current = chapter_at(chapters, channel.position_time()?).unwrap_or(usize::MAX);
+		let mut watch = Attached::new(Watch {
+			chapters: chapters.to_vec(),
+			current: AtomicUsize::new(current),
+			callback: Callback::new(callback),
+		});
+		for (index, chapter) in chapters.iter().enumerate() {
+			watch.set_sync(channel, BASS_SYNC_POS, channel.duration_to_bytes(chapter.start)?, move |watch, _| {
+				watch.entered(index)
+			})?;
+		}
+		watch.set_sync(channel, BASS_SYNC_SETPOS, 0, Watch::resync)?;
+		Ok(ChapterWatcher { watch })
+	}
+
+	/// The index of the chapter playback was last in.
+	pub fn current(&self) -> Option<usize> {
+		let current = self.watch.state().current.load(Ordering::Relaxed);
+		(current != usize::MAX).then_some(current)
+	}
+
+	pub fn chapters(&self) -> &[Chapter] {
+		&self.watch.state().chapters
+	}
+
+	/// The channel the watcher is attached to.
+	pub fn channel(&self) -> Option<DWORD> {
+		self.watch.channel()
+	}
+}
+
+impl Watch {
+	fn entered(&self, index: usize) {
+		if self.current.swap(index, Ordering::Relaxed) != index {
+			self.callback.call(index);
+		}
+	}
+
+	/// Called after a seek, which may have moved to another chapter.
+	fn resync(&self, channel: DWORD) {
+		let Some(seconds) = attached::position_seconds(channel, false) else { return };
+		match chapter_at(&self.chapters, Duration::from_secs_f64(seconds)) {
+			Some(index) => self.entered(index),
+			None => self.current.store(usize::MAX, Ordering::Relaxed),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tags::{
+		chapter::{
+			id3v2_chapters,
+			tests::{chap, ctoc},
+		},
+		id3::Id3v2Tag,
+	};
+
+	fn chapters() -> Vec<Chapter> {
+		[(0, 10), (10, 60), (60, 70)]
+			.into_iter()
+			.map(|(start, end)| Chapter {
+				title: String::new(),
+				start: Duration::from_secs(start),
+				end: Some(Duration::from_secs(end)),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn navigation() {
+		let chapters = chapters();
+		let at = Duration::from_secs;
+		assert_eq!(chapter_at(&chapters, at(10)), Some(1));
+		assert_eq!(chapter_at(&chapters, at(70)), None);
+		assert_eq!(next(&chapters, at(5)), Some(1));
+		assert_eq!(next(&chapters, at(65)), None);
+		assert_eq!(previous(&chapters, at(30)), Some(1));
+		assert_eq!(previous(&chapters, at(11)), Some(0));
+		assert_eq!(previous(&chapters, at(1)), Some(0));
+		assert_eq!(previous(&chapters, at(80)), Some(2));
+	}
+
+	#[test]
+	fn ctoc_ordered_navigation() {
+		let tag = Id3v2Tag {
+			version: 3,
+			frames: vec![
+				chap("a", 0, u32::MAX, "Intro"),
+				chap("b", 10_000, u32::MAX, "Outro"),
+				ctoc("toc", 0x03, &["b", "a"]),
+			],
+		};
+		let chapters = id3v2_chapters(&tag, None);
+		let at = Duration::from_secs;
+		assert_eq!(chapter_at(&chapters, at(15)), Some(1));
+		// The length isn't known, so the last chapter doesn't end.
+		assert_eq!(chapter_at(&chapters, at(100)), Some(1));
+		assert_eq!(next(&chapters, at(1)), Some(1));
+		assert_eq!(previous(&chapters, at(1)).map(|index| chapters[index].start), Some(Duration::ZERO));
+		assert_eq!(previous(&chapters, at(15)), Some(0));
+	}
+}
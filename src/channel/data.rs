@@ -0,0 +1,130 @@
+use std::{mem::size_of, os::raw::c_void};
+
+use bass_sys::*;
+
+use super::info::SampleFormat;
+use crate::{
+	bass::error::{BassError, BassErrorCode},
+	BassResult,
+};
+
+mod sealed {
+	pub trait Sealed {}
+	impl Sealed for f32 {}
+	impl Sealed for i16 {}
+	impl Sealed for u8 {}
+}
+
+/// A sample type that data can be read from a channel as: `f32`, `i16` or `u8`.
+///
+/// `f32` works with any channel, as BASS converts to floating-point. `i16` and `u8` are only the channel's own data,
+/// so need a 16-bit or 8-bit channel respectively.
+pub trait PcmSample: sealed::Sealed + Copy + Default {
+	#[doc(hidden)]
+	const FLAGS: DWORD;
+	#[doc(hidden)]
+	fn matches(info: &BASS_CHANNELINFO) -> bool;
+}
+
+impl PcmSample for f32 {
+	const FLAGS: DWORD = BASS_DATA_FLOAT;
+	fn matches(_: &BASS_CHANNELINFO) -> bool {
+		true
+	}
+}
+
+impl PcmSample for i16 {
+	const FLAGS: DWORD = DWORD(0);
+	fn matches(info: &BASS_CHANNELINFO) -> bool {
+		(info.flags & (BASS_SAMPLE_FLOAT | BASS_SAMPLE_8BITS)) == 0
+	}
+}
+
+impl PcmSample for u8 {
+	const FLAGS: DWORD = DWORD(0);
+	fn matches(info: &BASS_CHANNELINFO) -> bool {
+		(info.flags & BASS_SAMPLE_8BITS) == BASS_SAMPLE_8BITS
+	}
+}
+
+/// The size of one sample frame of the channel's own data, in bytes.
+pub(crate) fn bytes_per_frame(info: &BASS_CHANNELINFO) -> u32 {
+	SampleFormat::from_flags(info.flags).bytes() * info.chans.0.max(1)
+}
+
+pub(crate) fn channel_info(handle: DWORD, operation: &'static str) -> BassResult<BASS_CHANNELINFO> {
+	let mut info = BASS_CHANNELINFO::default();
+	if BASS_ChannelGetInfo(handle, &mut info) {
+		Ok(info)
+	} else {
+		Err(BassError::get(operation).with_handle(handle))
+	}
+}
+
+/// The most bytes that can be asked for at once. The top bits of `BASS_ChannelGetData`'s length are flags
+/// (`BASS_DATA_FLOAT`, `BASS_DATA_FFT*`, etc.), so a longer length would turn into a different request.
+const MAX_LENGTH: usize = 0x0fff_ffff;
+
+/// The number of whole frames to ask for at once, given how many there is room for.
+fn frames_to_read(room: usize, frame: usize) -> usize {
+	room.min(MAX_LENGTH / frame)
+}
+
+/// Fills as many whole frames of `buffer` as possible using `get_data` (`BASS_ChannelGetData` or
+/// `BASS_Mixer_ChannelGetData`), returning the number of frames written.
+pub(crate) fn read<S: PcmSample>(
+	handle: DWORD,
+	buffer: &mut [S],
+	flags: DWORD,
+	get_data: impl FnOnce(DWORD, *mut c_void, DWORD) -> DWORD,
+	operation: &'static str,
+) -> BassResult<usize> {
+	let info = channel_info(handle, operation)?;
+	if !S::matches(&info) {
+		return Err(BassError::new(BassErrorCode::BassErrorFormat, operation)
+			.with_handle(handle)
+			.with_context(format!("channel data isn't {}", std::any::type_name::<S>())));
+	}
+	let channels = info.chans.0.max(1) as usize;
+	let frame = channels * size_of::<S>();
+	// Only ask for whole frames, and never more than the buffer holds.
+	let frames = frames_to_read(buffer.len() / channels, frame);
+	let length = (frames * frame) as u32;
+	let read = get_data(handle, buffer.as_mut_ptr() as *mut c_void, DWORD(length) | S::FLAGS | flags);
+	if read.0 as i32 == -1 {
+		Err(BassError::get(operation).with_handle(handle))
+	} else {
+		Ok(read.0 as usize / frame)
+	}
+}
+
+/// The number of frames of the channel's own data available to read, using `get_data` with `BASS_DATA_AVAILABLE`.
+/// This is limited to what can be read at once, so it's always safe to size a read with.
+pub(crate) fn available(
+	handle: DWORD,
+	get_data: impl FnOnce(DWORD, *mut c_void, DWORD) -> DWORD,
+	operation: &'static str,
+) -> BassResult<usize> {
+	let info = channel_info(handle, operation)?;
+	let available = get_data(handle, std::ptr::null_mut(), BASS_DATA_AVAILABLE);
+	if available.0 as i32 == -1 {
+		Err(BassError::get(operation).with_handle(handle))
+	} else {
+		let frame = bytes_per_frame(&info) as usize;
+		Ok(frames_to_read(available.0 as usize / frame, frame))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn read_lengths_stay_clear_of_flags() {
+		assert_eq!(frames_to_read(1024, 8), 1024);
+		let frames = frames_to_read(usize::MAX / 8, 8);
+		assert!(frames * 8 <= MAX_LENGTH);
+		assert_eq!((frames * 8) as u32 & 0xf000_0000, 0);
+		assert_eq!(frames_to_read(usize::MAX, 6), MAX_LENGTH / 6);
+	}
+}
@@ -0,0 +1,205 @@
+use std::{mem, time::Duration};
+
+use bass_sys::*;
+
+use super::{
+	data::channel_info,
+	handle::HasHandle,
+	position::{Position, SeekOptions},
+	state::ChannelState,
+	Channel,
+};
+use crate::{
+	bass::error::{BassError, BassErrorCode},
+	BassResult,
+};
+
+/// A set of channels that are linked, so they play, pause and stop together.
+///
+/// The first member leads the group: every other member is linked to it, and it is what the group's state is read
+/// from. Members are unlinked when they are removed from the group, and when the group is dropped.
+///
+/// ```ignore
+/// let mut tracks = ChannelGroup::new();
+/// for path in ["drums.flac", "bass.flac"] {
+/// 	tracks.add(Stream::create_file(path, 0, 0, 0)?).map_err(|(error, _)| error)?;
+/// }
+/// tracks.seek(Duration::from_secs(30))?;
+/// tracks.play(false)?;
+/// ```
+#[derive(Debug)]
+pub struct ChannelGroup<C: Channel> {
+	members: Vec<C>,
+}
+
+impl<C: Channel> Default for ChannelGroup<C> {
+	fn default() -> Self {
+		ChannelGroup { members: Vec::new() }
+	}
+}
+
+impl<C: Channel> ChannelGroup<C> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a channel to the group, linking it to the other members.
+	///
+	/// Decoding channels can't be linked, so are rejected with `BASS_ERROR_DECODE`, as is a channel that is already a
+	/// member with `BASS_ERROR_ALREADY`. If adding fails, the channel is returned along with the error.
+	pub fn add(&mut self, channel: C) -> Result<(), (BassError, C)> {
+		if let Err(error) = self.check_member(&channel) {
+			return Err((error, channel));
+		}
+		if let Some(leader) = self.members.first() {
+			if let Err(error) = leader.set_link(channel.handle()) {
+				return Err((error, channel));
+			}
+		}
+		self.members.push(channel);
+		Ok(())
+	}
+
+	/// Removes a member from the group, unlinking it from the others. If the leader is removed, the next member takes
+	/// over.
+	///
+	/// # Panics
+	///
+	/// If `index` is out of bounds.
+	pub fn remove(&mut self, index: usize) -> C {
+		if index == 0 {
+			self.unlink_all();
+			let channel = self.members.remove(0);
+			self.link_all();
+			channel
+		} else {
+			let channel = self.members.remove(index);
+			// The link may already be gone if the channel has been freed.
+			let _ = self.members[0].remove_link(channel.handle());
+			channel
+		}
+	}
+
+	/// Unlinks every member and hands them back.
+	pub fn into_members(mut self) -> Vec<C> {
+		self.unlink_all();
+		mem::take(&mut self.members)
+	}
+
+	pub fn members(&self) -> &[C] {
+		&self.members
+	}
+
+	pub fn get(&self, index: usize) -> Option<&C> {
+		self.members.get(index)
+	}
+
+	/// The member whose playback the rest are linked to.
+	pub fn leader(&self) -> Option<&C> {
+		self.members.first()
+	}
+
+	pub fn len(&self) -> usize {
+		self.members.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.members.is_empty()
+	}
+
+	/// Whether a channel is a member of the group.
+	pub fn contains(&self, channel: impl Into<DWORD>) -> bool {
+		let channel = channel.into();
+		self.members.iter().any(|member| member.handle() == channel)
+	}
+
+	/// The state of the leader, which the other members follow. An empty group is stopped.
+	pub fn state(&self) -> ChannelState {
+		self.members.first().map(|leader| leader.is_active()).unwrap_or_default()
+	}
+
+	/// Starts (or restarts) every member at the same time.
+	pub fn play(&self, restart: bool) -> BassResult<()> {
+		match self.members.first() {
+			Some(leader) => leader.play(restart),
+			None => Ok(()),
+		}
+	}
+
+	pub fn pause(&self) -> BassResult<()> {
+		match self.members.first() {
+			Some(leader) => leader.pause(),
+			None => Ok(()),
+		}
+	}
+
+	pub fn stop(&self) -> BassResult<()> {
+		match self.members.first() {
+			Some(leader) => leader.stop(),
+			None => Ok(()),
+		}
+	}
+
+	/// Moves every member to the same time.
+	///
+	/// Positions aren't linked, so if any member is playing, the playing members are paused while each member is
+	/// moved, then the whole group is resumed, to keep the members in step. Every member is moved and the group is
+	/// resumed even if something fails along the way, and then the first error is returned.
+	pub fn seek(&self, time: Duration) -> BassResult<()> {
+		let mut result = Ok(());
+		let mut was_playing = false;
+		for member in &self.members {
+			// Pausing the leader pauses everything linked to it, so this only catches members that are out of step.
+			if member.is_active().is_playing() {
+				was_playing = true;
+				result = result.and(member.pause());
+			}
+		}
+		for member in &self.members {
+			result = result.and(member.set_position(Position::Time(time), SeekOptions::new().flush()));
+		}
+		if was_playing {
+			result = result.and(self.play(false));
+		}
+		result
+	}
+
+	fn check_member(&self, channel: &C) -> BassResult<()> {
+		if self.contains(channel.handle()) {
+			return Err(BassError::new(BassErrorCode::BassErrorAlready, "ChannelGroup::add")
+				.with_handle(channel.handle())
+				.with_context("the channel is already in the group"));
+		}
+		let info = channel_info(channel.handle(), "ChannelGroup::add")?;
+		if (info.flags & BASS_STREAM_DECODE) == BASS_STREAM_DECODE {
+			return Err(BassError::new(BassErrorCode::BassErrorDecode, "ChannelGroup::add")
+				.with_handle(channel.handle())
+				.with_context("decoding channels can't be linked"));
+		}
+		Ok(())
+	}
+
+	fn link_all(&self) {
+		if let Some((leader, rest)) = self.members.split_first() {
+			for member in rest {
+				// Every member was linked to the old leader, so it can be linked to the new one.
+				let _ = leader.set_link(member.handle());
+			}
+		}
+	}
+
+	fn unlink_all(&self) {
+		if let Some((leader, rest)) = self.members.split_first() {
+			for member in rest {
+				// The link may already be gone if a channel has been freed.
+				let _ = leader.remove_link(member.handle());
+			}
+		}
+	}
+}
+
+impl<C: Channel> Drop for ChannelGroup<C> {
+	fn drop(&mut self) {
+		self.unlink_all();
+	}
+}
@@ -0,0 +1,327 @@
+use std::{
+	ffi::CStr,
+	fmt::{self, Display},
+	os::raw::c_char,
+	path::PathBuf,
+};
+
+use bass_sys::*;
+use widestring::U16CStr;
+
+use super::data::channel_info;
+use crate::{bass::plugin::plugin_info, BassResult};
+
+/// `BASS_CTYPE_STREAM_DUMMY`, a stream created with `STREAMPROC_DUMMY`.
+const CTYPE_STREAM_DUMMY: DWORD = DWORD(0x18000);
+/// `BASS_CTYPE_STREAM_DEVICE`, a stream created with `STREAMPROC_DEVICE`.
+const CTYPE_STREAM_DEVICE: DWORD = DWORD(0x18001);
+/// `BASS_CTYPE_STREAM_MIXER`, from the BASSmix add-on.
+const CTYPE_STREAM_MIXER: DWORD = DWORD(0x10800);
+/// `BASS_CTYPE_STREAM_SPLIT`, from the BASSmix add-on.
+const CTYPE_STREAM_SPLIT: DWORD = DWORD(0x10801);
+
+/// Information about a channel, from `Channel::get_info`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelInfo {
+	/// The default sample rate.
+	pub frequency: u32,
+	pub channels: u32,
+	/// The `BASS_SAMPLE_*`, `BASS_STREAM_*`, `BASS_MUSIC_*` and add-on flags the channel was created with.
+	pub flags: DWORD,
+	pub channel_type: ChannelType,
+	/// The format of the channel's own data.
+	pub format: SampleFormat,
+	/// The resolution of the original data, in bits, if it has one (lossy formats don't).
+	pub original_bits: Option<u32>,
+	/// Whether the original data is floating-point.
+	pub original_float: bool,
+	/// The plugin that created the channel, if any.
+	pub plugin: Option<HPLUGIN>,
+	/// The sample a sample channel is playing.
+	pub sample: Option<HSAMPLE>,
+	/// The file the channel was created from, if any.
+	pub filename: Option<PathBuf>,
+}
+
+impl ChannelInfo {
+	pub(crate) fn get(handle: DWORD, operation: &'static str) -> BassResult<Self> {
+		let info = channel_info(handle, operation)?;
+		let resolution = info.origres.0 & 0xffff;
+		Ok(ChannelInfo {
+			frequency: info.freq.0,
+			channels: info.chans.0,
+			flags: info.flags,
+			channel_type: ChannelType::from_raw(info.ctype, info.plugin),
+			format: SampleFormat::from_flags(info.flags),
+			original_bits: (resolution != 0).then_some(resolution),
+			original_float: (info.origres & BASS_ORIGRES_FLOAT) == BASS_ORIGRES_FLOAT,
+			plugin: (info.plugin != 0).then_some(info.plugin),
+			sample: (info.sample != 0).then_some(info.sample),
+			filename: filename(info.filename, info.flags),
+		})
+	}
+
+	pub fn has_flag(&self, flag: DWORD) -> bool {
+		(self.flags & flag) == flag
+	}
+
+	/// Whether the channel is a decoding channel, which can only be read with `Channel::get_data` (or by a mixer).
+	pub fn is_decoding(&self) -> bool {
+		self.has_flag(BASS_STREAM_DECODE)
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.has_flag(BASS_SAMPLE_LOOP)
+	}
+
+	/// Whether the channel has 3D functionality. See `crate::spatial`.
+	pub fn is_3d(&self) -> bool {
+		self.has_flag(BASS_SAMPLE_3D)
+	}
+
+	/// The size of one sample frame of the channel's own data, in bytes.
+	pub fn bytes_per_frame(&self) -> u32 {
+		self.format.bytes() * self.channels.max(1)
+	}
+}
+
+/// The filename is in UTF-16 if the channel was created with `BASS_UNICODE`, which this crate always uses.
+fn filename(filename: *const c_char, flags: DWORD) -> Option<PathBuf> {
+	if filename.is_null() {
+		return None;
+	}
+	let name = if (flags & BASS_UNICODE) == BASS_UNICODE {
+		unsafe { U16CStr::from_ptr_str(filename as *const u16) }.to_string_lossy()
+	} else {
+		unsafe { CStr::from_ptr(filename) }.to_string_lossy().into_owned()
+	};
+	(!name.is_empty()).then(|| PathBuf::from(name))
+}
+
+/// The format of a channel's sample data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SampleFormat {
+	/// 8-bit unsigned integers.
+	U8,
+	/// 16-bit signed integers.
+	#[default]
+	I16,
+	/// 32-bit floating-point.
+	F32,
+}
+
+impl SampleFormat {
+	pub(crate) fn from_flags(flags: DWORD) -> Self {
+		if (flags & BASS_SAMPLE_FLOAT) == BASS_SAMPLE_FLOAT {
+			SampleFormat::F32
+		} else if (flags & BASS_SAMPLE_8BITS) == BASS_SAMPLE_8BITS {
+			SampleFormat::U8
+		} else {
+			SampleFormat::I16
+		}
+	}
+
+	/// The size of one sample, in bytes.
+	pub fn bytes(self) -> u32 {
+		match self {
+			SampleFormat::U8 => 1,
+			SampleFormat::I16 => 2,
+			SampleFormat::F32 => 4,
+		}
+	}
+}
+
+/// The format of a MOD music.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MusicFormat {
+	Mod,
+	Mtm,
+	S3m,
+	Xm,
+	It,
+}
+
+/// What kind of channel a handle is, from the `BASS_CTYPE_*` in its info.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelType {
+	/// A channel playing a sample.
+	Sample,
+	Recording,
+	/// A stream with a user `STREAMPROC`, or a push stream.
+	User,
+	Dummy,
+	/// A stream of a device's final output mix.
+	Device,
+	Vorbis,
+	Mp1,
+	Mp2,
+	Mp3,
+	Aiff,
+	CoreAudio,
+	MediaFoundation,
+	AndroidMedia,
+	WavPcm,
+	WavFloat,
+	/// A WAVE file in another codec, with its `WAVE_FORMAT_*` tag.
+	Wav {
+		codec: u16,
+	},
+	/// A MOD music, which may be MO3 compressed.
+	Music {
+		format: MusicFormat,
+		mo3: bool,
+	},
+	Mixer,
+	Splitter,
+	#[cfg(feature = "cd")]
+	Cd,
+	/// A format supported by a plugin, with the plugin's name for it.
+	Plugin {
+		ctype: u32,
+		name: Option<String>,
+	},
+	Unknown(u32),
+}
+
+impl ChannelType {
+	/// Identifies a channel type. `plugin` is the `plugin` of the channel's info, used to name plugin formats.
+	pub fn from_raw(ctype: DWORD, plugin: HPLUGIN) -> Self {
+		if plugin != 0 {
+			let name = plugin_info(plugin).ok().and_then(|plugin| {
+				plugin.formats.into_iter().find(|format| format.channel_type == ctype).map(|format| format.name)
+			});
+			return ChannelType::Plugin { ctype: ctype.0, name };
+		}
+		if (ctype & BASS_CTYPE_MUSIC_MOD) == BASS_CTYPE_MUSIC_MOD {
+			let mo3 = (ctype & BASS_CTYPE_MUSIC_MO3) == BASS_CTYPE_MUSIC_MO3;
+			let format = ctype & !BASS_CTYPE_MUSIC_MO3;
+			let format = if format == BASS_CTYPE_MUSIC_MTM {
+				MusicFormat::Mtm
+			} else if format == BASS_CTYPE_MUSIC_S3M {
+				MusicFormat::S3m
+			} else if format == BASS_CTYPE_MUSIC_XM {
+				MusicFormat::Xm
+			} else if format == BASS_CTYPE_MUSIC_IT {
+				MusicFormat::It
+			} else {
+				MusicFormat::Mod
+			};
+			return ChannelType::Music { format, mo3 };
+		}
+		let known = [
+			(BASS_CTYPE_SAMPLE, ChannelType::Sample),
+			(BASS_CTYPE_RECORD, ChannelType::Recording),
+			(BASS_CTYPE_STREAM, ChannelType::User),
+			(CTYPE_STREAM_DUMMY, ChannelType::Dummy),
+			(CTYPE_STREAM_DEVICE, ChannelType::Device),
+			(BASS_CTYPE_STREAM_VORBIS, ChannelType::Vorbis),
+			(BASS_CTYPE_STREAM_MP1, ChannelType::Mp1),
+			(BASS_CTYPE_STREAM_MP2, ChannelType::Mp2),
+			(BASS_CTYPE_STREAM_MP3, ChannelType::Mp3),
+			(BASS_CTYPE_STREAM_AIFF, ChannelType::Aiff),
+			(BASS_CTYPE_STREAM_CA, ChannelType::CoreAudio),
+			(BASS_CTYPE_STREAM_MF, ChannelType::MediaFoundation),
+			(BASS_CTYPE_STREAM_AM, ChannelType::AndroidMedia),
+			(BASS_CTYPE_STREAM_WAV_PCM, ChannelType::WavPcm),
+			(BASS_CTYPE_STREAM_WAV_FLOAT, ChannelType::WavFloat),
+			(CTYPE_STREAM_MIXER, ChannelType::Mixer),
+			(CTYPE_STREAM_SPLIT, ChannelType::Splitter),
+			#[cfg(feature = "cd")]
+			(BASS_CTYPE_STREAM_CD, ChannelType::Cd),
+		];
+		if let Some((_, kind)) = known.into_iter().find(|(raw, _)| *raw == ctype) {
+			kind
+		} else if (ctype & BASS_CTYPE_STREAM_WAV) == BASS_CTYPE_STREAM_WAV {
+			ChannelType::Wav { codec: (ctype.0 & 0xffff) as u16 }
+		} else {
+			ChannelType::Unknown(ctype.0)
+		}
+	}
+
+	/// Whether the channel is a stream of some kind, as opposed to a sample channel, recording or MOD music.
+	pub fn is_stream(&self) -> bool {
+		!matches!(self, ChannelType::Sample | ChannelType::Recording | ChannelType::Music { .. })
+	}
+}
+
+impl Display for ChannelType {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ChannelType::Sample => f.write_str("Sample"),
+			ChannelType::Recording => f.write_str("Recording"),
+			ChannelType::User => f.write_str("User stream"),
+			ChannelType::Dummy => f.write_str("Dummy stream"),
+			ChannelType::Device => f.write_str("Device output"),
+			ChannelType::Vorbis => f.write_str("Ogg Vorbis"),
+			ChannelType::Mp1 => f.write_str("MPEG layer 1"),
+			ChannelType::Mp2 => f.write_str("MPEG layer 2"),
+			ChannelType::Mp3 => f.write_str("MPEG layer 3"),
+			ChannelType::Aiff => f.write_str("Audio IFF"),
+			ChannelType::CoreAudio => f.write_str("CoreAudio"),
+			ChannelType::MediaFoundation => f.write_str("Media Foundation"),
+			ChannelType::AndroidMedia => f.write_str("Android media codec"),
+			ChannelType::WavPcm => f.write_str("WAVE (PCM)"),
+			ChannelType::WavFloat => f.write_str("WAVE (floating-point)"),
+			ChannelType::Wav { codec } => write!(f, "WAVE (codec 0x{codec:04x})"),
+			ChannelType::Music { format, mo3 } => {
+				let name = match format {
+					MusicFormat::Mod => "MOD",
+					MusicFormat::Mtm => "MultiTracker",
+					MusicFormat::S3m => "ScreamTracker 3",
+					MusicFormat::Xm => "FastTracker 2",
+					MusicFormat::It => "Impulse Tracker",
+				};
+				if *mo3 {
+					write!(f, "{name} (MO3)")
+				} else {
+					f.write_str(name)
+				}
+			}
+			ChannelType::Mixer => f.write_str("Mixer"),
+			ChannelType::Splitter => f.write_str("Splitter"),
+			#[cfg(feature = "cd")]
+			ChannelType::Cd => f.write_str("CD audio"),
+			ChannelType::Plugin { name: Some(name), .. } => f.write_str(name),
+			ChannelType::Plugin { ctype, name: None } => write!(f, "Plugin format (0x{ctype:x})"),
+			ChannelType::Unknown(ctype) => write!(f, "Unknown (0x{ctype:x})"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn kind(ctype: DWORD) -> ChannelType {
+		ChannelType::from_raw(ctype, HPLUGIN(DWORD(0)))
+	}
+
+	#[test]
+	fn music_types() {
+		assert_eq!(kind(BASS_CTYPE_MUSIC_MOD), ChannelType::Music { format: MusicFormat::Mod, mo3: false });
+		assert_eq!(kind(BASS_CTYPE_MUSIC_XM), ChannelType::Music { format: MusicFormat::Xm, mo3: false });
+		assert_eq!(
+			kind(BASS_CTYPE_MUSIC_IT | BASS_CTYPE_MUSIC_MO3),
+			ChannelType::Music { format: MusicFormat::It, mo3: true }
+		);
+		assert_eq!(
+			kind(BASS_CTYPE_MUSIC_MTM | BASS_CTYPE_MUSIC_MO3),
+			ChannelType::Music { format: MusicFormat::Mtm, mo3: true }
+		);
+	}
+
+	#[test]
+	fn stream_types() {
+		assert_eq!(kind(BASS_CTYPE_STREAM_WAV_PCM), ChannelType::WavPcm);
+		assert_eq!(kind(BASS_CTYPE_STREAM_WAV | DWORD(0x11)), ChannelType::Wav { codec: 0x11 });
+		assert_eq!(kind(DWORD(0x18000)), ChannelType::Dummy);
+		assert_eq!(kind(DWORD(0x18001)), ChannelType::Device);
+		assert_eq!(kind(DWORD(0x10800)), ChannelType::Mixer);
+		assert_eq!(kind(DWORD(0x10801)), ChannelType::Splitter);
+		assert_eq!(kind(DWORD(0x12345)), ChannelType::Unknown(0x12345));
+		assert!(!kind(BASS_CTYPE_RECORD).is_stream());
+	}
+}
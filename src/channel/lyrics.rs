@@ -0,0 +1,137 @@
+use std::{sync::Mutex, time::Duration};
+
+use bass_sys::*;
+
+use super::{
+	attached::{self, Attached, Callback},
+	Channel,
+};
+use crate::{
+	bass::error::{BassError, BassErrorCode},
+	tags::lyrics::Lyrics,
+	BassResult,
+};
+
+/// A change of the line or word being sung, from a `LyricsDriver`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LyricsEvent {
+	/// The current line changed, by playing into it or seeking. `None` is before the first line.
+	Line(Option<usize>),
+	/// The current word of a line changed. This always follows the `Line` event for its line.
+	Word { line: usize, word: usize },
+}
+
+/// Calls a function whenever the current line or word of synced lyrics changes, replacing polling the position.
+///
+/// Every line and word start has a `BASS_SYNC_POS | BASS_SYNC_MIXTIME` sync, and a `BASS_SYNC_SETPOS` sync (which is
+/// also triggered by looping) works out where the lyrics are after a seek. As they are mixtime syncs, events arrive
+/// when the audio is decoded, which is ahead of when it is heard by the length of the playback buffer. The function is
+/// called in BASS's update thread, so it should just pass the event on, like to a channel.
+///
+/// No more events are sent once the driver is dropped.
+///
+/// ```ignore
+/// let lyrics = Lyrics::read_lrc("song.lrc")?;
+/// let driver = LyricsDriver::attach(&stream, &lyrics, move |event| sender.send(event).unwrap())?;
+/// ```
+#[derive(Debug)]
+pub struct LyricsDriver {
+	cue: Attached<Cue>,
+}
+
+#[derive(Debug)]
+struct Cue {
+	lyrics: Lyrics,
+	/// The current line and word.
+	current: Mutex<(Option<usize>, Option<usize>)>,
+	callback: Callback<LyricsEvent>,
+}
+
+impl LyricsDriver {
+	/// Attaches to a channel, and sends the events for the channel's current position. Plain lyrics are rejected with
+	/// `BASS_ERROR_ILLPARAM`, as there is nothing to time.
+	pub fn attach(
+		channel: &(impl Channel + ?Sized),
+		lyrics: &Lyrics,
+		callback: impl FnMut(LyricsEvent) + Send + Sync + 'static,
+	) -> BassResult<Self> {
+		if !lyrics.synced {
+			return Err(BassError::new(BassErrorCode::BassErrorIllParam, "LyricsDriver::attach")
+				.with_handle(channel.handle())
+				.with_context("the lyrics aren't synced"));
+		}
+		let mut cue = Attached::new(Cue {
+			lyrics: lyrics.clone(),
+			current: Mutex::new((None, None)),
+			callback: Callback::new(callback),
+		});
+
+		for (index, line) in lyrics.lines.iter().enumerate() {
+			cue.set_sync(
+				channel,
+				BASS_SYNC_POS | BASS_SYNC_MIXTIME,
+				channel.duration_to_bytes(line.start)?,
+				move |cue, _| cue.reached_line(index),
+			)?;
+			for (word, at) in line.words.iter().enumerate() {
+				cue.set_sync(
+					channel,
+					BASS_SYNC_POS | BASS_SYNC_MIXTIME,
+					channel.duration_to_bytes(at.start)?,
+					move |cue, _| cue.moved(Some(index), Some(word)),
+				)?;
+			}
+		}
+		cue.set_sync(channel, BASS_SYNC_SETPOS | BASS_SYNC_MIXTIME, 0, Cue::resync)?;
+
+		cue.state().resync(channel.handle());
+		Ok(LyricsDriver { cue })
+	}
+
+	/// The current line and word.
+	pub fn current(&self) -> (Option<usize>, Option<usize>) {
+		*self.cue.state().current.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	pub fn lyrics(&self) -> &Lyrics {
+		&self.cue.state().lyrics
+	}
+
+	/// The channel the driver is attached to.
+	pub fn channel(&self) -> Option<DWORD> {
+		self.cue.channel()
+	}
+}
+
+impl Cue {
+	fn reached_line(&self, line: usize) {
+		let start = self.lyrics.lines[line].start;
+		self.moved(Some(line), self.lyrics.word_at(line, start))
+	}
+
+	/// Sends the events for a move to a line and word, if they have changed. Syncs at the same position can be called
+	/// in either order, so this is given the word too when a line starts.
+	fn moved(&self, line: Option<usize>, word: Option<usize>) {
+		// Holding this while calling the function keeps the events in order, whichever thread the syncs are called on.
+		let mut current = self.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		if current.0 != line {
+			self.callback.call(LyricsEvent::Line(line));
+			*current = (line, None);
+		}
+		if let (Some(line), Some(word)) = (line, word) {
+			if current.1 != Some(word) {
+				self.callback.call(LyricsEvent::Word { line, word });
+				current.1 = Some(word);
+			}
+		}
+	}
+
+	/// Called after a seek or loop: works out the line and word at the new position. The syncs are mixtime, so this is
+	/// the decode position, which playback hasn't caught up with yet.
+	fn resync(&self, channel: DWORD) {
+		let Some(seconds) = attached::position_seconds(channel, true) else { return };
+		let now = Duration::from_secs_f64(seconds);
+		let line = self.lyrics.line_at(now);
+		self.moved(line, line.and_then(|line| self.lyrics.word_at(line, now)))
+	}
+}
@@ -1,20 +1,46 @@
+pub mod any;
+mod attached;
+pub mod attribute;
+pub mod automation;
+pub mod chapters;
+pub mod data;
+pub mod group;
+pub mod info;
+pub mod lyrics;
+pub mod position;
+pub mod spectrum;
+pub mod state;
+
 use std::{
 	ops::DerefMut,
 	os::raw::c_void,
 	ptr::null_mut,
 	slice,
 	sync::{Arc, Mutex, MutexGuard, Weak},
+	time::Duration,
 };
 
+use attribute::{Attribute, ChannelAttribute, Slide};
 use bass_sys::*;
+use chapters::ChapterWatcher;
+use data::PcmSample;
 use handle::HasHandle;
+use info::ChannelInfo;
+use position::{Position, PositionUnit, SeekOptions};
+use spectrum::{FftOptions, FftSize, Spectrum};
+use state::ChannelState;
 
 use crate::{
-	bass::error::BassError,
+	bass::{
+		error::BassError,
+		handles::{self, HandleKind},
+	},
 	dsp::{BassDsp, DspUserData},
 	functions::make_word,
 	fx::BassFx,
+	spatial::{Attributes3d, Position3d},
 	sync::{BassSync, SyncUserData},
+	tags::{self, chapter::Chapter, TagBlock, Tags},
 	BassResult,
 };
 
@@ -22,43 +48,25 @@ use crate::{
 extern "C" fn sync_handler<T: Send + Sync>(handle: HSYNC, channel: DWORD, data: DWORD, user: *mut c_void) {
 	// let mut user_box = unsafe { Arc::from_raw(user as *mut SyncUserData<T>) };
 	let f = |mut user_box: MutexGuard<'_, SyncUserData<T>>| {
-		#[cfg(debug_assertions)]
-		println!("deref_mut'ing SyncUserData...");
 		let user_box = user_box.deref_mut();
-		#[cfg(debug_assertions)]
-		println!("Running user SyncProc...");
 		(user_box.0)(user_box.1.as_mut(), handle, channel, data);
-		#[cfg(debug_assertions)]
-		println!("Success!");
 	};
 	// Attempt to upgrade the weak pointer, failing gracefully if it has already been dropped.
 	match unsafe { Weak::from_raw(user as *const Mutex<SyncUserData<T>>) }.upgrade() {
 		Some(arc) => {
-			#[cfg(debug_assertions)]
-			println!("Strong: {}; Weak: {}", Arc::strong_count(&arc), Arc::weak_count(&arc));
 			// Handle mutex locking.
-			#[cfg(debug_assertions)]
-			println!("Locking Mutex...");
 			match arc.lock() {
 				Ok(user_box) => f(user_box),
 				Err(e) => f(e.into_inner()),
 			}
-			#[cfg(debug_assertions)]
-			println!("Resetting weak count...");
 			// Reset the weak count.
 			let weak = Arc::downgrade(&arc);
 			// Equivalent to std::mem::forget in a way...
 			let _ = weak.into_raw();
-			#[cfg(debug_assertions)]
-			println!("Hopefully success?");
-		}
-		None => {
-			#[cfg(debug_assertions)]
-			println!("User data freed")
 		}
+		// The user data has already been freed, so there is nothing to call.
+		None => {}
 	}
-	#[cfg(debug_assertions)]
-	println!("Everything should drop now.");
 }
 
 extern "C" fn dsp_handler<T: Send + Sync>(
@@ -73,44 +81,26 @@ extern "C" fn dsp_handler<T: Send + Sync>(
 	// (user_box.0)(user_box.1.as_mut(), &mut data, handle, channel); // FIXME!
 	// Box::into_raw(user_box);
 	let f = |mut user_box: MutexGuard<'_, DspUserData<T>>| {
-		#[cfg(debug_assertions)]
-		println!("deref_mut'ing DspUserData...");
 		let user_box = user_box.deref_mut();
-		#[cfg(debug_assertions)]
-		println!("Running user DspProc...");
 		let mut data = unsafe { slice::from_raw_parts_mut(buffer as *mut f32, (length.0 / 4) as usize) };
 		(user_box.0)(user_box.1.as_mut(), &mut data, handle, channel);
-		#[cfg(debug_assertions)]
-		println!("Success!");
 	};
 	// Attempt to upgrade the weak pointer, failing gracefully if it has already been dropped.
 	match unsafe { Weak::from_raw(user as *const Mutex<DspUserData<T>>) }.upgrade() {
 		Some(arc) => {
-			#[cfg(debug_assertions)]
-			println!("Strong: {}; Weak: {}", Arc::strong_count(&arc), Arc::weak_count(&arc));
 			// Handle mutex locking.
-			#[cfg(debug_assertions)]
-			println!("Locking Mutex...");
 			match arc.lock() {
 				Ok(user_box) => f(user_box),
 				Err(e) => f(e.into_inner()),
 			}
-			#[cfg(debug_assertions)]
-			println!("Resetting weak count...");
 			// Reset the weak count.
 			let weak = Arc::downgrade(&arc);
 			// Equivalent to std::mem::forget in a way...
 			let _ = weak.into_raw();
-			#[cfg(debug_assertions)]
-			println!("Hopefully success?");
-		}
-		None => {
-			#[cfg(debug_assertions)]
-			println!("User data freed")
 		}
+		// The user data has already been freed, so there is nothing to call.
+		None => {}
 	}
-	#[cfg(debug_assertions)]
-	println!("Everything should drop now.");
 }
 
 pub(crate) mod handle {
@@ -121,25 +111,23 @@ pub(crate) mod handle {
 	}
 }
 
+/// The methods every kind of channel has.
+///
+/// This is object-safe, so channels of different kinds can be kept together as `Box<dyn Channel>` or `&dyn Channel`.
+/// The methods that register callbacks or take generic buffers are in `ChannelExt`.
 pub trait Channel: handle::HasHandle {
 	#[inline]
 	fn raw_handle(&self) -> DWORD {
 		HasHandle::handle(self)
 	}
 
-	/// Equivalent to `self.handle() == channel`.
-	#[inline]
-	fn represents_handle(&self, channel: impl Into<DWORD>) -> bool {
-		self.handle() == channel.into()
-	}
-
 	#[inline]
-	fn bytes_to_seconds(&self, position: impl Into<QWORD>) -> BassResult<f64> {
-		let value = BASS_ChannelBytes2Seconds(self.handle(), position);
+	fn bytes_to_duration(&self, bytes: u64) -> BassResult<Duration> {
+		let value = BASS_ChannelBytes2Seconds(self.handle(), bytes);
 		if value >= 0. {
-			Ok(value)
+			Ok(Duration::from_secs_f64(value))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::bytes_to_duration").with_handle(self.handle()))
 		}
 	}
 
@@ -149,7 +137,7 @@ pub trait Channel: handle::HasHandle {
 		if ok != -1 {
 			Ok(ok)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::flag_remove").with_handle(self.handle()))
 		}
 	}
 
@@ -159,20 +147,39 @@ pub trait Channel: handle::HasHandle {
 		if ok != -1 {
 			Ok(ok)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::flag_set").with_handle(self.handle()))
 		}
 	}
 
-	// 3D Attributes TODO
+	/// The channel's 3D attributes. Only channels created with `BASS_SAMPLE_3D` have them.
+	fn get_3d_attributes(&self) -> BassResult<Attributes3d> {
+		Attributes3d::get(self.handle(), "Channel::get_3d_attributes")
+	}
 
-	#[inline]
-	fn get_attribute(&self, attribute: DWORD) -> BassResult<f32> {
+	/// Sets the channel's 3D attributes. Like other 3D changes, this only takes effect after `Bass::apply_3d`.
+	fn set_3d_attributes(&self, attributes: Attributes3d) -> BassResult<()> {
+		attributes.set(self.handle(), "Channel::set_3d_attributes")
+	}
+
+	fn get_3d_position(&self) -> BassResult<Position3d> {
+		Position3d::get(self.handle(), "Channel::get_3d_position")
+	}
+
+	/// Sets the channel's 3D position, orientation and velocity. This only takes effect after `Bass::apply_3d`.
+	fn set_3d_position(&self, position: Position3d) -> BassResult<()> {
+		position.set(self.handle(), "Channel::set_3d_position")
+	}
+
+	fn get_attribute(&self, attribute: ChannelAttribute) -> BassResult<Attribute> {
+		attribute.check_applies(self.handle(), "Channel::get_attribute")?;
 		let mut value: f32 = 0.;
-		let ok = BASS_ChannelGetAttribute(self.handle(), attribute, &mut value);
+		let ok = BASS_ChannelGetAttribute(self.handle(), attribute.raw(), &mut value);
 		if ok {
-			Ok(value)
+			Ok(Attribute::from_f32(attribute, value))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::get_attribute")
+				.with_handle(self.handle())
+				.with_context(format!("{attribute:?}")))
 		}
 	}
 
@@ -184,28 +191,49 @@ pub trait Channel: handle::HasHandle {
 		if device as i32 != -1 {
 			Ok(device)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::get_device").with_handle(self.handle()))
 		}
 	}
 
 	#[inline]
-	fn get_info(&self) -> BassResult<BASS_CHANNELINFO> {
-		let mut info = BASS_CHANNELINFO::default();
-		let ok = BASS_ChannelGetInfo(self.handle(), &mut info); // This should always succeed.
-		if ok {
-			Ok(info)
-		} else {
-			Err(BassError::get())
-		}
+	fn get_info(&self) -> BassResult<ChannelInfo> {
+		ChannelInfo::get(self.handle(), "Channel::get_info")
 	}
 
-	#[inline]
-	fn get_length(&self, mode: DWORD) -> BassResult<u64> {
-		let ok = BASS_ChannelGetLength(self.handle(), mode);
+	/// The raw blocks of every kind of tag the channel has.
+	fn raw_tags(&self) -> BassResult<Vec<TagBlock>> {
+		tags::read(self.handle(), "Channel::raw_tags")
+	}
+
+	/// The channel's tags, parsed from every block it has.
+	fn tags(&self) -> BassResult<Tags> {
+		let blocks = tags::read(self.handle(), "Channel::tags")?;
+		Ok(Tags::parse(&blocks))
+	}
+
+	fn get_length(&self, unit: PositionUnit) -> BassResult<Position> {
+		unit.check_applies(self.handle(), "Channel::get_length")?;
+		let ok = BASS_ChannelGetLength(self.handle(), unit.raw_mode());
 		if ok.0 as i64 != -1 {
-			Ok(ok.0)
+			Position::from_raw(unit, self.handle(), ok.0, "Channel::get_length")
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::get_length").with_handle(self.handle()))
+		}
+	}
+
+	/// The length of the channel in bytes.
+	fn length_bytes(&self) -> BassResult<u64> {
+		match self.get_length(PositionUnit::Bytes)? {
+			Position::Bytes(bytes) => Ok(bytes),
+			_ => unreachable!("lengths are read in the requested unit"),
+		}
+	}
+
+	/// The length of the channel as a `Duration`.
+	fn length_time(&self) -> BassResult<Duration> {
+		match self.get_length(PositionUnit::Time)? {
+			Position::Time(time) => Ok(time),
+			_ => unreachable!("lengths are read in the requested unit"),
 		}
 	}
 
@@ -215,72 +243,76 @@ pub trait Channel: handle::HasHandle {
 		if ok.0 as i32 != -1 {
 			Ok(make_word(ok))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::get_level").with_handle(self.handle()))
 		}
 	}
 
 	fn get_level_ex(&self, length: f32, flags: Option<DWORD>) -> BassResult<Vec<f32>> {
-		let mono = (flags.unwrap_or_default() & BASS_LEVEL_MONO) == BASS_LEVEL_MONO;
-		let stereo = (flags.unwrap_or_default() & BASS_LEVEL_STEREO) == BASS_LEVEL_STEREO;
-		if mono || stereo {
-			let mut levels = Vec::with_capacity(2);
-			#[cfg(debug_assertions)]
-			println!("Getting levels in mono or stereo mode.");
-			let ok = unsafe {
-				BASS_ChannelGetLevelEx(self.handle(), levels.as_mut_ptr(), length, flags.unwrap_or_default())
-			};
-			if ok {
-				unsafe {
-					levels.set_len(if mono { 1 } else { 2 });
-				}
-				Ok(levels)
-			} else {
-				Err(BassError::get())
-			}
+		let flags = flags.unwrap_or_default();
+		let count = if (flags & BASS_LEVEL_MONO) == BASS_LEVEL_MONO {
+			1
+		} else if (flags & BASS_LEVEL_STEREO) == BASS_LEVEL_STEREO {
+			2
 		} else {
-			let mut info = BASS_CHANNELINFO::default();
-			let ok = BASS_ChannelGetInfo(self.handle(), &mut info); // This should always succeed.
-			if ok {
-				let chan_count = info.chans.0 as usize;
-				let mut levels = Vec::with_capacity(chan_count);
-				#[cfg(debug_assertions)]
-				println!("Getting levels for channel with {chan_count} channels.");
-				let ok = unsafe {
-					BASS_ChannelGetLevelEx(self.handle(), levels.as_mut_ptr(), length, flags.unwrap_or_default())
-				};
-				if ok {
-					unsafe {
-						levels.set_len(chan_count);
-					}
-					Ok(levels)
-				} else {
-					Err(BassError::get())
-				}
-			} else {
-				Err(BassError::get())
-			}
+			data::channel_info(self.handle(), "Channel::get_level_ex")?.chans.0 as usize
+		};
+		trace!("Getting levels for {count} channels");
+		let mut levels = vec![0.; count];
+		let ok = unsafe { BASS_ChannelGetLevelEx(self.handle(), levels.as_mut_ptr(), length, flags) };
+		if ok {
+			Ok(levels)
+		} else {
+			Err(BassError::get("Channel::get_level_ex").with_handle(self.handle()))
 		}
 	}
 
-	#[inline]
-	fn get_position(&self, mode: DWORD) -> BassResult<u64> {
-		let value = BASS_ChannelGetPosition(self.handle(), mode);
+	/// The number of frames that can be read with `get_data` without waiting (`BASS_DATA_AVAILABLE`).
+	fn data_available(&self) -> BassResult<usize> {
+		data::available(
+			self.handle(),
+			|handle, buffer, length| unsafe { BASS_ChannelGetData(handle, buffer, length) },
+			"Channel::data_available",
+		)
+	}
+
+	/// Performs an FFT on the data the channel is about to play (or the next data of a decoding channel).
+	fn spectrum(&self, size: FftSize, options: FftOptions) -> BassResult<Spectrum> {
+		spectrum::spectrum(
+			self.handle(),
+			size,
+			options,
+			|handle, buffer, length| unsafe { BASS_ChannelGetData(handle, buffer, length) },
+			"Channel::spectrum",
+		)
+	}
+
+	fn get_position(&self, unit: PositionUnit) -> BassResult<Position> {
+		unit.check_applies(self.handle(), "Channel::get_position")?;
+		let value = BASS_ChannelGetPosition(self.handle(), unit.raw_mode());
 		if value.0 as i64 != -1 {
-			Ok(value.0)
+			Position::from_raw(unit, self.handle(), value.0, "Channel::get_position")
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::get_position").with_handle(self.handle()))
+		}
+	}
+
+	/// The current playback (or decoding) position as a `Duration`.
+	fn position_time(&self) -> BassResult<Duration> {
+		match self.get_position(PositionUnit::Time)? {
+			Position::Time(time) => Ok(time),
+			_ => unreachable!("positions are read in the requested unit"),
 		}
 	}
 
+	/// Whether the channel is playing, paused, stalled or stopped. Use a `StateTracker` to be told when this changes.
 	#[inline]
-	/// TODO :: Use an enum?
-	fn is_active(&self) -> DWORD {
-		BASS_ChannelIsActive(self.handle())
+	fn is_active(&self) -> ChannelState {
+		BASS_ChannelIsActive(self.handle()).into()
 	}
 
 	#[inline]
-	fn is_sliding(&self, attrib: DWORD) -> bool {
-		BASS_ChannelIsSliding(self.handle(), attrib)
+	fn is_sliding(&self, attribute: ChannelAttribute) -> bool {
+		BASS_ChannelIsSliding(self.handle(), attribute.raw())
 	}
 
 	#[inline]
@@ -289,7 +321,7 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::lock").with_handle(self.handle()))
 		}
 	}
 
@@ -299,7 +331,7 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::unlock").with_handle(self.handle()))
 		}
 	}
 
@@ -309,7 +341,7 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::pause").with_handle(self.handle()))
 		}
 	}
 
@@ -319,7 +351,7 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::play").with_handle(self.handle()))
 		}
 	}
 
@@ -328,7 +360,7 @@ pub trait Channel: handle::HasHandle {
 	// 	if ok {
 	// 		Ok(())
 	// 	} else {
-	// 		Err(BassError::get())
+	// 		Err(BassError::get("Channel::remove_dsp").with_handle(self.handle()))
 	// 	}
 	// }
 
@@ -338,17 +370,17 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::remove_fx").with_handle(self.handle()))
 		}
 	}
 
 	#[inline]
-	fn remove_link(&self, channel: impl Into<DWORD>) -> BassResult<()> {
+	fn remove_link(&self, channel: DWORD) -> BassResult<()> {
 		let ok = BASS_ChannelRemoveLink(self.handle(), channel);
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::remove_link").with_handle(self.handle()))
 		}
 	}
 
@@ -357,29 +389,60 @@ pub trait Channel: handle::HasHandle {
 	// 	if ok {
 	// 		Ok(())
 	// 	} else {
-	// 		Err(BassError::get())
+	// 		Err(BassError::get("Channel::remove_sync").with_handle(self.handle()))
 	// 	}
 	// }
 
 	#[inline]
-	fn seconds_to_bytes(&self, seconds: f64) -> BassResult<u64> {
-		let value = BASS_ChannelSeconds2Bytes(self.handle(), seconds);
+	fn duration_to_bytes(&self, duration: Duration) -> BassResult<u64> {
+		let value = BASS_ChannelSeconds2Bytes(self.handle(), duration.as_secs_f64());
 		if value.0 as i64 != -1 {
 			Ok(value.0)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::duration_to_bytes").with_handle(self.handle()))
 		}
 	}
 
-	// Set 3D Attributes...
+	/// The index of the chapter the channel's position is in.
+	fn current_chapter(&self, chapters: &[Chapter]) -> BassResult<Option<usize>> {
+		Ok(chapters::chapter_at(chapters, self.position_time()?))
+	}
 
-	#[inline]
-	fn set_attribute(&self, attribute: DWORD, value: f32) -> BassResult<()> {
-		let ok = BASS_ChannelSetAttribute(self.handle(), attribute, value);
+	/// Seeks to the start of the chapter after the current one, and returns its index. Returns `None` without seeking
+	/// if there isn't one.
+	fn next_chapter(&self, chapters: &[Chapter]) -> BassResult<Option<usize>> {
+		let next = chapters::next(chapters, self.position_time()?);
+		if let Some(index) = next {
+			self.seek(chapters[index].start)?;
+		}
+		Ok(next)
+	}
+
+	/// Seeks to the start of the previous chapter, and returns its index. Like most players, this restarts the
+	/// current chapter instead if it has played for more than `chapters::RESTART_THRESHOLD`.
+	fn previous_chapter(&self, chapters: &[Chapter]) -> BassResult<Option<usize>> {
+		let previous = chapters::previous(chapters, self.position_time()?);
+		if let Some(index) = previous {
+			self.seek(chapters[index].start)?;
+		}
+		Ok(previous)
+	}
+
+	/// Equivalent to `set_position(Position::Time(time), SeekOptions::default())`.
+	fn seek(&self, time: Duration) -> BassResult<()> {
+		self.set_position(Position::Time(time), SeekOptions::default())
+	}
+
+	fn set_attribute(&self, attribute: Attribute) -> BassResult<()> {
+		attribute.validate("Channel::set_attribute")?;
+		attribute.attribute().check_applies(self.handle(), "Channel::set_attribute")?;
+		let ok = BASS_ChannelSetAttribute(self.handle(), attribute.attribute().raw(), attribute.to_f32());
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::set_attribute")
+				.with_handle(self.handle())
+				.with_context(format!("{attribute:?}")))
 		}
 	}
 
@@ -389,27 +452,7 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
-		}
-	}
-
-	/// To use user data, the recommended way is a static Arc/Mutex
-	fn set_dsp<T: Send + Sync>(
-		&self,
-		priority: i32,
-		user_data: T,
-		proc: impl FnMut(&mut T, &mut [f32], HDSP, DWORD) + Send + Sync + 'static,
-	) -> BassResult<BassDsp<T>> {
-		let data = Box::new(user_data);
-		let user = Arc::new(Mutex::new(DspUserData(Box::new(proc), data)));
-		// let raw = Box::into_raw(user);
-		let weak = Arc::downgrade(&user);
-		let dsp = BASS_ChannelSetDSP(self.handle(), Some(dsp_handler::<T>), weak.into_raw() as *mut Mutex<DspUserData<T>>, priority);
-		println!("HDSP: {:?}", dsp);
-		if dsp != 0 {
-			Ok(BassDsp { dsp, channel: self.handle(), user })
-		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::set_device").with_handle(self.handle()))
 		}
 	}
 
@@ -420,46 +463,21 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::set_link").with_handle(self.handle()))
 		}
 	}
 
-	#[inline]
-	fn set_position(&self, position: impl Into<QWORD>, mode: DWORD) -> BassResult<()> {
-		let ok = BASS_ChannelSetPosition(self.handle(), position, mode);
+	/// Positions that don't apply to the channel (such as an order and row on anything but a MOD music) are rejected
+	/// with `BASS_ERROR_ILLTYPE`.
+	fn set_position(&self, position: Position, options: SeekOptions) -> BassResult<()> {
+		let (value, mode) = position.to_raw(self.handle(), options, "Channel::set_position")?;
+		let ok = BASS_ChannelSetPosition(self.handle(), value, mode);
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
-		}
-	}
-
-	/// The `BassSync` type holds the user data.
-	/// You must hold the `BassSync` until you no longer wish to have the sync.
-	/// The Sync will free itself automatically when dropped.
-	fn set_sync<T: Send + Sync>(
-		&self,
-		sync_type: DWORD,
-		parameter: impl Into<QWORD>,
-		proc: impl FnMut(&mut T, HSYNC, DWORD, DWORD) + Send + Sync + 'static,
-		user_data: T,
-	) -> BassResult<BassSync<T>> {
-		// let sync = BASS_ChannelSetSync(self.handle(), sync_type & (!BASS_SYNC_ONETIME), parameter, proc, null_mut() as *mut c_void);
-		let data = Box::new(user_data);
-		let user = Arc::new(Mutex::new(SyncUserData(Box::new(proc), data)));
-		let weak = Arc::downgrade(&user);
-		let sync = BASS_ChannelSetSync(
-			self.handle(),
-			sync_type,
-			parameter,
-			Some(sync_handler::<T>),
-			weak.into_raw() as *mut Mutex<SyncUserData<T>>,
-		);
-		println!("Sync: {:?}", sync);
-		if sync != 0 {
-			Ok(BassSync { sync, channel: self.handle(), user })
-		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::set_position")
+				.with_handle(self.handle())
+				.with_context(format!("{position:?}")))
 		}
 	}
 
@@ -469,17 +487,23 @@ pub trait Channel: handle::HasHandle {
 	// 	if sync != 0 {
 	// 		Ok(BassSyncOnce { sync, channel: self.handle() })
 	// 	} else {
-	// 		Err(BassError::get())
+	// 		Err(BassError::get("Channel::set_sync_once").with_handle(self.handle()))
 	// 	}
 	// }
 
-	#[inline]
-	fn slide_attribute(&self, attribute: DWORD, value: f32, milliseconds: u32) -> BassResult<()> {
-		let ok = BASS_ChannelSlideAttribute(self.handle(), attribute, value, milliseconds);
+	/// Slides an attribute from its current value to `slide.target`. A `BASS_SYNC_SLIDE` sync is called when it
+	/// finishes.
+	fn slide_attribute(&self, slide: Slide) -> BassResult<()> {
+		slide.target.validate("Channel::slide_attribute")?;
+		slide.target.attribute().check_applies(self.handle(), "Channel::slide_attribute")?;
+		let milliseconds = slide.duration.as_millis().min(u32::MAX as u128) as u32;
+		let ok = BASS_ChannelSlideAttribute(self.handle(), slide.raw_attribute(), slide.target.to_f32(), milliseconds);
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::slide_attribute")
+				.with_handle(self.handle())
+				.with_context(format!("{:?}", slide.target)))
 		}
 	}
 
@@ -489,7 +513,7 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::start").with_handle(self.handle()))
 		}
 	}
 
@@ -499,7 +523,7 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::stop").with_handle(self.handle()))
 		}
 	}
 
@@ -509,19 +533,131 @@ pub trait Channel: handle::HasHandle {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Channel::update").with_handle(self.handle()))
+		}
+	}
+}
+
+/// The `Channel` methods that take generic arguments, which can't be called on a `dyn Channel` directly.
+///
+/// Every `Channel` (including `dyn Channel`) implements this, so it only needs to be imported.
+pub trait ChannelExt: Channel {
+	/// Equivalent to `self.handle() == channel`.
+	#[inline]
+	fn represents_handle(&self, channel: impl Into<DWORD>) -> bool {
+		self.handle() == channel.into()
+	}
+
+	/// Reads sample data from a decoding channel, or the data a playing channel is about to play, into `buffer`.
+	///
+	/// `f32` buffers work with any channel, while `i16` and `u8` buffers need a 16-bit or 8-bit channel. Only whole
+	/// frames (one sample for every channel) are read, and the number of frames written is returned.
+	fn get_data<S: PcmSample>(&self, buffer: &mut [S]) -> BassResult<usize> {
+		data::read(
+			self.handle(),
+			buffer,
+			DWORD(0),
+			|handle, buffer, length| unsafe { BASS_ChannelGetData(handle, buffer, length) },
+			"Channel::get_data",
+		)
+	}
+
+	/// Equivalent to `get_data`, but leaves the data in a recording channel's buffer (`BASS_DATA_NOREMOVE`).
+	fn peek_data<S: PcmSample>(&self, buffer: &mut [S]) -> BassResult<usize> {
+		data::read(
+			self.handle(),
+			buffer,
+			BASS_DATA_NOREMOVE,
+			|handle, buffer, length| unsafe { BASS_ChannelGetData(handle, buffer, length) },
+			"Channel::peek_data",
+		)
+	}
+
+	/// Calls a function with a chapter's index whenever playback moves into it. See `ChapterWatcher`.
+	fn watch_chapters(
+		&self,
+		chapters: &[Chapter],
+		callback: impl FnMut(usize) + Send + Sync + 'static,
+	) -> BassResult<ChapterWatcher> {
+		ChapterWatcher::attach(self, chapters, callback)
+	}
+
+	/// To use user data, the recommended way is a static Arc/Mutex
+	fn set_dsp<T: Send + Sync>(
+		&self,
+		priority: i32,
+		user_data: T,
+		proc: impl FnMut(&mut T, &mut [f32], HDSP, DWORD) + Send + Sync + 'static,
+	) -> BassResult<BassDsp<T>> {
+		let data = Box::new(user_data);
+		let user = Arc::new(Mutex::new(DspUserData(Box::new(proc), data)));
+		// let raw = Box::into_raw(user);
+		let weak = Arc::downgrade(&user);
+		let dsp = BASS_ChannelSetDSP(self.handle(), Some(dsp_handler::<T>), weak.into_raw() as *mut Mutex<DspUserData<T>>, priority);
+		trace!("Set DSP {:?} on channel {:?}", dsp, self.handle());
+		if dsp != 0 {
+			handles::register(HandleKind::Dsp, dsp, Some(self.handle()));
+			Ok(BassDsp { dsp, channel: self.handle(), user })
+		} else {
+			Err(BassError::get("Channel::set_dsp").with_handle(self.handle()))
+		}
+	}
+
+	/// The `BassSync` type holds the user data.
+	/// You must hold the `BassSync` until you no longer wish to have the sync.
+	/// The Sync will free itself automatically when dropped.
+	fn set_sync<T: Send + Sync>(
+		&self,
+		sync_type: DWORD,
+		parameter: impl Into<QWORD>,
+		proc: impl FnMut(&mut T, HSYNC, DWORD, DWORD) + Send + Sync + 'static,
+		user_data: T,
+	) -> BassResult<BassSync<T>> {
+		// let sync = BASS_ChannelSetSync(self.handle(), sync_type & (!BASS_SYNC_ONETIME), parameter, proc, null_mut() as *mut c_void);
+		let data = Box::new(user_data);
+		let user = Arc::new(Mutex::new(SyncUserData(Box::new(proc), data)));
+		let weak = Arc::downgrade(&user);
+		let sync = BASS_ChannelSetSync(
+			self.handle(),
+			sync_type,
+			parameter,
+			Some(sync_handler::<T>),
+			weak.into_raw() as *mut Mutex<SyncUserData<T>>,
+		);
+		trace!("Set sync {:?} on channel {:?}", sync, self.handle());
+		if sync != 0 {
+			handles::register(HandleKind::Sync, sync, Some(self.handle()));
+			Ok(BassSync { sync, channel: self.handle(), user })
+		} else {
+			Err(BassError::get("Channel::set_sync").with_handle(self.handle()))
 		}
 	}
 }
 
-impl PartialEq for dyn handle::HasHandle {
+impl<C: Channel + ?Sized> ChannelExt for C {}
+
+impl<C: Channel + ?Sized> HasHandle for &C {
+	fn handle(&self) -> DWORD {
+		(**self).handle()
+	}
+}
+impl<C: Channel + ?Sized> Channel for &C {}
+
+impl<C: Channel + ?Sized> HasHandle for Box<C> {
+	fn handle(&self) -> DWORD {
+		(**self).handle()
+	}
+}
+impl<C: Channel + ?Sized> Channel for Box<C> {}
+
+impl PartialEq for dyn Channel + '_ {
 	fn eq(&self, other: &Self) -> bool {
 		self.handle() == other.handle()
 	}
 }
-impl Eq for dyn handle::HasHandle {}
+impl Eq for dyn Channel + '_ {}
 
-impl PartialEq<DWORD> for dyn handle::HasHandle {
+impl PartialEq<DWORD> for dyn Channel + '_ {
 	fn eq(&self, other: &DWORD) -> bool {
 		self.handle() == *other
 	}
@@ -534,8 +670,10 @@ pub(crate) mod mixer {
 
 #[cfg(feature = "mixer")]
 pub trait MixerSource: Channel + mixer::MixableChannel {
-	fn mixer_channel_active(&self) -> DWORD {
-		BASS_Mixer_ChannelIsActive(self.handle())
+	/// The state of the channel within its mixer, which is `ChannelState::Paused` if it has been paused with
+	/// `BASS_MIXER_CHAN_PAUSE`.
+	fn mixer_channel_active(&self) -> ChannelState {
+		BASS_Mixer_ChannelIsActive(self.handle()).into()
 	}
 
 	fn mixer_channel_flag_remove(&self, flag: DWORD) -> BassResult<DWORD> {
@@ -543,7 +681,7 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok != -1 {
 			Ok(ok)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_flag_remove").with_handle(self.handle()))
 		}
 	}
 
@@ -552,7 +690,7 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok != -1 {
 			Ok(ok)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_flag_set").with_handle(self.handle()))
 		}
 	}
 
@@ -562,59 +700,58 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok.0 as i64 != -1 {
 			Ok((ok, value))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_get_envelope_pos").with_handle(self.handle()))
 		}
 	}
 
+	/// See docs for `Channel::get_data`. This reads the data the mixer is about to mix in, and requires the source
+	/// to have been added with `BASS_MIXER_CHAN_BUFFER`.
+	fn mixer_channel_get_data<S: PcmSample>(&self, buffer: &mut [S]) -> BassResult<usize> {
+		data::read(
+			self.handle(),
+			buffer,
+			DWORD(0),
+			|handle, buffer, length| unsafe { BASS_Mixer_ChannelGetData(handle, buffer, length) },
+			"MixerSource::mixer_channel_get_data",
+		)
+	}
+
+	/// See docs for `Channel::spectrum`. Like `mixer_channel_get_data`, this requires `BASS_MIXER_CHAN_BUFFER`.
+	fn mixer_channel_spectrum(&self, size: FftSize, options: FftOptions) -> BassResult<Spectrum> {
+		spectrum::spectrum(
+			self.handle(),
+			size,
+			options,
+			|handle, buffer, length| unsafe { BASS_Mixer_ChannelGetData(handle, buffer, length) },
+			"MixerSource::mixer_channel_spectrum",
+		)
+	}
+
 	fn mixer_channel_get_level(&self) -> BassResult<(u16, u16)> {
 		let ok = BASS_Mixer_ChannelGetLevel(self.handle());
 		if ok.0 as i32 != -1 {
 			Ok(make_word(ok))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_get_level").with_handle(self.handle()))
 		}
 	}
 
 	fn mixer_channel_get_level_ex(&self, length: f32, flags: Option<DWORD>) -> BassResult<Vec<f32>> {
-		let mono = (flags.unwrap_or_default() & BASS_LEVEL_MONO) == BASS_LEVEL_MONO;
-		let stereo = (flags.unwrap_or_default() & BASS_LEVEL_STEREO) == BASS_LEVEL_STEREO;
-		if mono || stereo {
-			let mut levels = Vec::with_capacity(2);
-			#[cfg(debug_assertions)]
-			println!("Getting levels in mono or stereo mode.");
-			let ok = unsafe {
-				BASS_Mixer_ChannelGetLevelEx(self.handle(), levels.as_mut_ptr(), length, flags.unwrap_or_default())
-			};
-			if ok {
-				unsafe {
-					levels.set_len(if mono { 1 } else { 2 });
-				}
-				Ok(levels)
-			} else {
-				Err(BassError::get())
-			}
+		let flags = flags.unwrap_or_default();
+		let count = if (flags & BASS_LEVEL_MONO) == BASS_LEVEL_MONO {
+			1
+		} else if (flags & BASS_LEVEL_STEREO) == BASS_LEVEL_STEREO {
+			2
 		} else {
-			let mut info = BASS_CHANNELINFO::default();
-			let ok = BASS_ChannelGetInfo(self.handle(), &mut info); // This should always succeed.
-			if ok {
-				let chan_count = info.chans.0 as usize;
-				let mut levels = Vec::with_capacity(chan_count);
-				#[cfg(debug_assertions)]
-				println!("Getting levels for channel with {chan_count} channels.");
-				let ok = unsafe {
-					BASS_Mixer_ChannelGetLevelEx(self.handle(), levels.as_mut_ptr(), length, flags.unwrap_or_default())
-				};
-				if ok {
-					unsafe {
-						levels.set_len(chan_count);
-					}
-					Ok(levels)
-				} else {
-					Err(BassError::get())
-				}
-			} else {
-				Err(BassError::get())
-			}
+			data::channel_info(self.handle(), "MixerSource::mixer_channel_get_level_ex")?.chans.0 as usize
+		};
+		trace!("Getting levels for {count} channels");
+		let mut levels = vec![0.; count];
+		let ok = unsafe { BASS_Mixer_ChannelGetLevelEx(self.handle(), levels.as_mut_ptr(), length, flags) };
+		if ok {
+			Ok(levels)
+		} else {
+			Err(BassError::get("MixerSource::mixer_channel_get_level_ex").with_handle(self.handle()))
 		}
 	}
 
@@ -625,25 +762,27 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok != 0 {
 			Ok(ok)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_get_mixer").with_handle(self.handle()))
 		}
 	}
 
-	fn mixer_channel_get_position(&self, mode: DWORD) -> BassResult<u64> {
-		let value = BASS_Mixer_ChannelGetPosition(self.handle(), mode);
+	fn mixer_channel_get_position(&self, unit: PositionUnit) -> BassResult<Position> {
+		unit.check_applies(self.handle(), "MixerSource::mixer_channel_get_position")?;
+		let value = BASS_Mixer_ChannelGetPosition(self.handle(), unit.raw_mode());
 		if value.0 as i64 != -1 {
-			Ok(value.0)
+			Position::from_raw(unit, self.handle(), value.0, "MixerSource::mixer_channel_get_position")
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_get_position").with_handle(self.handle()))
 		}
 	}
 
-	fn mixer_channel_get_position_ex(&self, mode: DWORD, delay: impl Into<DWORD>) -> BassResult<u64> {
-		let value = BASS_Mixer_ChannelGetPositionEx(self.handle(), mode, delay);
+	fn mixer_channel_get_position_ex(&self, unit: PositionUnit, delay: impl Into<DWORD>) -> BassResult<Position> {
+		unit.check_applies(self.handle(), "MixerSource::mixer_channel_get_position_ex")?;
+		let value = BASS_Mixer_ChannelGetPositionEx(self.handle(), unit.raw_mode(), delay);
 		if value.0 as i64 != -1 {
-			Ok(value.0)
+			Position::from_raw(unit, self.handle(), value.0, "MixerSource::mixer_channel_get_position_ex")
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_get_position_ex").with_handle(self.handle()))
 		}
 	}
 
@@ -652,7 +791,7 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_remove").with_handle(self.handle()))
 		}
 	}
 
@@ -662,7 +801,7 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_remove_envelope").with_handle(self.handle()))
 		}
 	}
 
@@ -672,7 +811,7 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_set_envelope").with_handle(self.handle()))
 		}
 	}
 
@@ -681,7 +820,7 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_set_envelope_position").with_handle(self.handle()))
 		}
 	}
 
@@ -698,16 +837,20 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_set_matrix").with_handle(self.handle()))
 		}
 	}
 
-	fn mixer_channel_set_position(&self, position: impl Into<QWORD>, mode: DWORD) -> BassResult<()> {
-		let ok = BASS_Mixer_ChannelSetPosition(self.handle(), position, mode);
+	/// See docs for `Channel::set_position`
+	fn mixer_channel_set_position(&self, position: Position, options: SeekOptions) -> BassResult<()> {
+		let (value, mode) = position.to_raw(self.handle(), options, "MixerSource::mixer_channel_set_position")?;
+		let ok = BASS_Mixer_ChannelSetPosition(self.handle(), value, mode);
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_set_position")
+				.with_handle(self.handle())
+				.with_context(format!("{position:?}")))
 		}
 	}
 
@@ -732,11 +875,12 @@ pub trait MixerSource: Channel + mixer::MixableChannel {
 				weak.into_raw() as *mut c_void,
 			)
 		};
-		println!("Mixer Sync: {:?}", sync);
+		trace!("Set mixer sync {:?} on channel {:?}", sync, self.handle());
 		if sync != 0 {
+			handles::register(HandleKind::Sync, sync, Some(self.handle()));
 			Ok(BassSync { sync, channel: self.handle(), user })
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("MixerSource::mixer_channel_set_sync").with_handle(self.handle()))
 		}
 	}
 }
@@ -0,0 +1,246 @@
+use std::time::Duration;
+
+use bass_sys::*;
+
+use super::data::{bytes_per_frame, channel_info};
+use crate::{
+	bass::error::{BassError, BassErrorCode},
+	BassResult,
+};
+
+/// `BASS_CTYPE_STREAM_OPUS`, from the BASSOPUS add-on.
+const CTYPE_STREAM_OPUS: DWORD = DWORD(0x11200);
+/// `BASS_CTYPE_STREAM_FLAC_OGG`, from the BASSFLAC add-on.
+const CTYPE_STREAM_FLAC_OGG: DWORD = DWORD(0x10901);
+
+/// A position (or length) within a channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Position {
+	Bytes(u64),
+	Time(Duration),
+	/// Sample frames, ie. one sample for every channel.
+	Frames(u64),
+	/// An order and row in a MOD music. Lengths are the number of orders, with a row of 0.
+	MusicOrder { order: u32, row: u32 },
+	/// A track of a CD stream, numbered from 0.
+	#[cfg(feature = "cd")]
+	CdTrack(u32),
+	/// A bitstream of a chained Ogg file, numbered from 0. Lengths are the number of bitstreams.
+	OggBitstream(u32),
+}
+
+/// The unit a position (or length) is read in, without a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PositionUnit {
+	Bytes,
+	Time,
+	Frames,
+	MusicOrder,
+	#[cfg(feature = "cd")]
+	CdTrack,
+	OggBitstream,
+}
+
+/// Options for `Channel::set_position`.
+///
+/// ```ignore
+/// stream.set_position(Position::Time(Duration::from_secs(30)), SeekOptions::new().flush())?;
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SeekOptions {
+	flags: DWORD,
+}
+
+impl SeekOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Flush the playback buffer, so the new position is heard immediately rather than after what's already buffered.
+	pub fn flush(mut self) -> Self {
+		self.flags = self.flags | BASS_POS_FLUSH;
+		self
+	}
+
+	/// Decode up to the position rather than seeking to it, for formats that can't seek accurately.
+	pub fn decode_to(mut self) -> Self {
+		self.flags = self.flags | BASS_POS_DECODETO;
+		self
+	}
+
+	/// The position is relative to the current position, rather than the start.
+	pub fn relative(mut self) -> Self {
+		self.flags = self.flags | BASS_POS_RELATIVE;
+		self
+	}
+
+	/// Allow an inexact seek, which is faster for some formats.
+	pub fn inexact(mut self) -> Self {
+		self.flags = self.flags | BASS_POS_INEXACT;
+		self
+	}
+
+	/// Scan the file to find the exact position, for files that weren't created with `BASS_STREAM_PRESCAN`.
+	pub fn scan(mut self) -> Self {
+		self.flags = self.flags | BASS_POS_SCAN;
+		self
+	}
+
+	/// Set the position the channel loops back to, rather than the current position.
+	pub fn loop_start(mut self) -> Self {
+		self.flags = self.flags | BASS_POS_LOOP;
+		self
+	}
+
+	fn has(&self, flag: DWORD) -> bool {
+		(self.flags & flag) == flag
+	}
+
+	/// Checks the options can be used to set a position in `unit`.
+	fn check(&self, unit: PositionUnit, handle: DWORD, operation: &'static str) -> BassResult<()> {
+		let byte_based = matches!(unit, PositionUnit::Bytes | PositionUnit::Time | PositionUnit::Frames);
+		if self.has(BASS_POS_LOOP) && !byte_based {
+			return Err(BassError::new(BassErrorCode::BassErrorIllParam, operation)
+				.with_handle(handle)
+				.with_context(format!("a loop start can't be set as {unit:?}")));
+		}
+		Ok(())
+	}
+}
+
+impl Position {
+	pub fn unit(&self) -> PositionUnit {
+		match self {
+			Position::Bytes(_) => PositionUnit::Bytes,
+			Position::Time(_) => PositionUnit::Time,
+			Position::Frames(_) => PositionUnit::Frames,
+			Position::MusicOrder { .. } => PositionUnit::MusicOrder,
+			#[cfg(feature = "cd")]
+			Position::CdTrack(_) => PositionUnit::CdTrack,
+			Position::OggBitstream(_) => PositionUnit::OggBitstream,
+		}
+	}
+
+	/// Converts to the value and mode passed to `BASS_ChannelSetPosition`.
+	pub(crate) fn to_raw(
+		&self,
+		handle: DWORD,
+		options: SeekOptions,
+		operation: &'static str,
+	) -> BassResult<(QWORD, DWORD)> {
+		let unit = self.unit();
+		let info = unit.check_applies(handle, operation)?;
+		options.check(unit, handle, operation)?;
+		let value = match self {
+			Position::Bytes(bytes) => *bytes,
+			Position::Time(time) => {
+				let bytes = BASS_ChannelSeconds2Bytes(handle, time.as_secs_f64());
+				if bytes.0 as i64 == -1 {
+					return Err(BassError::get(operation).with_handle(handle));
+				}
+				bytes.0
+			}
+			Position::Frames(frames) => frames * bytes_per_frame(&info) as u64,
+			Position::MusicOrder { order, row } => pack_music_order(*order, *row),
+			#[cfg(feature = "cd")]
+			Position::CdTrack(track) => *track as u64,
+			Position::OggBitstream(bitstream) => *bitstream as u64,
+		};
+		Ok((QWORD(value), unit.raw_mode() | options.flags))
+	}
+
+	/// Converts a value from `BASS_ChannelGetPosition` or `BASS_ChannelGetLength`, read in `unit.raw_mode()`.
+	pub(crate) fn from_raw(unit: PositionUnit, handle: DWORD, value: u64, operation: &'static str) -> BassResult<Self> {
+		Ok(match unit {
+			PositionUnit::Bytes => Position::Bytes(value),
+			PositionUnit::Time => {
+				let seconds = BASS_ChannelBytes2Seconds(handle, value);
+				if seconds < 0. {
+					return Err(BassError::get(operation).with_handle(handle));
+				}
+				Position::Time(Duration::from_secs_f64(seconds))
+			}
+			PositionUnit::Frames => {
+				let info = channel_info(handle, operation)?;
+				Position::Frames(value / bytes_per_frame(&info) as u64)
+			}
+			PositionUnit::MusicOrder => unpack_music_order(value),
+			#[cfg(feature = "cd")]
+			PositionUnit::CdTrack => Position::CdTrack(value as u32),
+			PositionUnit::OggBitstream => Position::OggBitstream(value as u32),
+		})
+	}
+}
+
+/// `BASS_POS_MUSIC_ORDER` positions have the order in the low word and the row in the high word.
+fn pack_music_order(order: u32, row: u32) -> u64 {
+	((order & 0xffff) | ((row & 0xffff) << 16)) as u64
+}
+
+fn unpack_music_order(value: u64) -> Position {
+	Position::MusicOrder { order: (value & 0xffff) as u32, row: ((value >> 16) & 0xffff) as u32 }
+}
+
+impl PositionUnit {
+	/// The `BASS_POS_*` mode the unit is read in. Time and frames are read as bytes, then converted.
+	pub(crate) fn raw_mode(self) -> DWORD {
+		match self {
+			PositionUnit::Bytes | PositionUnit::Time | PositionUnit::Frames => BASS_POS_BYTE,
+			PositionUnit::MusicOrder => BASS_POS_MUSIC_ORDER,
+			#[cfg(feature = "cd")]
+			PositionUnit::CdTrack => BASS_POS_CD_TRACK,
+			PositionUnit::OggBitstream => BASS_POS_OGG,
+		}
+	}
+
+	/// Checks the unit can be used with a channel, returning the channel's info.
+	pub(crate) fn check_applies(self, handle: DWORD, operation: &'static str) -> BassResult<BASS_CHANNELINFO> {
+		let info = channel_info(handle, operation)?;
+		let (ok, kind) = match self {
+			PositionUnit::Bytes | PositionUnit::Time | PositionUnit::Frames => (true, ""),
+			PositionUnit::MusicOrder => ((info.ctype & BASS_CTYPE_MUSIC_MOD) == BASS_CTYPE_MUSIC_MOD, "MOD music"),
+			#[cfg(feature = "cd")]
+			PositionUnit::CdTrack => (info.ctype == BASS_CTYPE_STREAM_CD, "CD streams"),
+			PositionUnit::OggBitstream => (
+				info.ctype == BASS_CTYPE_STREAM_VORBIS
+					|| info.ctype == CTYPE_STREAM_OPUS
+					|| info.ctype == CTYPE_STREAM_FLAC_OGG,
+				"Ogg streams",
+			),
+		};
+		if ok {
+			Ok(info)
+		} else {
+			Err(BassError::new(BassErrorCode::BassErrorIllType, operation)
+				.with_handle(handle)
+				.with_context(format!("{self:?} positions only apply to {kind}")))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn music_order() {
+		assert_eq!(pack_music_order(3, 16), 0x0010_0003);
+		assert_eq!(unpack_music_order(0x0010_0003), Position::MusicOrder { order: 3, row: 16 });
+		assert_eq!(unpack_music_order(pack_music_order(0xffff, 63)), Position::MusicOrder { order: 0xffff, row: 63 });
+		// Only the row and order words are read; anything above them is ignored.
+		assert_eq!(unpack_music_order(0x1_0002_0001), Position::MusicOrder { order: 1, row: 2 });
+	}
+
+	#[test]
+	fn loop_start_units() {
+		let options = SeekOptions::new().loop_start();
+		for unit in [PositionUnit::Bytes, PositionUnit::Time, PositionUnit::Frames] {
+			assert_eq!(options.check(unit, DWORD(0), "test"), Ok(()));
+		}
+		for unit in [PositionUnit::MusicOrder, PositionUnit::OggBitstream] {
+			let error = options.check(unit, DWORD(0), "test").unwrap_err();
+			assert_eq!(error.code(), BassErrorCode::BassErrorIllParam, "{unit:?}");
+		}
+		assert_eq!(SeekOptions::new().flush().check(PositionUnit::MusicOrder, DWORD(0), "test"), Ok(()));
+	}
+}
@@ -0,0 +1,312 @@
+use std::{ops::RangeInclusive, os::raw::c_void};
+
+use bass_sys::*;
+
+use super::data::channel_info;
+use crate::{bass::error::BassError, BassResult};
+
+/// The number of samples used for an FFT. Each FFT gives half as many frequency bins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FftSize {
+	S256,
+	S512,
+	#[default]
+	S1024,
+	S2048,
+	S4096,
+	S8192,
+	S16384,
+	S32768,
+}
+
+impl FftSize {
+	pub fn samples(self) -> usize {
+		match self {
+			FftSize::S256 => 256,
+			FftSize::S512 => 512,
+			FftSize::S1024 => 1024,
+			FftSize::S2048 => 2048,
+			FftSize::S4096 => 4096,
+			FftSize::S8192 => 8192,
+			FftSize::S16384 => 16384,
+			FftSize::S32768 => 32768,
+		}
+	}
+
+	/// The number of frequency bins, from 0 Hz up to (but not including) the Nyquist frequency.
+	pub fn bins(self) -> usize {
+		self.samples() / 2
+	}
+
+	fn raw(self) -> DWORD {
+		match self {
+			FftSize::S256 => BASS_DATA_FFT256,
+			FftSize::S512 => BASS_DATA_FFT512,
+			FftSize::S1024 => BASS_DATA_FFT1024,
+			FftSize::S2048 => BASS_DATA_FFT2048,
+			FftSize::S4096 => BASS_DATA_FFT4096,
+			FftSize::S8192 => BASS_DATA_FFT8192,
+			FftSize::S16384 => BASS_DATA_FFT16384,
+			FftSize::S32768 => BASS_DATA_FFT32768,
+		}
+	}
+}
+
+/// Options for `Channel::spectrum`.
+///
+/// ```ignore
+/// let spectrum = stream.spectrum(FftSize::S2048, FftOptions::new().remove_dc())?;
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FftOptions {
+	flags: DWORD,
+}
+
+impl FftOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Perform a separate FFT for each channel, rather than one on the channels mixed together.
+	pub fn individual(mut self) -> Self {
+		self.flags = self.flags | BASS_DATA_FFT_INDIVIDUAL;
+		self
+	}
+
+	/// Return the complex result (real and imaginary parts) of the full FFT, rather than just the magnitudes.
+	pub fn complex(mut self) -> Self {
+		self.flags = self.flags | BASS_DATA_FFT_COMPLEX;
+		self
+	}
+
+	/// Don't apply a Hann window to the sample data before the FFT.
+	pub fn no_window(mut self) -> Self {
+		self.flags = self.flags | BASS_DATA_FFT_NOWINDOW;
+		self
+	}
+
+	/// Remove any DC bias from the sample data before the FFT.
+	pub fn remove_dc(mut self) -> Self {
+		self.flags = self.flags | BASS_DATA_FFT_REMOVEDC;
+		self
+	}
+
+	/// Leave the data in a recording channel's buffer.
+	pub fn no_remove(mut self) -> Self {
+		self.flags = self.flags | BASS_DATA_NOREMOVE;
+		self
+	}
+
+	fn has(&self, flag: DWORD) -> bool {
+		(self.flags & flag) == flag
+	}
+}
+
+/// The result of an FFT of a channel's data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectrum {
+	size: FftSize,
+	sample_rate: u32,
+	/// The number of FFTs in `data`: the channel count for individual FFTs, otherwise 1.
+	channels: usize,
+	complex: bool,
+	/// Interleaved by channel. Complex values are (real, imaginary) pairs.
+	data: Vec<f32>,
+}
+
+/// A frequency band folded from a `Spectrum`'s bins, with `Spectrum::bands`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Band {
+	/// The lowest frequency in the band, in Hz.
+	pub low: f32,
+	/// The highest frequency in the band, in Hz.
+	pub high: f32,
+	/// The largest magnitude of the bins in the band.
+	pub magnitude: f32,
+}
+
+impl Band {
+	/// The magnitude in decibels relative to full scale (so 0 dB is the loudest).
+	pub fn db(&self) -> f32 {
+		to_db(self.magnitude)
+	}
+
+	/// The magnitude scaled from `floor` dB (0) to 0 dB (1), which is what a bar in a spectrum display needs.
+	///
+	/// A `floor` above -1 dB (or NaN) leaves no range to scale across, so -1 dB is used instead.
+	pub fn level(&self, floor: f32) -> f32 {
+		let floor = floor.min(MAX_FLOOR);
+		((self.db() - floor) / -floor).clamp(0., 1.)
+	}
+}
+
+/// The highest floor `Band::level` scales from, in dB.
+const MAX_FLOOR: f32 = -1.;
+
+/// Converts a magnitude to decibels relative to full scale. Silence is `f32::NEG_INFINITY`.
+pub fn to_db(magnitude: f32) -> f32 {
+	20. * magnitude.log10()
+}
+
+impl Spectrum {
+	pub fn size(&self) -> FftSize {
+		self.size
+	}
+
+	/// The sample rate of the channel the FFT was performed on.
+	pub fn sample_rate(&self) -> u32 {
+		self.sample_rate
+	}
+
+	/// The number of FFTs: the channel count with `FftOptions::individual`, otherwise 1.
+	pub fn channels(&self) -> usize {
+		self.channels
+	}
+
+	pub fn is_complex(&self) -> bool {
+		self.complex
+	}
+
+	/// The data as returned by BASS, interleaved by channel.
+	pub fn raw(&self) -> &[f32] {
+		&self.data
+	}
+
+	/// The number of frequency bins in each channel's magnitudes.
+	pub fn bins(&self) -> usize {
+		self.size.bins()
+	}
+
+	/// The centre frequency of a bin, in Hz.
+	pub fn frequency(&self, bin: usize) -> f32 {
+		bin as f32 * self.sample_rate as f32 / self.size.samples() as f32
+	}
+
+	/// The bin a frequency falls in, limited to the available bins.
+	pub fn bin(&self, frequency: f32) -> usize {
+		let bin = (frequency.max(0.) * self.size.samples() as f32 / self.sample_rate as f32).round() as usize;
+		bin.min(self.bins() - 1)
+	}
+
+	/// The magnitude of each bin of one channel's FFT (or the only FFT, when they weren't individual).
+	pub fn channel_magnitudes(&self, channel: usize) -> Vec<f32> {
+		assert!(channel < self.channels, "channel {channel} out of range for {} channels", self.channels);
+		if self.complex {
+			let stride = self.channels * 2;
+			(0..self.bins())
+				.map(|bin| {
+					let index = bin * stride + channel * 2;
+					self.data[index].hypot(self.data[index + 1])
+				})
+				.collect()
+		} else {
+			self.data.iter().skip(channel).step_by(self.channels).take(self.bins()).copied().collect()
+		}
+	}
+
+	/// The magnitude of each bin, taking the largest across channels for individual FFTs.
+	pub fn magnitudes(&self) -> Vec<f32> {
+		let mut magnitudes = self.channel_magnitudes(0);
+		for channel in 1..self.channels {
+			for (magnitude, other) in magnitudes.iter_mut().zip(self.channel_magnitudes(channel)) {
+				*magnitude = magnitude.max(other);
+			}
+		}
+		magnitudes
+	}
+
+	/// Folds the bins into `count` bands, spaced logarithmically across `range` (in Hz), like a typical spectrum
+	/// display.
+	///
+	/// Bands narrower than a bin take the magnitude of the bin their centre falls in, so low bands don't appear
+	/// empty with small FFT sizes.
+	pub fn bands(&self, count: usize, range: RangeInclusive<f32>) -> Vec<Band> {
+		let magnitudes = self.magnitudes();
+		let nyquist = self.sample_rate as f32 / 2.;
+		// Logarithmic spacing can't start at 0 Hz.
+		let low = range.start().max(1.).min(nyquist);
+		let high = range.end().clamp(low, nyquist);
+		let ratio = (high / low).powf(1. / count.max(1) as f32);
+		(0..count)
+			.map(|index| {
+				let band_low = low * ratio.powi(index as i32);
+				let band_high = band_low * ratio;
+				let first = self.bin(band_low);
+				let last = self.bin(band_high).max(first);
+				let magnitude = if last > first {
+					magnitudes[first..last].iter().copied().fold(0., f32::max)
+				} else {
+					magnitudes[self.bin((band_low * band_high).sqrt())]
+				};
+				Band { low: band_low, high: band_high, magnitude }
+			})
+			.collect()
+	}
+}
+
+/// Performs an FFT using `get_data` (`BASS_ChannelGetData` or `BASS_Mixer_ChannelGetData`).
+pub(crate) fn spectrum(
+	handle: DWORD,
+	size: FftSize,
+	options: FftOptions,
+	get_data: impl FnOnce(DWORD, *mut c_void, DWORD) -> DWORD,
+	operation: &'static str,
+) -> BassResult<Spectrum> {
+	let info = channel_info(handle, operation)?;
+	let channels = if options.has(BASS_DATA_FFT_INDIVIDUAL) { info.chans.0.max(1) as usize } else { 1 };
+	let complex = options.has(BASS_DATA_FFT_COMPLEX);
+	let values = if complex { size.samples() * 2 } else { size.bins() };
+	let mut data = vec![0.; values * channels];
+	let read = get_data(handle, data.as_mut_ptr() as *mut c_void, size.raw() | options.flags);
+	if read.0 as i32 == -1 {
+		Err(BassError::get(operation).with_handle(handle))
+	} else {
+		Ok(Spectrum { size, sample_rate: info.freq.0, channels, complex, data })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Two individual FFTs of 256 samples at 25.6 kHz, so each bin is 100 Hz wide.
+	fn spectrum(bins: &[(usize, usize, f32)]) -> Spectrum {
+		let mut data = vec![0.; FftSize::S256.bins() * 2];
+		for &(bin, channel, magnitude) in bins {
+			data[bin * 2 + channel] = magnitude;
+		}
+		Spectrum { size: FftSize::S256, sample_rate: 25600, channels: 2, complex: false, data }
+	}
+
+	#[test]
+	fn bands() {
+		let spectrum = spectrum(&[(10, 0, 0.5), (15, 1, 0.75), (10, 1, 0.1), (39, 0, 0.25), (40, 0, 0.9)]);
+		let bands = spectrum.bands(2, 1000.0..=4000.);
+		let summary: Vec<_> = bands.iter().map(|band| (band.low.round(), band.high.round(), band.magnitude)).collect();
+		// The largest magnitude across bins and channels, with each band's top edge belonging to the next band.
+		assert_eq!(summary, [(1000., 2000., 0.75), (2000., 4000., 0.25)]);
+
+		// Narrower than a bin, so the bin at the centre is used.
+		assert_eq!(spectrum.bands(1, 1000.0..=1040.)[0].magnitude, 0.5);
+
+		// Limited to the Nyquist frequency, and to at least 1 Hz.
+		let band = spectrum.bands(1, 0.0..=20000.)[0];
+		assert_eq!((band.low, band.high.round()), (1., 12800.));
+	}
+
+	#[test]
+	fn levels() {
+		let band = Band { low: 0., high: 0., magnitude: 0.1 };
+		assert_eq!(band.db().round(), -20.);
+		assert!((band.level(-40.) - 0.5).abs() < 1e-5);
+		assert_eq!(band.level(-10.), 0.);
+		assert_eq!(Band { magnitude: 0., ..band }.level(-60.), 0.);
+		assert_eq!(Band { magnitude: 1., ..band }.level(-60.), 1.);
+		// Floors that leave no range are raised to -1 dB.
+		let half = Band { magnitude: 10f32.powf(-0.5 / 20.), ..band };
+		for floor in [0., 6., f32::NAN] {
+			assert!((half.level(floor) - 0.5).abs() < 1e-5);
+		}
+		assert_eq!(Band { magnitude: 1., ..band }.level(0.), 1.);
+	}
+}
@@ -0,0 +1,154 @@
+use bass_sys::*;
+
+use super::Channel;
+
+/// Whether a channel is playing, from `BASS_ChannelIsActive` (or `BASS_Mixer_ChannelIsActive`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ChannelState {
+	/// The channel isn't playing, or it has reached the end. Decoding channels are also stopped once they've ended.
+	#[default]
+	Stopped,
+	/// The channel is playing, or a decoding channel hasn't ended yet.
+	Playing,
+	/// The channel has been paused with `Channel::pause`.
+	Paused,
+	/// The channel's device has been paused (or has failed), so the channel isn't playing even though it hasn't been
+	/// paused itself.
+	PausedDevice,
+	/// Playback of the channel has stalled, as it has run out of data (eg. an internet stream that is still
+	/// downloading). Playback resumes automatically once there is enough data.
+	Stalled,
+}
+
+impl ChannelState {
+	/// Whether the channel is playing or waiting for data to continue playing.
+	pub fn is_playing(self) -> bool {
+		matches!(self, ChannelState::Playing | ChannelState::Stalled)
+	}
+
+	/// Whether the channel is paused, either by itself or because its device is.
+	pub fn is_paused(self) -> bool {
+		matches!(self, ChannelState::Paused | ChannelState::PausedDevice)
+	}
+}
+
+impl From<DWORD> for ChannelState {
+	fn from(value: DWORD) -> Self {
+		if value == BASS_ACTIVE_PLAYING {
+			ChannelState::Playing
+		} else if value == BASS_ACTIVE_PAUSED {
+			ChannelState::Paused
+		} else if value == BASS_ACTIVE_PAUSED_DEVICE {
+			ChannelState::PausedDevice
+		} else if value == BASS_ACTIVE_STALLED {
+			ChannelState::Stalled
+		} else {
+			ChannelState::Stopped
+		}
+	}
+}
+
+/// A channel going from one state to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StateChange {
+	pub from: ChannelState,
+	pub to: ChannelState,
+}
+
+impl StateChange {
+	/// Playback has stalled waiting for data.
+	pub fn stalled(&self) -> bool {
+		self.to == ChannelState::Stalled
+	}
+
+	/// Playback has resumed after stalling, or after its device was paused.
+	pub fn resumed(&self) -> bool {
+		matches!(self.from, ChannelState::Stalled | ChannelState::PausedDevice) && self.to == ChannelState::Playing
+	}
+
+	/// The channel has stopped, either because it was stopped or because it reached the end.
+	pub fn stopped(&self) -> bool {
+		self.to == ChannelState::Stopped
+	}
+}
+
+/// Remembers the last state seen of a channel, to report when it changes.
+///
+/// BASS doesn't notify about every state change (there's no sync for a device being paused, for example), so this is
+/// meant to be polled, such as once per UI frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StateTracker {
+	state: ChannelState,
+}
+
+impl StateTracker {
+	pub fn new(initial: ChannelState) -> Self {
+		StateTracker { state: initial }
+	}
+
+	/// Starts tracking from the channel's current state.
+	pub fn of(channel: &impl Channel) -> Self {
+		Self::new(channel.is_active())
+	}
+
+	/// The last state seen.
+	pub fn state(&self) -> ChannelState {
+		self.state
+	}
+
+	/// Records a newly observed state, returning the change if it differs from the last one.
+	pub fn update(&mut self, state: ChannelState) -> Option<StateChange> {
+		let from = std::mem::replace(&mut self.state, state);
+		(from != state).then_some(StateChange { from, to: state })
+	}
+
+	/// Checks the channel's current state, returning the change if it has changed since the last check.
+	pub fn poll(&mut self, channel: &impl Channel) -> Option<StateChange> {
+		self.update(channel.is_active())
+	}
+
+	/// Checks the state of a mixer source within its mixer, returning the change if it has changed since the last
+	/// check.
+	#[cfg(feature = "mixer")]
+	pub fn poll_mixer_channel(&mut self, channel: &impl super::MixerSource) -> Option<StateChange> {
+		self.update(channel.mixer_channel_active())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn from_active() {
+		assert_eq!(ChannelState::from(BASS_ACTIVE_STOPPED), ChannelState::Stopped);
+		assert_eq!(ChannelState::from(BASS_ACTIVE_PLAYING), ChannelState::Playing);
+		assert_eq!(ChannelState::from(BASS_ACTIVE_PAUSED), ChannelState::Paused);
+		assert_eq!(ChannelState::from(BASS_ACTIVE_PAUSED_DEVICE), ChannelState::PausedDevice);
+		assert_eq!(ChannelState::from(BASS_ACTIVE_STALLED), ChannelState::Stalled);
+		assert!(ChannelState::Stalled.is_playing() && !ChannelState::Stalled.is_paused());
+		assert!(ChannelState::PausedDevice.is_paused() && !ChannelState::PausedDevice.is_playing());
+	}
+
+	#[test]
+	fn transitions() {
+		let mut tracker = StateTracker::new(ChannelState::Playing);
+		assert_eq!(tracker.update(ChannelState::Playing), None);
+
+		let change = tracker.update(ChannelState::Stalled).unwrap();
+		assert_eq!(change, StateChange { from: ChannelState::Playing, to: ChannelState::Stalled });
+		assert!(change.stalled() && !change.resumed());
+
+		assert!(tracker.update(ChannelState::Playing).unwrap().resumed());
+
+		tracker.update(ChannelState::PausedDevice);
+		assert!(tracker.update(ChannelState::Playing).unwrap().resumed());
+
+		// Resuming from a pause by the user isn't recovering from a stall or a device pause.
+		tracker.update(ChannelState::Paused);
+		assert!(!tracker.update(ChannelState::Playing).unwrap().resumed());
+
+		assert!(tracker.update(ChannelState::Stopped).unwrap().stopped());
+		assert_eq!(tracker.state(), ChannelState::Stopped);
+	}
+}
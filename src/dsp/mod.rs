@@ -2,6 +2,8 @@ use std::{fmt::Debug, sync::{Arc, Mutex}};
 
 use bass_sys::{BASS_ChannelRemoveDSP, DWORD, HDSP};
 
+use crate::bass::handles::{self, HandleKind};
+
 #[derive(Debug)]
 pub struct BassDsp<T: Send + Sync> {
 	pub(crate) dsp: HDSP,
@@ -13,8 +15,8 @@ pub struct BassDsp<T: Send + Sync> {
 
 impl<T: Send + Sync> Drop for BassDsp<T> {
 	fn drop(&mut self) {
-		#[cfg(debug_assertions)]
-		println!("Freeing DSP {:?}", self.dsp);
+		trace!("Freeing DSP {:?}", self.dsp);
+		handles::release(HandleKind::Dsp, self.dsp);
 		BASS_ChannelRemoveDSP(self.channel, self.dsp);
 		// let _ = unsafe { Box::from_raw(self.user) };
 	}
@@ -0,0 +1,61 @@
+//! Passing paths to BASS without going through `str`, so paths that aren't valid Unicode can still be opened.
+
+use std::{os::raw::c_void, path::Path};
+
+use bass_sys::{BASS_UNICODE, DWORD};
+
+/// A path in the form BASS takes file names: UTF-16 with `BASS_UNICODE` on Windows, and the path's own bytes elsewhere.
+/// Like the strings given to BASS elsewhere, it is cut off at the first NUL.
+pub(crate) struct FileName {
+	#[cfg(windows)]
+	name: widestring::U16CString,
+	#[cfg(not(windows))]
+	name: std::ffi::CString,
+}
+
+impl FileName {
+	#[cfg(windows)]
+	pub(crate) fn new(path: &Path) -> Self {
+		use std::os::windows::ffi::OsStrExt;
+		let wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+		FileName { name: widestring::U16CString::from_vec_truncate(wide) }
+	}
+
+	#[cfg(not(windows))]
+	pub(crate) fn new(path: &Path) -> Self {
+		use std::os::unix::ffi::OsStrExt;
+		let bytes = path.as_os_str().as_bytes();
+		let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+		// There are no NULs left to fail on.
+		FileName { name: std::ffi::CString::new(&bytes[..end]).unwrap_or_default() }
+	}
+
+	pub(crate) fn as_ptr(&self) -> *const c_void {
+		self.name.as_ptr() as *const c_void
+	}
+
+	/// The flag telling BASS how the name is encoded, to combine with the other flags of the call.
+	pub(crate) fn flags(&self) -> DWORD {
+		if cfg!(windows) {
+			BASS_UNICODE
+		} else {
+			DWORD(0)
+		}
+	}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+	use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+	use super::*;
+
+	#[test]
+	fn keeps_bytes() {
+		// Latin-1 "é", which isn't valid UTF-8.
+		let path = Path::new(OsStr::from_bytes(b"/music/caf\xe9.mp3\0ignored"));
+		let name = FileName::new(path);
+		assert_eq!(name.name.as_bytes(), b"/music/caf\xe9.mp3");
+		assert_eq!(name.flags(), DWORD(0));
+	}
+}
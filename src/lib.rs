@@ -1,3 +1,7 @@
+#[macro_use]
+mod logging;
+mod file_name;
+
 pub mod bass;
 pub mod channel;
 pub mod dsp;
@@ -7,12 +11,15 @@ pub mod fx;
 #[cfg(feature = "mixer")]
 pub mod mixer;
 pub mod music;
+pub mod probe;
 pub mod recording;
 pub mod sample;
+pub mod spatial;
 #[cfg(feature = "mixer")]
 pub mod split;
 pub mod stream;
 pub mod sync;
+pub mod tags;
 pub mod types;
 pub use types::*;
 
@@ -30,13 +37,17 @@ mod tests {
 	};
 
 	use bass_sys::{
-		BASS_ATTRIB_FREQ, BASS_ATTRIB_VOL, BASS_LEVEL_MONO, BASS_LEVEL_STEREO, BASS_SAMPLE_FLOAT, BASS_SLIDE_LOG,
-		BASS_SYNC_SLIDE, BASS_SYNC_THREAD, DWORD, HDSP, HSYNC,
+		BASS_ATTRIB_VOL, BASS_LEVEL_MONO, BASS_LEVEL_STEREO, BASS_SAMPLE_FLOAT, BASS_SYNC_SLIDE, BASS_SYNC_THREAD,
+		DWORD, HDSP, HSYNC,
 	};
 
 	use crate::{
-		bass::Bass,
-		channel::Channel,
+		bass::{init::InitOptions, Bass},
+		channel::{
+			attribute::{Attribute, Slide},
+			Channel,
+			ChannelExt,
+		},
 		functions::make_word,
 		stream::Stream,
 	};
@@ -130,7 +141,7 @@ mod tests {
 				rotpos = rotpos % (2. * PI)
 			};
 
-			let bass = Bass::init(-1, 48000, None)?;
+			let bass = Bass::init(-1, 48000, InitOptions::default())?;
 			let device = bass.device();
 			println!("BASS Device: {device}");
 			let devices = Bass::devices();
@@ -138,7 +149,7 @@ mod tests {
 			let stream = Stream::create_file("./orchestra-tune-up.mp3", 0, 0, BASS_SAMPLE_FLOAT)?;
 			let _sync = stream.set_sync(
 				BASS_SYNC_SLIDE | BASS_SYNC_THREAD,
-				stream.seconds_to_bytes(3.)?,
+				stream.duration_to_bytes(Duration::from_secs(3))?,
 				sync_handler,
 				tx.clone(),
 			)?;
@@ -154,8 +165,8 @@ mod tests {
 			assert_eq!(stream.get_level_ex(1., Some(BASS_LEVEL_MONO)).expect("Levels").len(), 1);
 			assert_eq!(stream.get_level_ex(1., Some(BASS_LEVEL_STEREO)).expect("Levels").len(), 2);
 
-			stream.slide_attribute(BASS_ATTRIB_FREQ | BASS_SLIDE_LOG, 4800., 5000)?;
-			stream.slide_attribute(BASS_ATTRIB_VOL, 0.5, 2000)?;
+			stream.slide_attribute(Slide::log(Attribute::Frequency(4800.), Duration::from_secs(5)))?;
+			stream.slide_attribute(Slide::linear(Attribute::Volume(0.5), Duration::from_secs(2)))?;
 
 			let ok = rx.recv_timeout(Duration::from_secs(12));
 			match ok {
@@ -163,7 +174,7 @@ mod tests {
 				Err(e) => eprintln!("{}", e),
 			}
 
-			stream.slide_attribute(BASS_ATTRIB_VOL, 1., 2000)?;
+			stream.slide_attribute(Slide::linear(Attribute::Volume(1.), Duration::from_secs(2)))?;
 			println!("{:#?}", stream.get_level());
 
 			let ok = rx.recv_timeout(Duration::from_secs(12));
@@ -174,7 +185,7 @@ mod tests {
 
 			thread::sleep(Duration::from_secs(3));
 
-			stream.slide_attribute(BASS_ATTRIB_FREQ | BASS_SLIDE_LOG, 48000., 5000)?;
+			stream.slide_attribute(Slide::log(Attribute::Frequency(48000.), Duration::from_secs(5)))?;
 			println!("{:#?}", stream.get_level_ex(1., None));
 
 			// match rx.recv() {
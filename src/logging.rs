@@ -0,0 +1,26 @@
+//! Opt-in logging through the `log` crate, enabled with the `log` feature.
+//!
+//! Without the feature these compile to nothing, but still type-check their arguments so variables only used for
+//! logging don't become unused. Never use these inside DSP/sync/download callbacks, which run on real-time threads.
+
+macro_rules! trace {
+	($($arg:tt)+) => {
+		#[cfg(feature = "log")]
+		::log::trace!($($arg)+);
+		#[cfg(not(feature = "log"))]
+		if false {
+			let _ = format_args!($($arg)+);
+		}
+	};
+}
+
+macro_rules! debug {
+	($($arg:tt)+) => {
+		#[cfg(feature = "log")]
+		::log::debug!($($arg)+);
+		#[cfg(not(feature = "log"))]
+		if false {
+			let _ = format_args!($($arg)+);
+		}
+	};
+}
@@ -2,18 +2,19 @@ use std::ptr::null_mut;
 
 use bass_sys::*;
 
-use crate::{bass::error::{BassError, BassErrorCode}, channel::{handle::HasHandle, mixer::MixableChannel, Channel, MixerSource}, BassResult};
+use crate::{bass::{device::Device, error::{BassError, BassErrorCode}, handles::{self, HandleKind}}, channel::{handle::HasHandle, mixer::MixableChannel, Channel, MixerSource}, BassResult};
 
 #[derive(Debug)]
-pub struct Mixer(HSTREAM);
+pub struct Mixer(HSTREAM, #[allow(dead_code)] Option<Device>);
 
 impl Mixer {
 	pub fn create(frequency: impl Into<DWORD>, channels: impl Into<DWORD>, flags: Option<DWORD>) -> BassResult<Self> {
 		let handle = BASS_Mixer_StreamCreate(frequency, channels, flags.unwrap_or_default());
 		if handle != 0 {
-			Ok(Self(handle))
+			handles::register(HandleKind::Mixer, handle, None);
+			Ok(Self(handle, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Mixer::create"))
 		}
 	}
 
@@ -22,7 +23,9 @@ impl Mixer {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Mixer::add_channel")
+				.with_handle(self.0)
+				.with_context(format!("channel {:#x}", channel.handle().0)))
 		}
 	}
 
@@ -31,7 +34,9 @@ impl Mixer {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Mixer::add_channel_ex")
+				.with_handle(self.0)
+				.with_context(format!("channel {:#x}", channel.handle().0)))
 		}
 	}
 
@@ -39,7 +44,7 @@ impl Mixer {
 		let mut channels: Vec<DWORD> = Vec::with_capacity(1);
 		let inserted = unsafe { BASS_Mixer_StreamGetChannels(self.0, channels.as_mut_ptr(), 1) };
 		if *inserted as i32 == -1 {
-			return Err(BassError::get()); // An error occurred; pass the error up the call chain.
+			return Err(BassError::get("Mixer::current").with_handle(self.0)); // An error occurred; pass the error up the call chain.
 		} else if *inserted == 0 {
 			return Ok(None); // No channels currently in Mixer.
 		}
@@ -48,7 +53,8 @@ impl Mixer {
 		}
 		match channels.get(0) {
 			Some(channel) => Ok(Some(*channel)),
-			None => Err(BassErrorCode::BassErrorUnknown), // This shouldn't be able to happen, but if it does, a horrible error occurred somewhere
+			// This shouldn't be able to happen, but if it does, a horrible error occurred somewhere
+			None => Err(BassError::new(BassErrorCode::BassErrorUnknown, "Mixer::current").with_handle(self.0)),
 		}
 	}
 
@@ -66,7 +72,7 @@ impl Mixer {
 			}
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Mixer::clear").with_handle(self.0))
 		}
 	}
 
@@ -75,7 +81,7 @@ impl Mixer {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Mixer::flush").with_handle(self.0))
 		}
 	}
 
@@ -102,7 +108,7 @@ impl Mixer {
 		let mut channels: Vec<DWORD> = Vec::with_capacity(count as usize);
 		let inserted = unsafe { BASS_Mixer_StreamGetChannels(self.0, channels.as_mut_ptr(), count) };
 		if *inserted as i32 == -1 {
-			return Err(BassError::get());
+			return Err(BassError::get("Mixer::channels").with_handle(self.0));
 		}
 		unsafe {
 			channels.set_len(*inserted as usize);
@@ -122,10 +128,25 @@ impl Channel for Mixer {}
 impl MixableChannel for Mixer {}
 impl MixerSource for Mixer {}
 
+/// A mixer that is owned elsewhere, so isn't freed when this is dropped. See `AnyChannel`.
+#[derive(Debug)]
+pub struct MaybeMixer(pub(crate) HSTREAM);
+
+impl HasHandle for MaybeMixer {
+	fn handle(&self) -> DWORD {
+		self.0 .0
+	}
+}
+
+impl Channel for MaybeMixer {}
+
+impl MixableChannel for MaybeMixer {}
+impl MixerSource for MaybeMixer {}
+
 impl Drop for Mixer {
 	fn drop(&mut self) {
-		#[cfg(debug_assertions)]
-		println!("Freeing Mixer {:?}", self.0);
+		trace!("Freeing mixer {:?}", self.0);
+		handles::release(HandleKind::Mixer, self.0);
 		BASS_StreamFree(self.0); // Only reason it can fail is if the stream has already been freed
 	}
 }
@@ -4,13 +4,17 @@ use bass_sys::{BASS_MusicLoad, BASS_UNICODE, DWORD, HMUSIC, QWORD};
 use widestring::U16CString;
 
 use crate::{
-	bass::error::BassError,
+	bass::{
+		device::Device,
+		error::BassError,
+		handles::{self, HandleKind},
+	},
 	channel::{handle::HasHandle, Channel},
 	BassResult,
 };
 
 #[derive(Debug)]
-pub struct Music(HMUSIC);
+pub struct Music(HMUSIC, #[allow(dead_code)] Option<Device>);
 
 impl Music {
 	pub fn load(
@@ -26,9 +30,10 @@ impl Music {
 			BASS_MusicLoad(false, file.as_ptr() as *const c_void, offset, length, flags | BASS_UNICODE, frequency)
 		};
 		if handle != 0 {
-			Ok(Self(handle))
+			handles::register(HandleKind::Music, handle, None);
+			Ok(Self(handle, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Music::load").with_context(path.as_ref()))
 		}
 	}
 
@@ -36,9 +41,10 @@ impl Music {
 		let handle =
 			unsafe { BASS_MusicLoad(true, data.as_ptr() as *const c_void, 0, data.len(), flags | BASS_UNICODE, frequency) };
 		if handle != 0 {
-			Ok(Self(handle))
+			handles::register(HandleKind::Music, handle, None);
+			Ok(Self(handle, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Music::load_memory").with_context(format!("{} bytes", data.len())))
 		}
 	}
 }
@@ -55,3 +61,27 @@ impl Channel for Music {}
 impl crate::channel::mixer::MixableChannel for Music {}
 #[cfg(feature = "mixer")]
 impl crate::channel::MixerSource for Music {}
+
+/// A MOD music that is owned elsewhere, so isn't freed when this is dropped. See `AnyChannel`.
+#[derive(Debug)]
+pub struct MaybeMusic(pub(crate) HMUSIC);
+
+impl HasHandle for MaybeMusic {
+	fn handle(&self) -> DWORD {
+		self.0 .0
+	}
+}
+
+impl Channel for MaybeMusic {}
+
+#[cfg(feature = "mixer")]
+impl crate::channel::mixer::MixableChannel for MaybeMusic {}
+#[cfg(feature = "mixer")]
+impl crate::channel::MixerSource for MaybeMusic {}
+
+impl Drop for Music {
+	fn drop(&mut self) {
+		// Music isn't freed when dropped (it goes with its device), so this only stops tracking it.
+		handles::release(HandleKind::Music, self.0);
+	}
+}
@@ -0,0 +1,110 @@
+use std::{
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use bass_sys::*;
+use thiserror::Error;
+
+use crate::{
+	bass::{
+		error::{BassError, BassErrorCode},
+		plugin::plugin_info,
+	},
+	channel::{
+		attribute::{Attribute, ChannelAttribute},
+		info::ChannelType,
+		Channel,
+	},
+	stream::Stream,
+};
+
+/// Everything `probe` can find out about a file without playing it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaInfo {
+	pub path: PathBuf,
+	/// A description of the codec/container, eg. "MPEG layer 3" or a plugin's format name.
+	pub codec: String,
+	pub channel_type: ChannelType,
+	pub sample_rate: u32,
+	pub channels: u32,
+	/// The original resolution of the file, if it has one (lossy formats don't).
+	pub bits: Option<u32>,
+	/// Whether the original data is floating-point.
+	pub float: bool,
+	/// The exact length, as the stream is pre-scanned.
+	pub length: Duration,
+	pub length_bytes: u64,
+	/// The average bitrate in kilobits per second, if known.
+	pub bitrate: Option<f32>,
+	/// The plugin that opened the file, or `None` if BASS supports the format itself.
+	pub plugin: Option<ProbedPlugin>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProbedPlugin {
+	/// The plugin's `HPLUGIN` handle, which can be looked up in a `PluginRegistry`.
+	pub handle: u32,
+	pub version: u32,
+	/// The name of the plugin's format that opened the file.
+	pub format: Option<String>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProbeError {
+	/// Neither BASS nor any loaded plugin supports the file's format.
+	#[error("Unsupported file format")]
+	UnsupportedFormat,
+	/// The file's format is recognised, but it doesn't contain any audio.
+	#[error("File does not contain audio")]
+	NotAudio,
+	#[error(transparent)]
+	Bass(#[from] BassError),
+}
+
+/// Opens a file as a decode-only, pre-scanned stream to find out what it contains, then frees it.
+pub fn probe(path: impl AsRef<Path>) -> Result<MediaInfo, ProbeError> {
+	let path = path.as_ref();
+	let stream =
+		Stream::create_file(path, 0, 0, BASS_STREAM_DECODE | BASS_STREAM_PRESCAN).map_err(|error| {
+			match error.code() {
+				BassErrorCode::BassErrorFileForm | BassErrorCode::BassErrorCodec => ProbeError::UnsupportedFormat,
+				BassErrorCode::BassErrorNotAudio => ProbeError::NotAudio,
+				_ => ProbeError::Bass(error),
+			}
+		})?;
+	let info = stream.get_info()?;
+	let length_bytes = stream.length_bytes()?;
+	let length = stream.bytes_to_duration(length_bytes)?;
+	// Not every format has a bitrate.
+	let bitrate = match stream.get_attribute(ChannelAttribute::Bitrate) {
+		Ok(Attribute::Bitrate(bitrate)) if bitrate > 0. => Some(bitrate),
+		_ => None,
+	};
+	let plugin = info.plugin.map(|handle| {
+		let name = match &info.channel_type {
+			ChannelType::Plugin { name, .. } => name.clone(),
+			_ => None,
+		};
+		ProbedPlugin {
+			handle: handle.0 .0,
+			version: plugin_info(handle).map_or(0, |plugin| plugin.version),
+			format: name,
+		}
+	});
+	Ok(MediaInfo {
+		path: path.to_path_buf(),
+		codec: info.channel_type.to_string(),
+		channel_type: info.channel_type.clone(),
+		sample_rate: info.frequency,
+		channels: info.channels,
+		bits: info.original_bits,
+		float: info.original_float,
+		length,
+		length_bytes,
+		bitrate,
+		plugin,
+	})
+}
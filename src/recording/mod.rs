@@ -1,6 +1,16 @@
-use bass_sys::HRECORD;
+use bass_sys::{DWORD, HRECORD};
+
+use crate::channel::{handle::HasHandle, Channel};
 
 /// TODO
 #[allow(unused)]
 #[derive(Debug)]
-pub struct RecordingStream(HRECORD);
\ No newline at end of file
+pub struct RecordingStream(pub(crate) HRECORD);
+
+impl HasHandle for RecordingStream {
+	fn handle(&self) -> DWORD {
+		self.0 .0
+	}
+}
+
+impl Channel for RecordingStream {}
\ No newline at end of file
@@ -4,11 +4,11 @@ use bass_sys::{BASS_SampleFree, BASS_SampleGetChannel, BASS_SampleGetChannels, B
 use widestring::U16CString;
 
 use crate::{
-	bass::error::BassError, channel::{handle::HasHandle, Channel}, stream::Stream, BassResult
+	bass::{device::Device, error::BassError, handles::{self, HandleKind}}, channel::{handle::HasHandle, Channel}, stream::Stream, BassResult
 };
 
 #[derive(Debug)]
-pub struct Sample(HSAMPLE);
+pub struct Sample(HSAMPLE, #[allow(dead_code)] Option<Device>);
 
 /// Samples will automatically free themselves and all streams and channels created from them when dropped.
 impl Sample {
@@ -25,9 +25,10 @@ impl Sample {
 			BASS_SampleLoad(false, file.as_ptr() as *const c_void, offset, length, maximum, flags | BASS_UNICODE)
 		};
 		if handle != 0 {
-			Ok(Self(handle))
+			handles::register(HandleKind::Sample, handle, None);
+			Ok(Self(handle, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Sample::load").with_context(path.as_ref()))
 		}
 	}
 
@@ -35,9 +36,10 @@ impl Sample {
 		let handle =
 			unsafe { BASS_SampleLoad(true, data.as_ptr() as *const c_void, 0, data.len(), maximum, flags | BASS_UNICODE) };
 		if handle != 0 {
-			Ok(Self(handle))
+			handles::register(HandleKind::Sample, handle, None);
+			Ok(Self(handle, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Sample::load_memory").with_context(format!("{} bytes", data.len())))
 		}
 	}
 
@@ -46,7 +48,7 @@ impl Sample {
 		if let Some(handle) = ok {
 			Ok(SampleChannel(HCHANNEL(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Sample::get_channel").with_handle(self.0))
 		}
 	}
 
@@ -69,7 +71,7 @@ impl Sample {
 		let mut channels: Vec<HCHANNEL> = Vec::with_capacity(*count as usize);
 		let inserted = unsafe { BASS_SampleGetChannels(self.0, channels.as_mut_ptr()) };
 		if *inserted as i32 == -1 {
-			return Err(BassError::get());
+			return Err(BassError::get("Sample::channels").with_handle(self.0));
 		}
 		unsafe {
 			channels.set_len(*inserted as usize);
@@ -83,7 +85,7 @@ impl Sample {
 		if ok {
 			Ok(info)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Sample::info").with_handle(self.0))
 		}
 	}
 
@@ -92,7 +94,7 @@ impl Sample {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Sample::set_info").with_handle(self.0))
 		}
 	}
 
@@ -101,7 +103,7 @@ impl Sample {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Sample::stop").with_handle(self.0))
 		}
 	}
 }
@@ -114,11 +116,14 @@ impl HasHandle for Sample {
 
 impl Drop for Sample {
     fn drop(&mut self) {
+        handles::release(HandleKind::Sample, self.0);
         BASS_SampleFree(self.0);
     }
 }
 
-pub struct SampleChannel(HCHANNEL);
+/// A channel playing a sample. The channel belongs to the sample, so this doesn't free it.
+#[derive(Debug)]
+pub struct SampleChannel(pub(crate) HCHANNEL);
 
 impl HasHandle for SampleChannel {
     fn handle(&self) -> DWORD {
@@ -0,0 +1,296 @@
+//! 3D positional audio.
+//!
+//! Channels need to be created with `BASS_SAMPLE_3D` (and be mono) on a device initialised with
+//! `InitOptions::enable_3d`. Changes made with `Channel::set_3d_attributes`, `Channel::set_3d_position`,
+//! `Bass::set_listener` and `Bass::set_factors_3d` only take effect once `Bass::apply_3d` is called.
+
+use bass_sys::*;
+
+use crate::{
+	bass::error::{BassError, BassErrorCode},
+	BassResult,
+};
+
+/// A position, direction or velocity. BASS uses a left-handed coordinate system, with +Z going forwards.
+///
+/// Units are metres by default (see `Factors3d::distance`), and velocities are per second.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec3 {
+	pub x: f32,
+	pub y: f32,
+	pub z: f32,
+}
+
+impl Vec3 {
+	pub const ZERO: Vec3 = Vec3 { x: 0., y: 0., z: 0. };
+	pub const FORWARD: Vec3 = Vec3 { x: 0., y: 0., z: 1. };
+	pub const UP: Vec3 = Vec3 { x: 0., y: 1., z: 0. };
+
+	pub fn new(x: f32, y: f32, z: f32) -> Self {
+		Vec3 { x, y, z }
+	}
+}
+
+impl From<BASS_3DVECTOR> for Vec3 {
+	fn from(vector: BASS_3DVECTOR) -> Self {
+		Vec3 { x: vector.x, y: vector.y, z: vector.z }
+	}
+}
+
+impl From<Vec3> for BASS_3DVECTOR {
+	fn from(vector: Vec3) -> Self {
+		BASS_3DVECTOR { x: vector.x, y: vector.y, z: vector.z }
+	}
+}
+
+/// How a channel's 3D position is interpreted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Mode3d {
+	/// The position is absolute.
+	#[default]
+	Normal,
+	/// The position is relative to the listener.
+	Relative,
+	/// 3D processing is turned off, and the sound plays from the centre.
+	Off,
+}
+
+impl Mode3d {
+	fn raw(self) -> DWORD {
+		match self {
+			Mode3d::Normal => BASS_3DMODE_NORMAL,
+			Mode3d::Relative => BASS_3DMODE_RELATIVE,
+			Mode3d::Off => BASS_3DMODE_OFF,
+		}
+	}
+}
+
+impl From<DWORD> for Mode3d {
+	fn from(mode: DWORD) -> Self {
+		if mode == BASS_3DMODE_RELATIVE {
+			Mode3d::Relative
+		} else if mode == BASS_3DMODE_OFF {
+			Mode3d::Off
+		} else {
+			Mode3d::Normal
+		}
+	}
+}
+
+/// A channel's 3D attributes, from `Channel::get_3d_attributes`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attributes3d {
+	pub mode: Mode3d,
+	/// The distance the sound stops getting louder at as it gets closer.
+	pub min_distance: f32,
+	/// The distance the sound stops getting quieter at as it gets further away.
+	pub max_distance: f32,
+	/// The angle of the inside of the sound's projection cone, in degrees (0-360). `0` means no cone.
+	pub inside_angle: u32,
+	/// The angle of the outside of the projection cone, in degrees (0-360).
+	pub outside_angle: u32,
+	/// The volume outside the projection cone, 0 (silent) to 1 (the same as inside).
+	pub outside_volume: f32,
+}
+
+impl Default for Attributes3d {
+	/// The attributes BASS gives new channels.
+	fn default() -> Self {
+		Attributes3d {
+			mode: Mode3d::Normal,
+			min_distance: 1.,
+			max_distance: 1_000_000_000.,
+			inside_angle: 360,
+			outside_angle: 360,
+			outside_volume: 1.,
+		}
+	}
+}
+
+impl Attributes3d {
+	/// Checks the values are within the ranges BASS accepts.
+	pub(crate) fn validate(&self, operation: &'static str) -> BassResult<()> {
+		let valid = self.min_distance > 0.
+			&& self.max_distance >= self.min_distance
+			&& self.inside_angle <= 360
+			&& self.outside_angle <= 360
+			&& (0. ..=1.).contains(&self.outside_volume);
+		if valid {
+			Ok(())
+		} else {
+			Err(BassError::new(BassErrorCode::BassErrorIllParam, operation).with_context(format!("{self:?}")))
+		}
+	}
+
+	pub(crate) fn set(&self, handle: DWORD, operation: &'static str) -> BassResult<()> {
+		self.validate(operation)?;
+		let ok = BASS_ChannelSet3DAttributes(
+			handle,
+			self.mode.raw().0 as i32,
+			self.min_distance,
+			self.max_distance,
+			self.inside_angle as i32,
+			self.outside_angle as i32,
+			self.outside_volume,
+		);
+		if ok {
+			Ok(())
+		} else {
+			Err(BassError::get(operation).with_handle(handle))
+		}
+	}
+
+	pub(crate) fn get(handle: DWORD, operation: &'static str) -> BassResult<Self> {
+		let mut mode = DWORD(0);
+		let (mut min_distance, mut max_distance, mut outside_volume) = (0., 0., 0.);
+		let (mut inside_angle, mut outside_angle) = (DWORD(0), DWORD(0));
+		let ok = unsafe {
+			BASS_ChannelGet3DAttributes(
+				handle,
+				&mut mode,
+				&mut min_distance,
+				&mut max_distance,
+				&mut inside_angle,
+				&mut outside_angle,
+				&mut outside_volume,
+			)
+		};
+		if ok {
+			Ok(Attributes3d {
+				mode: mode.into(),
+				min_distance,
+				max_distance,
+				inside_angle: inside_angle.0,
+				outside_angle: outside_angle.0,
+				outside_volume,
+			})
+		} else {
+			Err(BassError::get(operation).with_handle(handle))
+		}
+	}
+}
+
+/// Where a channel is in 3D space, from `Channel::get_3d_position`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position3d {
+	pub position: Vec3,
+	/// The direction the sound is facing, which only matters if it has a projection cone.
+	pub orientation: Vec3,
+	/// Used to calculate the doppler effect. This doesn't move the channel.
+	pub velocity: Vec3,
+}
+
+impl Position3d {
+	pub(crate) fn set(&self, handle: DWORD, operation: &'static str) -> BassResult<()> {
+		let position: BASS_3DVECTOR = self.position.into();
+		let orientation: BASS_3DVECTOR = self.orientation.into();
+		let velocity: BASS_3DVECTOR = self.velocity.into();
+		let ok = unsafe { BASS_ChannelSet3DPosition(handle, &position, &orientation, &velocity) };
+		if ok {
+			Ok(())
+		} else {
+			Err(BassError::get(operation).with_handle(handle))
+		}
+	}
+
+	pub(crate) fn get(handle: DWORD, operation: &'static str) -> BassResult<Self> {
+		let mut position = BASS_3DVECTOR::default();
+		let mut orientation = BASS_3DVECTOR::default();
+		let mut velocity = BASS_3DVECTOR::default();
+		let ok = unsafe { BASS_ChannelGet3DPosition(handle, &mut position, &mut orientation, &mut velocity) };
+		if ok {
+			Ok(Position3d { position: position.into(), orientation: orientation.into(), velocity: velocity.into() })
+		} else {
+			Err(BassError::get(operation).with_handle(handle))
+		}
+	}
+}
+
+/// The position and orientation of the listener, from `Bass::listener`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Listener {
+	pub position: Vec3,
+	/// Used to calculate the doppler effect. This doesn't move the listener.
+	pub velocity: Vec3,
+	/// The direction the listener is facing.
+	pub front: Vec3,
+	/// The direction of the top of the listener's head. This should be at right angles to `front`.
+	pub top: Vec3,
+}
+
+impl Default for Listener {
+	fn default() -> Self {
+		Listener { position: Vec3::ZERO, velocity: Vec3::ZERO, front: Vec3::FORWARD, top: Vec3::UP }
+	}
+}
+
+impl Listener {
+	pub(crate) fn set(&self) -> BassResult<()> {
+		let position: BASS_3DVECTOR = self.position.into();
+		let velocity: BASS_3DVECTOR = self.velocity.into();
+		let front: BASS_3DVECTOR = self.front.into();
+		let top: BASS_3DVECTOR = self.top.into();
+		let ok = unsafe { BASS_Set3DPosition(&position, &velocity, &front, &top) };
+		if ok {
+			Ok(())
+		} else {
+			Err(BassError::get("Bass::set_listener"))
+		}
+	}
+
+	pub(crate) fn get() -> BassResult<Self> {
+		let mut position = BASS_3DVECTOR::default();
+		let mut velocity = BASS_3DVECTOR::default();
+		let mut front = BASS_3DVECTOR::default();
+		let mut top = BASS_3DVECTOR::default();
+		let ok = unsafe { BASS_Get3DPosition(&mut position, &mut velocity, &mut front, &mut top) };
+		if ok {
+			Ok(Listener { position: position.into(), velocity: velocity.into(), front: front.into(), top: top.into() })
+		} else {
+			Err(BassError::get("Bass::listener"))
+		}
+	}
+}
+
+/// How distances, rolloff and the doppler effect are calculated, from `Bass::factors_3d`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Factors3d {
+	/// The number of metres in a distance unit, eg. 0.3048 to use feet.
+	pub distance: f32,
+	/// How quickly sounds get quieter with distance, 0 (not at all) to 10 (10 times faster than real life).
+	pub rolloff: f32,
+	/// How strong the doppler effect is, 0 (none) to 10 (10 times real life).
+	pub doppler: f32,
+}
+
+impl Default for Factors3d {
+	fn default() -> Self {
+		Factors3d { distance: 1., rolloff: 1., doppler: 1. }
+	}
+}
+
+impl Factors3d {
+	pub(crate) fn set(&self) -> BassResult<()> {
+		let valid = self.distance > 0. && (0. ..=10.).contains(&self.rolloff) && (0. ..=10.).contains(&self.doppler);
+		if !valid {
+			return Err(BassError::new(BassErrorCode::BassErrorIllParam, "Bass::set_factors_3d")
+				.with_context(format!("{self:?}")));
+		}
+		if BASS_Set3DFactors(self.distance, self.rolloff, self.doppler) {
+			Ok(())
+		} else {
+			Err(BassError::get("Bass::set_factors_3d"))
+		}
+	}
+
+	pub(crate) fn get() -> BassResult<Self> {
+		let (mut distance, mut rolloff, mut doppler) = (0., 0., 0.);
+		let ok = unsafe { BASS_Get3DFactors(&mut distance, &mut rolloff, &mut doppler) };
+		if ok {
+			Ok(Factors3d { distance, rolloff, doppler })
+		} else {
+			Err(BassError::get("Bass::factors_3d"))
+		}
+	}
+}
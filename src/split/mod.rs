@@ -3,22 +3,28 @@ use std::ptr::{null, null_mut};
 use bass_sys::{BASS_Split_StreamCreate, BASS_Split_StreamGetAvailable, BASS_Split_StreamGetSource, BASS_Split_StreamGetSplits, BASS_Split_StreamReset, BASS_Split_StreamResetEx, BASS_StreamFree, DWORD, HSTREAM};
 
 use crate::{
-	bass::error::BassError,
+	bass::{
+		device::Device,
+		error::BassError,
+		handles::{self, HandleKind},
+	},
 	channel::{handle::HasHandle, mixer::MixableChannel, Channel, MixerSource},
 	stream::Stream,
 	BassResult,
 };
 
 #[derive(Debug)]
-pub struct Splitter(HSTREAM);
+pub struct Splitter(HSTREAM, #[allow(dead_code)] Option<Device>);
 
 impl Splitter {
 	pub fn create(stream: &Stream, flags: DWORD) -> BassResult<Self> {
-		let stream = unsafe { BASS_Split_StreamCreate(stream.handle(), flags, null()) };
+		let source = stream.handle();
+		let stream = unsafe { BASS_Split_StreamCreate(source, flags, null()) };
 		if stream != 0 {
-			Ok(Splitter(stream))
+			handles::register(HandleKind::Splitter, stream, None);
+			Ok(Splitter(stream, Device::of_channel(stream)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Splitter::create").with_handle(source))
 		}
 	}
 
@@ -27,7 +33,7 @@ impl Splitter {
 		if value.0 as i32 != -1 {
 			Ok(value.0)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Splitter::available").with_handle(handle.unwrap_or(self.0)))
 		}
 	}
 
@@ -36,7 +42,7 @@ impl Splitter {
 		if source != 0 {
 			Ok(source)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Splitter::source").with_handle(self.0))
 		}
 	}
 
@@ -55,7 +61,7 @@ impl Splitter {
 		let mut channels: Vec<HSTREAM> = Vec::with_capacity(*count as usize);
 		let inserted = unsafe { BASS_Split_StreamGetSplits(self.0, channels.as_mut_ptr(), count) };
 		if *inserted as i32 == -1 {
-			return Err(BassError::get());
+			return Err(BassError::get("Splitter::splits").with_handle(self.0));
 		}
 		unsafe {
 			channels.set_len(*inserted as usize);
@@ -72,7 +78,7 @@ impl Splitter {
 		if ok {
 			Ok(())
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Splitter::reset").with_handle(self.0))
 		}
 	}
 }
@@ -88,10 +94,25 @@ impl Channel for Splitter {}
 impl MixableChannel for Splitter {}
 impl MixerSource for Splitter {}
 
+/// A splitter stream that is owned elsewhere, so isn't freed when this is dropped. See `AnyChannel`.
+#[derive(Debug)]
+pub struct MaybeSplitter(pub(crate) HSTREAM);
+
+impl HasHandle for MaybeSplitter {
+	fn handle(&self) -> DWORD {
+		self.0 .0
+	}
+}
+
+impl Channel for MaybeSplitter {}
+
+impl MixableChannel for MaybeSplitter {}
+impl MixerSource for MaybeSplitter {}
+
 impl Drop for Splitter {
 	fn drop(&mut self) {
-		#[cfg(debug_assertions)]
-		println!("Freeing Mixer {:?}", self.0);
+		trace!("Freeing splitter {:?}", self.0);
+		handles::release(HandleKind::Splitter, self.0);
 		BASS_StreamFree(self.0); // Only reason it can fail is if the stream has already been freed
 	}
 }
@@ -2,14 +2,18 @@ use std::{ffi::c_void, ptr::null_mut};
 
 use bass_sys::{BASS_StreamCreate, HSTREAM, STREAMPROC_DEVICE};
 
-use crate::channel::{handle::HasHandle, Channel};
+use crate::{
+	bass::device::Device,
+	channel::{handle::HasHandle, Channel},
+};
 
 #[derive(Debug)]
-pub struct DeviceStream(HSTREAM);
+pub struct DeviceStream(HSTREAM, #[allow(dead_code)] Option<Device>);
 
 impl DeviceStream {
 	pub fn get() -> Self {
-		DeviceStream(BASS_StreamCreate(0, 0, 0, *STREAMPROC_DEVICE, null_mut() as *mut c_void))
+		let handle = BASS_StreamCreate(0, 0, 0, *STREAMPROC_DEVICE, null_mut() as *mut c_void);
+		DeviceStream(handle, Device::current())
 	}
 }
 
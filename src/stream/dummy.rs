@@ -2,19 +2,34 @@ use std::{os::raw::c_void, ptr::null_mut};
 
 use bass_sys::{BASS_StreamCreate, HSTREAM, STREAMPROC_DUMMY};
 
-use crate::{bass::error::BassError, BassResult};
+use crate::{
+	bass::{
+		device::Device,
+		error::BassError,
+		handles::{self, HandleKind},
+	},
+	BassResult,
+};
 
 #[derive(Debug)]
 #[allow(unused)]
-pub struct DummyStream(HSTREAM);
+pub struct DummyStream(HSTREAM, Option<Device>);
 
 impl DummyStream {
 	pub fn create() -> BassResult<Self> {
 		let handle = BASS_StreamCreate(0, 0, 0, STREAMPROC_DUMMY, null_mut::<c_void>());
 		if handle != 0 {
-			Ok(Self(handle))
+			handles::register(HandleKind::DummyStream, handle, None);
+			Ok(Self(handle, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("DummyStream::create"))
 		}
 	}
 }
+
+impl Drop for DummyStream {
+	fn drop(&mut self) {
+		// The stream isn't freed when dropped (it goes with its device), so this only stops tracking it.
+		handles::release(HandleKind::DummyStream, self.0);
+	}
+}
@@ -1,21 +1,28 @@
-use bass_sys::{BASS_ChannelBytes2Seconds, DWORD, HSTREAM};
+use bass_sys::{DWORD, HSTREAM};
 
 use crate::{
 	bass::error::{BassError, BassErrorCode},
-	channel::{handle::HasHandle, Channel},
+	channel::{data::channel_info, handle::HasHandle, info::ChannelType, Channel},
 };
 
-pub struct MaybeStream(HSTREAM);
+/// A stream that is owned elsewhere, so isn't freed when this is dropped. See also `AnyChannel`.
+#[derive(Debug)]
+pub struct MaybeStream(pub(crate) HSTREAM);
 
 impl TryFrom<DWORD> for MaybeStream {
-	type Error = BassErrorCode;
+	type Error = BassError;
 
+	/// Fails with `BASS_ERROR_HANDLE` if the handle isn't a channel, or `BASS_ERROR_ILLTYPE` if it is a channel that
+	/// isn't a stream (a sample channel, recording or MOD music).
 	fn try_from(value: DWORD) -> Result<Self, Self::Error> {
-		let ok = BASS_ChannelBytes2Seconds(value, 64);
-		if ok.is_sign_negative() {
-			Err(BassError::get())
-		} else {
+		let info = channel_info(value, "MaybeStream::try_from")?;
+		let channel_type = ChannelType::from_raw(info.ctype, info.plugin);
+		if channel_type.is_stream() {
 			Ok(MaybeStream(HSTREAM(value)))
+		} else {
+			Err(BassError::new(BassErrorCode::BassErrorIllType, "MaybeStream::try_from")
+				.with_handle(value)
+				.with_context(format!("{channel_type} isn't a stream")))
 		}
 	}
 }
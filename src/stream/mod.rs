@@ -4,7 +4,7 @@ pub mod maybe;
 pub mod push;
 
 use std::{
-	fmt::Debug, hash::Hash, ops::DerefMut, os::raw::{c_char, c_void}, ptr::null_mut, slice, sync::{Arc, Mutex, MutexGuard, Weak}
+	fmt::Debug, hash::Hash, ops::DerefMut, os::raw::{c_char, c_void}, path::Path, ptr::null_mut, slice, sync::{Arc, Mutex, MutexGuard, Weak}
 };
 
 use bass_sys::{
@@ -14,8 +14,22 @@ use bass_sys::{
 use widestring::U16CString;
 
 use crate::{
-	bass::error::BassError,
+	bass::{
+		device::Device,
+		error::BassError,
+		handles::{self, HandleKind},
+	},
 	channel::{handle::HasHandle, Channel},
+	file_name::FileName,
+	tags::{
+		self,
+		chapter::{self, Chapter},
+		id3::Id3v2Tag,
+		lyrics::Lyrics,
+		mp4,
+		picture::{self, Picture},
+		TagKind,
+	},
 	BassResult,
 };
 
@@ -33,6 +47,9 @@ pub struct Stream/*<T: Send + Sync + 'static>*/(
 	/// It is required for a "memory stream" to hold onto the data it is streaming.
 	#[allow(dead_code)]
 	Option<MemoryDataOrDownloadProc>,
+	/// Keeps the device the stream is playing on initialised.
+	#[allow(dead_code)]
+	Option<Device>,
 );
 
 #[repr(C)]
@@ -56,64 +73,46 @@ pub unsafe extern "C" fn download_proc<T: Send + Sync + 'static>(buffer: *const
 	// (user_box.callback)(data, user_box.user.as_mut());
 	// Box::into_raw(user_box);
 	let f = |mut user_box: MutexGuard<'_, DownloadProc<T>>| {
-		#[cfg(debug_assertions)]
-		println!("deref_mut'ing DownloadProc...");
 		let user_box = user_box.deref_mut();
-		#[cfg(debug_assertions)]
-		println!("Running user DownloadProc...");
 		let data = unsafe { slice::from_raw_parts(buffer as *const u8, (length.0 / 4) as usize) };
 		(user_box.callback)(data, user_box.user.as_mut());
-		#[cfg(debug_assertions)]
-		println!("Success!");
 	};
 	// Attempt to upgrade the weak pointer, failing gracefully if it has already been dropped.
 	match unsafe { Weak::from_raw(user as *const Mutex<DownloadProc<T>>) }.upgrade() {
 		Some(arc) => {
-			#[cfg(debug_assertions)]
-			println!("Strong: {}; Weak: {}", Arc::strong_count(&arc), Arc::weak_count(&arc));
 			// Handle mutex locking.
-			#[cfg(debug_assertions)]
-			println!("Locking Mutex...");
 			match arc.lock() {
 				Ok(user_box) => f(user_box),
 				Err(e) => f(e.into_inner()),
 			}
-			#[cfg(debug_assertions)]
-			println!("Resetting weak count...");
 			// Reset the weak count.
 			let weak = Arc::downgrade(&arc);
 			// Equivalent to std::mem::forget in a way...
 			let _ = weak.into_raw();
-			#[cfg(debug_assertions)]
-			println!("Hopefully success?");
-		}
-		None => {
-			#[cfg(debug_assertions)]
-			println!("User data freed")
 		}
+		// The user data has already been freed, so there is nothing to call.
+		None => {}
 	}
-	#[cfg(debug_assertions)]
-	println!("Everything should drop now.");
 }
 
 impl Stream {
 	pub fn create() {}
 
+	/// Paths are passed to BASS as they are, so ones that aren't valid Unicode can be opened too.
 	pub fn create_file(
-		path: impl AsRef<str>,
+		path: impl AsRef<Path>,
 		offset: impl Into<QWORD>,
 		length: impl Into<QWORD>,
 		flags: DWORD,
 	) -> BassResult<Self> {
-		let file: Vec<u16> = path.as_ref().encode_utf16().collect();
-		let file = U16CString::from_vec_truncate(file);
-		let handle = unsafe {
-			BASS_StreamCreateFile(false, file.as_ptr() as *const c_void, offset, length, flags | BASS_UNICODE)
-		};
+		let path = path.as_ref();
+		let file = FileName::new(path);
+		let handle = unsafe { BASS_StreamCreateFile(false, file.as_ptr(), offset, length, flags | file.flags()) };
 		if handle != 0 {
-			Ok(Self(handle, None))
+			handles::register(HandleKind::Stream, handle, None);
+			Ok(Self(handle, None, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Stream::create_file").with_context(path.display().to_string()))
 		}
 	}
 
@@ -128,9 +127,10 @@ impl Stream {
 			BASS_StreamCreateFile(true, data.as_ptr() as *const c_void, offset, length, flags | BASS_UNICODE)
 		};
 		if handle != 0 {
-			Ok(Self(handle, Some(MemoryDataOrDownloadProc::MemoryStream(data))))
+			handles::register(HandleKind::Stream, handle, None);
+			Ok(Self(handle, Some(MemoryDataOrDownloadProc::MemoryStream(data)), Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Stream::create_file_mem").with_context(format!("{} bytes", data.len())))
 		}
 	}
 
@@ -149,18 +149,20 @@ impl Stream {
 			)
 		};
 		if handle != 0 {
-			Ok(Self(handle, None))
+			handles::register(HandleKind::Stream, handle, None);
+			Ok(Self(handle, None, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Stream::create_url").with_context(path.as_ref()))
 		}
 	}
 
 	pub(crate) fn from_sample(handle: HSAMPLE, flags: DWORD) -> BassResult<Self> {
 		let ok = BASS_SampleGetChannel(handle, flags | BASS_SAMCHAN_STREAM);
 		if let Some(handle) = ok {
-			Ok(Self(HSTREAM(handle), None))
+			handles::register(HandleKind::Stream, handle, None);
+			Ok(Self(HSTREAM(handle), None, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Stream::from_sample").with_handle(handle))
 		}
 	}
 
@@ -168,12 +170,78 @@ impl Stream {
 		BASS_StreamGetFilePosition(self.0, mode).0
 	}
 
+	/// The pictures embedded in the stream's tags, like cover art. This works on decoding streams too.
+	///
+	/// BASS doesn't return MP4 cover art, so for a stream created from an MP4 file, this reads the file's `moov` box.
+	pub fn pictures(&self) -> BassResult<Vec<Picture>> {
+		let blocks = tags::read(self.handle(), "Stream::pictures")?;
+		let mut pictures = picture::pictures(&blocks);
+		pictures.extend(picture::read_binary(self.handle()));
+		if let Some(filename) = self.get_info()?.filename {
+			match mp4::read_moov(&filename) {
+				Ok(Some(moov)) => pictures.extend(picture::mp4_pictures(&moov)),
+				Ok(None) => {}
+				Err(error) => debug!("Couldn't read MP4 pictures from {}: {error}", filename.display()),
+			}
+		}
+		Ok(pictures)
+	}
+
+	/// The stream's chapters, from its ID3v2 tag, or from its MP4 chapter track or Nero chapters.
+	///
+	/// Like `pictures`, this reads the `moov` box of MP4 files. Use the chapters with `Channel::next_chapter`,
+	/// `Channel::previous_chapter` and `ChannelExt::watch_chapters`.
+	pub fn chapters(&self) -> BassResult<Vec<Chapter>> {
+		let blocks = tags::read(self.handle(), "Stream::chapters")?;
+		// Only needed for a last chapter without an end time, which runs to the end either way.
+		let length = self.length_time().ok();
+		let id3v2 = blocks.iter().find(|block| block.kind == TagKind::Id3v2);
+		let chapters = id3v2
+			.and_then(|block| Id3v2Tag::parse(&block.data))
+			.map(|tag| chapter::id3v2_chapters(&tag, length))
+			.unwrap_or_default();
+		if !chapters.is_empty() {
+			return Ok(chapters);
+		}
+		let Some(filename) = self.get_info()?.filename else { return Ok(chapters) };
+		let chapters = match mp4::read_moov(&filename) {
+			Ok(Some(moov)) => chapter::mp4_chapters(&filename, &moov, length),
+			Ok(None) => Ok(Vec::new()),
+			Err(error) => Err(error),
+		};
+		Ok(chapters.unwrap_or_else(|error| {
+			debug!("Couldn't read MP4 chapters from {}: {error}", filename.display());
+			Vec::new()
+		}))
+	}
+
+	/// The stream's lyrics. Synced lyrics from a SYLT frame come first, then a `.lrc` file next to the stream's file
+	/// with the same name, then plain lyrics from a USLT frame.
+	pub fn lyrics(&self) -> BassResult<Option<Lyrics>> {
+		let blocks = tags::read(self.handle(), "Stream::lyrics")?;
+		let id3v2 = blocks.iter().find(|block| block.kind == TagKind::Id3v2);
+		let embedded = id3v2.and_then(|block| Id3v2Tag::parse(&block.data)).and_then(|tag| Lyrics::from_id3v2(&tag));
+		if embedded.as_ref().is_some_and(|lyrics| lyrics.synced) {
+			return Ok(embedded);
+		}
+		if let Some(filename) = self.get_info()?.filename {
+			let lrc = filename.with_extension("lrc");
+			if lrc.is_file() {
+				match Lyrics::read_lrc(&lrc) {
+					Ok(lyrics) => return Ok(Some(lyrics)),
+					Err(error) => debug!("Couldn't read {}: {error}", lrc.display()),
+				}
+			}
+		}
+		Ok(embedded)
+	}
+
 	pub fn put_data(&self, data: &[u8]) -> BassResult<usize> {
 		let inserted = unsafe { BASS_StreamPutData(self.0, data.as_ptr() as *const c_void, data.len()) };
 		if inserted.0 as i32 != -1 {
 			Ok(inserted.0 as usize)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("Stream::put_data").with_handle(self.0))
 		}
 	}
 }
@@ -204,7 +272,7 @@ impl Stream {
 // 		if handle != 0 {
 // 			Ok(Self(handle, Some(MemoryDataOrDownloadProc::DownloadProc(user))))
 // 		} else {
-// 			Err(BassError::get())
+// 			Err(BassError::get("Stream::create_url_download_proc").with_context(path.as_ref()))
 // 		}
 // 	}
 // }
@@ -224,8 +292,8 @@ impl crate::channel::MixerSource for Stream {}
 
 impl Drop for Stream {
 	fn drop(&mut self) {
-		#[cfg(debug_assertions)]
-		println!("Freeing Stream {:?}", self.0);
+		trace!("Freeing stream {:?}", self.0);
+		handles::release(HandleKind::Stream, self.0);
 		BASS_StreamFree(self.0); // Only reason it can fail is if the stream has already been freed
 	}
 }
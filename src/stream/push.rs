@@ -2,18 +2,26 @@ use std::{ffi::c_void, ptr::null_mut};
 
 use bass_sys::{BASS_StreamCreate, BASS_StreamFree, BASS_StreamPutData, DWORD, HSTREAM, STREAMPROC_PUSH};
 
-use crate::{bass::error::BassError, BassResult};
+use crate::{
+	bass::{
+		device::Device,
+		error::BassError,
+		handles::{self, HandleKind},
+	},
+	BassResult,
+};
 
 #[derive(Debug)]
-pub struct PushStream(HSTREAM);
+pub struct PushStream(HSTREAM, #[allow(dead_code)] Option<Device>);
 
 impl PushStream {
 	pub fn create(frequency: impl Into<DWORD>, channels: impl Into<DWORD>, flags: DWORD) -> BassResult<Self> {
 		let handle = BASS_StreamCreate(frequency, channels, flags, *STREAMPROC_PUSH, null_mut::<c_void>());
 		if handle != 0 {
-			Ok(Self(handle))
+			handles::register(HandleKind::PushStream, handle, None);
+			Ok(Self(handle, Device::of_channel(handle)))
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("PushStream::create"))
 		}
 	}
 
@@ -22,15 +30,15 @@ impl PushStream {
 		if inserted.0 as i32 != -1 {
 			Ok(inserted.0 as usize)
 		} else {
-			Err(BassError::get())
+			Err(BassError::get("PushStream::put_data").with_handle(self.0))
 		}
 	}
 }
 
 impl Drop for PushStream{
     fn drop(&mut self) {
-        #[cfg(debug_assertions)]
-		println!("Freeing Stream {:?}", self.0);
+		trace!("Freeing push stream {:?}", self.0);
+		handles::release(HandleKind::PushStream, self.0);
 		BASS_StreamFree(self.0); // Only reason it can fail is if the stream has already been freed
     }
 }
\ No newline at end of file
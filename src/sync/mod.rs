@@ -5,6 +5,8 @@ use std::{
 
 use bass_sys::{BASS_ChannelRemoveSync, DWORD, HSYNC};
 
+use crate::bass::handles::{self, HandleKind};
+
 #[derive(Debug)]
 pub struct BassSync<T: Send + Sync> {
 	pub(crate) sync: HSYNC,
@@ -35,26 +37,10 @@ unsafe impl<T: Send + Sync> Sync for BassSync<T> {}
 
 impl<T: Send + Sync> Drop for BassSync<T> {
 	fn drop(&mut self) {
-		#[cfg(debug_assertions)]
-		println!("Freeing Sync {:?}", self.sync);
+		handles::release(HandleKind::Sync, self.sync);
 		let ok = BASS_ChannelRemoveSync(self.channel, self.sync);
-		#[cfg(debug_assertions)]
-		println!("Sync Freed: {}", ok);
-		#[cfg(debug_assertions)]
-		println!("Sync Removed... freeing user data...");
-		#[cfg(debug_assertions)]
-		{
-			// let arc = unsafe { Arc::from_raw(self.user) };
-			// let arc = self.user;
-			println!("Strong: {}; Weak: {}", Arc::strong_count(&self.user), Arc::weak_count(&self.user));
-		}
-		#[cfg(not(debug_assertions))]
-		{
-			// let _ = unsafe { Arc::from_raw(self.user) };
-			// let _ = self.user;
-		}
-		#[cfg(debug_assertions)]
-		println!("User data freed");
+		trace!("Freeing sync {:?} (removed: {})", self.sync, ok);
+		trace!("Sync user data: strong {}, weak {}", Arc::strong_count(&self.user), Arc::weak_count(&self.user));
 	}
 }
 
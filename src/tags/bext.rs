@@ -0,0 +1,66 @@
+//! The Broadcast Wave "bext" chunk, from `BASS_TAG_RIFF_BEXT`.
+
+use super::pairs::latin1;
+
+/// The size of the chunk before the coding history.
+pub(crate) const FIXED_LENGTH: usize = 602;
+
+/// The fields of the chunk, with the names from the Broadcast Wave specification. The time reference is the number of
+/// samples since midnight, and empty fields are left out.
+pub fn fields(data: &[u8]) -> Vec<(String, String)> {
+	if data.len() < FIXED_LENGTH {
+		return Vec::new();
+	}
+	let text = |range: std::ops::Range<usize>| {
+		let field = &data[range];
+		let end = field.iter().position(|byte| *byte == 0).unwrap_or(field.len());
+		latin1(&field[..end]).trim().to_string()
+	};
+	let time_reference = u64::from_le_bytes(data[338..346].try_into().unwrap());
+	let version = u16::from_le_bytes(data[346..348].try_into().unwrap());
+	let umid = &data[348..412];
+	let mut fields = vec![
+		("Description", text(0..256)),
+		("Originator", text(256..288)),
+		("OriginatorReference", text(288..320)),
+		("OriginationDate", text(320..330)),
+		("OriginationTime", text(330..338)),
+		("TimeReference", time_reference.to_string()),
+		("Version", version.to_string()),
+	];
+	if umid.iter().any(|byte| *byte != 0) {
+		fields.push(("UMID", umid.iter().map(|byte| format!("{byte:02x}")).collect()));
+	}
+	fields.push(("CodingHistory", text(FIXED_LENGTH..data.len())));
+	fields.into_iter().filter(|(_, value)| !value.is_empty()).map(|(key, value)| (key.to_string(), value)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn chunk() {
+		let mut data = vec![0; FIXED_LENGTH];
+		data[..7].copy_from_slice(b"Take 12");
+		data[256..263].copy_from_slice(b"Studio ");
+		data[320..330].copy_from_slice(b"2019-03-04");
+		data[338..346].copy_from_slice(&48000u64.to_le_bytes());
+		data[346] = 1;
+		data.extend(b"A=PCM,F=48000\r\n");
+		let fields = fields(&data);
+		let get = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+		assert_eq!(get("Description"), Some("Take 12"));
+		assert_eq!(get("Originator"), Some("Studio"));
+		assert_eq!(get("OriginationDate"), Some("2019-03-04"));
+		assert_eq!(get("TimeReference"), Some("48000"));
+		assert_eq!(get("Version"), Some("1"));
+		assert_eq!(get("UMID"), None);
+		assert_eq!(get("CodingHistory"), Some("A=PCM,F=48000"));
+	}
+
+	#[test]
+	fn too_short() {
+		assert!(fields(&[0; 100]).is_empty());
+	}
+}
@@ -0,0 +1,371 @@
+//! Chapters, from ID3v2 CHAP and CTOC frames, MP4 chapter tracks and Nero `chpl` boxes.
+
+use std::{
+	fs::File,
+	io::{self, Read, Seek, SeekFrom},
+	path::Path,
+	time::Duration,
+};
+
+use super::{
+	id3::{self, Id3v2Frame, Id3v2Tag},
+	mp4,
+	pairs::latin1,
+};
+
+/// A chapter of a stream, like those in audiobooks and podcasts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chapter {
+	/// The title, which is empty if the chapter doesn't have one.
+	pub title: String,
+	pub start: Duration,
+	/// `None` if the chapter runs to the end of a stream whose length isn't known.
+	pub end: Option<Duration>,
+}
+
+/// The chapters in an ID3v2 tag, in order of their starts. If the tag has a top level CTOC frame, only the chapters
+/// it (or the tables of contents under it) lists are included.
+///
+/// Chapters without an end time end where the next one starts, and the last one at `length`.
+pub fn id3v2_chapters(tag: &Id3v2Tag, length: Option<Duration>) -> Vec<Chapter> {
+	let listed = tag.frames("CTOC").find(|frame| toc(frame).is_some_and(|(top_level, _)| top_level)).map(|frame| {
+		let mut listed = Vec::new();
+		toc_order(tag, frame, &mut listed, 0);
+		listed
+	});
+	let mut chaps: Vec<(String, Duration, u32)> = tag
+		.frames("CHAP")
+		.filter_map(|frame| {
+			let (id, rest) = id3::split_terminated(0, &frame.data);
+			let id = latin1(id);
+			if listed.as_ref().is_some_and(|listed| !listed.is_empty() && !listed.contains(&id)) {
+				return None;
+			}
+			let times = rest.get(..16)?;
+			let time = |index: usize| u32::from_be_bytes(times[index * 4..index * 4 + 4].try_into().unwrap());
+			let title = id3::parse_frames(tag.version, false, &rest[16..])
+				.iter()
+				.find(|frame| frame.id == "TIT2")
+				.map(text)
+				.unwrap_or_default();
+			Some((title, Duration::from_millis(time(0) as u64), time(1)))
+		})
+		.collect();
+	chaps.sort_by_key(|(_, start, _)| *start);
+
+	let starts: Vec<Duration> = chaps.iter().map(|(_, start, _)| *start).collect();
+	chaps
+		.into_iter()
+		.enumerate()
+		.map(|(index, (title, start, end))| {
+			let end = match Duration::from_millis(end as u64) {
+				// 0xffffffff is sometimes used to leave it out.
+				time if time > start && end != u32::MAX => Some(time),
+				_ => starts[index + 1..].iter().copied().find(|next| *next > start).or(length),
+			};
+			Chapter { title, start, end }
+		})
+		.collect()
+}
+
+/// Whether a CTOC frame is the top level one, and the element IDs of its children.
+fn toc(frame: &Id3v2Frame) -> Option<(bool, Vec<String>)> {
+	let (_, rest) = id3::split_terminated(0, &frame.data);
+	let (&flags, rest) = rest.split_first()?;
+	let (&count, mut rest) = rest.split_first()?;
+	let mut children = Vec::new();
+	for _ in 0..count {
+		let (child, after) = id3::split_terminated(0, rest);
+		children.push(latin1(child));
+		rest = after;
+	}
+	Some(((flags & 0x02) != 0, children))
+}
+
+/// Adds the CHAP element IDs under a CTOC frame to `order`, following nested CTOC frames.
+fn toc_order(tag: &Id3v2Tag, frame: &Id3v2Frame, order: &mut Vec<String>, depth: usize) {
+	// Tables of contents can't legitimately contain themselves, but a broken tag could.
+	if depth > 8 {
+		return;
+	}
+	let Some((_, children)) = toc(frame) else { return };
+	for child in children {
+		let nested = tag.frames("CTOC").find(|frame| id3::split_terminated(0, &frame.data).0 == child.as_bytes());
+		match nested {
+			Some(nested) => toc_order(tag, nested, order, depth + 1),
+			None => order.push(child),
+		}
+	}
+}
+
+fn text(frame: &Id3v2Frame) -> String {
+	match frame.data.split_first() {
+		Some((&encoding, text)) => id3::decode_text(encoding, text),
+		None => String::new(),
+	}
+}
+
+/// The chapters in a Nero `chpl` box, in the `udta` box of a `moov` box.
+pub fn nero_chapters(moov: &[u8], length: Option<Duration>) -> Vec<Chapter> {
+	let Some(chpl) = mp4::find(moov, &[b"udta", b"chpl"]) else { return Vec::new() };
+	let Some((&version, rest)) = chpl.split_first() else { return Vec::new() };
+	// The flags, then (in version 1) 4 more bytes.
+	let Some(mut rest) = rest.get(if version == 1 { 7 } else { 3 }..) else { return Vec::new() };
+	let Some((&count, after)) = rest.split_first() else { return Vec::new() };
+	rest = after;
+	let mut starts = Vec::new();
+	for _ in 0..count {
+		let Some(header) = rest.get(..9) else { break };
+		// In 100 nanosecond units.
+		let start = u64::from_be_bytes(header[..8].try_into().unwrap());
+		let Some(title) = rest.get(9..9 + header[8] as usize) else { break };
+		starts.push((Duration::from_nanos(start.saturating_mul(100)), String::from_utf8_lossy(title).into_owned()));
+		rest = &rest[9 + title.len()..];
+	}
+	with_ends(starts, length)
+}
+
+/// A sample of an MP4 chapter track: where its title is in the file, and when it starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrackSample {
+	pub start: Duration,
+	pub offset: u64,
+	pub size: u32,
+}
+
+/// The samples of the chapter track (a text track referred to by another track's `tref`/`chap` box) in a `moov` box.
+pub fn chapter_track(moov: &[u8]) -> Option<Vec<TrackSample>> {
+	let chapter_id = mp4::boxes(moov)
+		.filter(|(kind, _)| kind == b"trak")
+		.find_map(|(_, trak)| mp4::find(trak, &[b"tref", b"chap"]))
+		.and_then(|chap| chap.get(..4))
+		.map(|id| u32::from_be_bytes(id.try_into().unwrap()))?;
+	let trak = mp4::boxes(moov).filter(|(kind, _)| kind == b"trak").map(|(_, trak)| trak).find(|trak| {
+		let tkhd = mp4::find(trak, &[b"tkhd"]).unwrap_or_default();
+		let at = if tkhd.first() == Some(&1) { 20 } else { 12 };
+		tkhd.get(at..at + 4).is_some_and(|id| u32::from_be_bytes(id.try_into().unwrap()) == chapter_id)
+	})?;
+
+	let mdhd = mp4::find(trak, &[b"mdia", b"mdhd"])?;
+	let at = if mdhd.first() == Some(&1) { 20 } else { 12 };
+	let timescale = u32::from_be_bytes(mdhd.get(at..at + 4)?.try_into().unwrap());
+	let stbl = mp4::find(trak, &[b"mdia", b"minf", b"stbl"])?;
+	// Every table is a full box, with 4 bytes of version and flags then the number of entries.
+	let table = |kind: &[u8; 4]| {
+		let body = mp4::find(stbl, &[kind])?;
+		let count = u32::from_be_bytes(body.get(4..8)?.try_into().unwrap()) as usize;
+		Some((count, &body[8..]))
+	};
+	let number = |data: &[u8], index: usize| {
+		data.get(index * 4..index * 4 + 4).map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+	};
+
+	// The duration of each sample.
+	let (count, stts) = table(b"stts")?;
+	let mut durations = Vec::new();
+	for entry in 0..count {
+		let samples = number(stts, entry * 2)?;
+		let duration = number(stts, entry * 2 + 1)?;
+		durations.extend(std::iter::repeat(duration).take(samples.min(u16::MAX as u32) as usize));
+	}
+
+	// The size of each sample: `stsz` has a size for every sample, unless they are all the same.
+	let stsz = mp4::find(stbl, &[b"stsz"])?;
+	let uniform = number(stsz, 1)?;
+	let sizes: Vec<u32> = (0..durations.len())
+		.map(|index| if uniform != 0 { Some(uniform) } else { number(stsz, 3 + index) })
+		.collect::<Option<_>>()?;
+
+	// The offset of each chunk, then which chunks the samples are in.
+	let chunks: Vec<u64> = match table(b"stco") {
+		Some((count, stco)) => (0..count).map(|index| number(stco, index).map(u64::from)).collect::<Option<_>>()?,
+		None => {
+			let (count, co64) = table(b"co64")?;
+			(0..count)
+				.map(|index| {
+					co64.get(index * 8..index * 8 + 8).map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+				})
+				.collect::<Option<_>>()?
+		}
+	};
+	let (count, stsc) = table(b"stsc")?;
+	let mut samples = Vec::with_capacity(durations.len());
+	let mut time = 0u64;
+	for entry in 0..count {
+		let first = number(stsc, entry * 3)? as usize;
+		let per_chunk = number(stsc, entry * 3 + 1)? as usize;
+		// Chunks are numbered from 1, and each entry lasts until the next one's first chunk.
+		let last = match number(stsc, (entry + 1) * 3) {
+			Some(next) if entry + 1 < count => next as usize,
+			_ => chunks.len() + 1,
+		};
+		for chunk in first..last {
+			let mut offset = *chunks.get(chunk.checked_sub(1)?)?;
+			for _ in 0..per_chunk {
+				let index = samples.len();
+				if index == durations.len() {
+					break;
+				}
+				let start = Duration::from_secs_f64(time as f64 / timescale.max(1) as f64);
+				samples.push(TrackSample { start, offset, size: sizes[index] });
+				offset += sizes[index] as u64;
+				time += durations[index] as u64;
+			}
+		}
+	}
+	Some(samples)
+}
+
+/// The title in a chapter track sample: a 16-bit length, then UTF-8 or UTF-16 (with a byte order mark) text.
+pub fn track_sample_title(sample: &[u8]) -> String {
+	let Some(length) = sample.get(..2).map(|length| u16::from_be_bytes([length[0], length[1]]) as usize) else {
+		return String::new();
+	};
+	let text = &sample[2..(2 + length).min(sample.len())];
+	match text {
+		[0xfe, 0xff, ..] | [0xff, 0xfe, ..] => id3::decode_text(1, text),
+		_ => String::from_utf8_lossy(text).into_owned(),
+	}
+}
+
+/// The chapters in an MP4 file from its `moov` box: its chapter track if it has one, or else its Nero chapters.
+pub fn mp4_chapters(path: impl AsRef<Path>, moov: &[u8], length: Option<Duration>) -> io::Result<Vec<Chapter>> {
+	let Some(samples) = chapter_track(moov).filter(|samples| !samples.is_empty()) else {
+		return Ok(nero_chapters(moov, length));
+	};
+	let mut file = File::open(path)?;
+	let mut starts = Vec::with_capacity(samples.len());
+	for sample in samples {
+		let mut data = vec![0; sample.size.min(u16::MAX as u32 + 2) as usize];
+		file.seek(SeekFrom::Start(sample.offset))?;
+		file.read_exact(&mut data)?;
+		starts.push((sample.start, track_sample_title(&data)));
+	}
+	Ok(with_ends(starts, length))
+}
+
+/// Makes chapters from their starts and titles, ending each where the next starts, and the last at `length`.
+fn with_ends(mut starts: Vec<(Duration, String)>, length: Option<Duration>) -> Vec<Chapter> {
+	starts.sort_by_key(|(start, _)| *start);
+	let ends: Vec<Option<Duration>> = starts.iter().skip(1).map(|(start, _)| Some(*start)).chain([length]).collect();
+	starts
+		.into_iter()
+		.zip(ends)
+		.map(|((start, title), end)| Chapter { title, start, end: end.map(|end| end.max(start)) })
+		.collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use crate::tags::{id3::tests::frame3, mp4::tests::mp4_box};
+
+	pub(crate) fn chap(id: &str, start: u32, end: u32, title: &str) -> Id3v2Frame {
+		let mut data = [id.as_bytes(), &[0]].concat();
+		for time in [start, end, u32::MAX, u32::MAX] {
+			data.extend(time.to_be_bytes());
+		}
+		data.extend(frame3("TIT2", 0, &[&[3][..], title.as_bytes()].concat()));
+		Id3v2Frame { id: "CHAP".to_string(), data }
+	}
+
+	pub(crate) fn ctoc(id: &str, flags: u8, children: &[&str]) -> Id3v2Frame {
+		let mut data = [id.as_bytes(), &[0, flags, children.len() as u8]].concat();
+		for child in children {
+			data.extend(child.as_bytes().iter().chain([&0]));
+		}
+		Id3v2Frame { id: "CTOC".to_string(), data }
+	}
+
+	#[test]
+	fn id3v2_chap_frames() {
+		let tag = Id3v2Tag {
+			version: 3,
+			frames: vec![chap("b", 60_000, u32::MAX, "Two"), chap("a", 0, 60_000, "One"), chap("c", 90_000, 0, "")],
+		};
+		let summary = |length: Option<Duration>| {
+			let chapters = id3v2_chapters(&tag, length);
+			chapters
+				.iter()
+				.map(|c| (c.title.clone(), c.start.as_secs(), c.end.map(|end| end.as_secs())))
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(
+			summary(Some(Duration::from_secs(100))),
+			[("One".to_string(), 0, Some(60)), ("Two".to_string(), 60, Some(90)), ("".to_string(), 90, Some(100))]
+		);
+		assert_eq!(summary(None)[2], ("".to_string(), 90, None));
+	}
+
+	#[test]
+	fn ctoc_order() {
+		let tag = Id3v2Tag {
+			version: 3,
+			frames: vec![
+				chap("a", 0, u32::MAX, "Intro"),
+				chap("b", 10_000, 20_000, "Outro"),
+				chap("x", 5_000, 6_000, "Unlisted"),
+				ctoc("toc", 0x03, &["b", "sub"]),
+				ctoc("sub", 0x01, &["a"]),
+			],
+		};
+		let chapters = id3v2_chapters(&tag, None);
+		let summary: Vec<_> = chapters.iter().map(|c| (c.title.as_str(), c.start, c.end)).collect();
+		let at = |seconds| Duration::from_secs(seconds);
+		assert_eq!(summary, [("Intro", at(0), Some(at(10))), ("Outro", at(10), Some(at(20)))]);
+	}
+
+	#[test]
+	fn nero() {
+		let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+		for (start, title) in [(0u64, "First"), (300_000_000, "Second")] {
+			chpl.extend(start.to_be_bytes());
+			chpl.push(title.len() as u8);
+			chpl.extend(title.as_bytes());
+		}
+		let moov = mp4_box(b"udta", &mp4_box(b"chpl", &chpl));
+		let chapters = nero_chapters(&moov, Some(Duration::from_secs(45)));
+		assert_eq!(chapters.len(), 2);
+		assert_eq!(
+			chapters[0],
+			Chapter { title: "First".to_string(), start: Duration::ZERO, end: Some(Duration::from_secs(30)) }
+		);
+		assert_eq!((chapters[1].start, chapters[1].end), (Duration::from_secs(30), Some(Duration::from_secs(45))));
+	}
+
+	#[test]
+	fn chapter_tracks() {
+		let numbers = |numbers: &[u32]| numbers.iter().flat_map(|number| number.to_be_bytes()).collect::<Vec<_>>();
+		let table = |kind: &[u8; 4], count: u32, entries: &[u32]| {
+			mp4_box(kind, &[vec![0; 4], numbers(&[count]), numbers(entries)].concat())
+		};
+		let stbl = [
+			table(b"stts", 2, &[1, 500, 1, 1500]),
+			mp4_box(b"stsz", &[vec![0; 4], numbers(&[0, 2, 7, 9])].concat()),
+			table(b"stsc", 1, &[1, 2, 1]),
+			table(b"stco", 1, &[4096]),
+		]
+		.concat();
+		let mut mdhd = vec![0; 12];
+		mdhd.extend(1000u32.to_be_bytes());
+		let mdia = mp4_box(b"mdia", &[mp4_box(b"mdhd", &mdhd), mp4_box(b"minf", &mp4_box(b"stbl", &stbl))].concat());
+		let tkhd = |id: u32| mp4_box(b"tkhd", &[vec![0; 12], id.to_be_bytes().to_vec()].concat());
+		let audio = mp4_box(b"trak", &[tkhd(1), mp4_box(b"tref", &mp4_box(b"chap", &numbers(&[2])))].concat());
+		let text = mp4_box(b"trak", &[tkhd(2), mdia].concat());
+		let samples = chapter_track(&[audio, text].concat()).unwrap();
+		assert_eq!(
+			samples,
+			[
+				TrackSample { start: Duration::ZERO, offset: 4096, size: 7 },
+				TrackSample { start: Duration::from_millis(500), offset: 4103, size: 9 },
+			]
+		);
+	}
+
+	#[test]
+	fn track_sample_titles() {
+		assert_eq!(track_sample_title(b"\x00\x05Intro\x00\x00\x00\x0cencd"), "Intro");
+		assert_eq!(track_sample_title(b"\x00\x06\xfe\xff\x00H\x00i"), "Hi");
+		assert_eq!(track_sample_title(b"\x00"), "");
+	}
+}
@@ -0,0 +1,611 @@
+//! ID3v1 and ID3v2 tags, from `BASS_TAG_ID3` and `BASS_TAG_ID3V2`.
+
+use std::borrow::Cow;
+
+use super::pairs::latin1;
+
+pub(crate) const ID3V1_LENGTH: usize = 128;
+pub(crate) const ID3V2_HEADER_LENGTH: usize = 10;
+
+/// The total length of an ID3v2 tag (including its header and footer) from its header.
+pub(crate) fn id3v2_length(header: &[u8]) -> usize {
+	let footer = if header[3] >= 4 && (header[5] & 0x10) != 0 { ID3V2_HEADER_LENGTH } else { 0 };
+	ID3V2_HEADER_LENGTH + syncsafe(&header[6..10]) as usize + footer
+}
+
+/// The fields of an ID3v1 (or ID3v1.1) tag, named like Vorbis comments: TITLE, ARTIST, ALBUM, DATE, COMMENT,
+/// TRACKNUMBER and GENRE.
+pub fn id3v1_fields(data: &[u8]) -> Vec<(String, String)> {
+	if data.len() < ID3V1_LENGTH || &data[..3] != b"TAG" {
+		return Vec::new();
+	}
+	let text = |field: &[u8]| {
+		let end = field.iter().position(|byte| *byte == 0).unwrap_or(field.len());
+		latin1(&field[..end]).trim().to_string()
+	};
+	let comment = &data[97..127];
+	// ID3v1.1 uses the last byte of the comment for the track number.
+	let (comment, track) =
+		if comment[28] == 0 && comment[29] != 0 { (&comment[..28], comment[29]) } else { (comment, 0) };
+	let mut fields = vec![
+		("TITLE", text(&data[3..33])),
+		("ARTIST", text(&data[33..63])),
+		("ALBUM", text(&data[63..93])),
+		("DATE", text(&data[93..97])),
+		("COMMENT", text(comment)),
+	];
+	if track != 0 {
+		fields.push(("TRACKNUMBER", track.to_string()));
+	}
+	if let Some(genre) = GENRES.get(data[127] as usize) {
+		fields.push(("GENRE", genre.to_string()));
+	}
+	fields.into_iter().filter(|(_, value)| !value.is_empty()).map(|(key, value)| (key.to_string(), value)).collect()
+}
+
+/// A parsed ID3v2 tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Id3v2Tag {
+	/// The major version: 2, 3 or 4.
+	pub version: u8,
+	pub frames: Vec<Id3v2Frame>,
+}
+
+/// A frame of an ID3v2 tag, with any unsynchronisation, grouping and data length fields removed.
+///
+/// Version 2.2 frames have three character IDs (eg. "TT2" rather than "TIT2").
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Id3v2Frame {
+	pub id: String,
+	pub data: Vec<u8>,
+}
+
+impl Id3v2Tag {
+	/// Parses a tag, returning `None` if it doesn't start with an ID3v2 header. Compressed and encrypted frames are
+	/// skipped, as is a whole version 2.2 tag that is compressed.
+	pub fn parse(data: &[u8]) -> Option<Self> {
+		if data.len() < ID3V2_HEADER_LENGTH || &data[..3] != b"ID3" || !(2..=4).contains(&data[3]) {
+			return None;
+		}
+		let version = data[3];
+		let flags = data[5];
+		let end = (ID3V2_HEADER_LENGTH + syncsafe(&data[6..10]) as usize).min(data.len());
+		let mut tag = Id3v2Tag { version, frames: Vec::new() };
+		if version == 2 && (flags & 0x40) != 0 {
+			return Some(tag);
+		}
+		let unsynchronised = (flags & 0x80) != 0;
+		// Version 2.4 unsynchronises each frame instead, which its frame flags also say.
+		let body = if unsynchronised && version < 4 {
+			remove_unsync(&data[ID3V2_HEADER_LENGTH..end])
+		} else {
+			Cow::Borrowed(&data[ID3V2_HEADER_LENGTH..end])
+		};
+
+		let mut position = 0;
+		if version >= 3 && (flags & 0x40) != 0 && body.len() >= 4 {
+			// The extended header's size excludes itself in version 2.3, but not in 2.4.
+			position = match version {
+				3 => 4 + u32::from_be_bytes(body[..4].try_into().unwrap()) as usize,
+				_ => syncsafe(&body[..4]) as usize,
+			};
+		}
+		tag.frames = parse_frames(version, unsynchronised, body.get(position..).unwrap_or_default());
+		Some(tag)
+	}
+
+	/// The frames with an ID.
+	pub fn frames<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Id3v2Frame> + 'a {
+		self.frames.iter().filter(move |frame| frame.id == id)
+	}
+
+	/// The text, URL, comment and lyrics frames as key/value pairs. Keys are frame IDs, followed by the description
+	/// for user-defined frames and described comments (eg. "TXXX:REPLAYGAIN_TRACK_GAIN" or "COMM:iTunNORM").
+	/// Text frames with several values have them joined with "; ".
+	pub fn fields(&self) -> Vec<(String, String)> {
+		let mut fields = Vec::new();
+		for frame in &self.frames {
+			let id = frame.id.as_str();
+			let data = frame.data.as_slice();
+			let field = match id {
+				"TXXX" | "TXX" | "WXXX" | "WXX" if !data.is_empty() => {
+					let (description, value) = split_terminated(data[0], &data[1..]);
+					let value =
+						if id.starts_with('W') { latin1(trim_nulls(value)) } else { decode_text(data[0], value) };
+					Some((format!("{id}:{}", decode_text(data[0], description)), value))
+				}
+				"COMM" | "COM" | "USLT" | "ULT" if data.len() >= 4 => {
+					let (description, text) = split_terminated(data[0], &data[4..]);
+					let description = decode_text(data[0], description);
+					let key = if description.is_empty() { id.to_string() } else { format!("{id}:{description}") };
+					Some((key, decode_text(data[0], text)))
+				}
+				_ if id.starts_with('T') && !data.is_empty() => {
+					Some((id.to_string(), text_values(data[0], &data[1..])))
+				}
+				_ if id.starts_with('W') => Some((id.to_string(), latin1(trim_nulls(data)))),
+				_ => None,
+			};
+			if let Some((key, value)) = field {
+				if !value.is_empty() {
+					fields.push((key, value));
+				}
+			}
+		}
+		fields
+	}
+}
+
+/// Parses a series of frames, until the padding at the end of a tag. This is also used for the frames embedded in
+/// CHAP and CTOC frames.
+///
+/// `unsynchronised` is whether the tag's header says every frame is unsynchronised, which only matters for version
+/// 2.4; earlier versions unsynchronise the whole tag at once.
+pub(crate) fn parse_frames(version: u8, unsynchronised: bool, body: &[u8]) -> Vec<Id3v2Frame> {
+	let mut frames = Vec::new();
+	let mut position = 0;
+	let (id_length, header_length) = if version == 2 { (3, 6) } else { (4, 10) };
+	while position + header_length <= body.len() {
+		let header = &body[position..position + header_length];
+		let id = &header[..id_length];
+		// Anything else is padding.
+		if !id.iter().all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit()) {
+			break;
+		}
+		let size = match version {
+			2 => u32::from_be_bytes([0, header[3], header[4], header[5]]) as usize,
+			3 => u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize,
+			_ => syncsafe(&header[4..8]) as usize,
+		};
+		let format = if version == 2 { 0 } else { header[9] };
+		let start = position + header_length;
+		let Some(frame) = body.get(start..start + size) else { break };
+		position = start + size;
+
+		let frame = match version {
+			2 => Some(Cow::Borrowed(frame)),
+			3 => {
+				let grouping = if (format & 0x20) != 0 { 1 } else { 0 };
+				// Compressed or encrypted.
+				((format & 0xc0) == 0).then(|| frame.get(grouping..)).flatten().map(Cow::Borrowed)
+			}
+			_ => {
+				let skip = if (format & 0x40) != 0 { 1 } else { 0 } + if (format & 0x01) != 0 { 4 } else { 0 };
+				let frame = ((format & 0x0c) == 0).then(|| frame.get(skip..)).flatten();
+				frame.map(|frame| {
+					if unsynchronised || (format & 0x02) != 0 {
+						remove_unsync(frame)
+					} else {
+						Cow::Borrowed(frame)
+					}
+				})
+			}
+		};
+		if let Some(frame) = frame {
+			frames.push(Id3v2Frame { id: latin1(id), data: frame.into_owned() });
+		}
+	}
+	frames
+}
+
+/// Decodes a 28-bit "syncsafe" integer, which has the top bit of each byte clear.
+fn syncsafe(bytes: &[u8]) -> u32 {
+	bytes.iter().fold(0, |value, byte| (value << 7) | (*byte & 0x7f) as u32)
+}
+
+/// Reverses unsynchronisation, which inserts a 0 after every 0xff.
+pub(crate) fn remove_unsync(data: &[u8]) -> Cow<'_, [u8]> {
+	if !data.windows(2).any(|pair| pair == [0xff, 0]) {
+		return Cow::Borrowed(data);
+	}
+	let mut output = Vec::with_capacity(data.len());
+	let mut previous = 0;
+	for byte in data {
+		if !(previous == 0xff && *byte == 0) {
+			output.push(*byte);
+		}
+		// A 0 that was removed doesn't count as the start of another pair.
+		previous = if previous == 0xff && *byte == 0 { 0 } else { *byte };
+	}
+	Cow::Owned(output)
+}
+
+/// Whether an encoding uses two bytes per character (UTF-16).
+fn is_wide(encoding: u8) -> bool {
+	encoding == 1 || encoding == 2
+}
+
+/// Splits a null-terminated string in an encoding from the data after it.
+pub(crate) fn split_terminated(encoding: u8, data: &[u8]) -> (&[u8], &[u8]) {
+	let end = if is_wide(encoding) {
+		data.chunks_exact(2).position(|pair| pair == [0, 0]).map(|index| (index * 2, index * 2 + 2))
+	} else {
+		data.iter().position(|byte| *byte == 0).map(|index| (index, index + 1))
+	};
+	match end {
+		Some((end, rest)) => (&data[..end], &data[rest..]),
+		None => (data, &[]),
+	}
+}
+
+fn trim_nulls(data: &[u8]) -> &[u8] {
+	let end = data.iter().rposition(|byte| *byte != 0).map_or(0, |index| index + 1);
+	&data[..end]
+}
+
+/// Decodes text in one of the ID3v2 encodings: 0 is Latin-1, 1 is UTF-16 with a byte order mark, 2 is UTF-16BE and 3
+/// is UTF-8. Anything after a terminator is ignored.
+pub(crate) fn decode_text(encoding: u8, data: &[u8]) -> String {
+	let (text, _) = split_terminated(encoding, data);
+	match encoding {
+		1 | 2 => {
+			let (big_endian, text) = match text {
+				[0xfe, 0xff, rest @ ..] => (true, rest),
+				[0xff, 0xfe, rest @ ..] => (false, rest),
+				// Some taggers leave out the byte order mark, which is most often for little endian.
+				_ => (encoding == 2, text),
+			};
+			let units = text.chunks_exact(2).map(|pair| {
+				if big_endian {
+					u16::from_be_bytes([pair[0], pair[1]])
+				} else {
+					u16::from_le_bytes([pair[0], pair[1]])
+				}
+			});
+			char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+		}
+		3 => String::from_utf8_lossy(text).into_owned(),
+		_ => latin1(text),
+	}
+}
+
+/// Decodes every null-separated value of a text frame.
+fn text_values(encoding: u8, mut data: &[u8]) -> String {
+	let mut values = Vec::new();
+	while !data.is_empty() {
+		let (value, rest) = split_terminated(encoding, data);
+		let value = decode_text(encoding, value);
+		if !value.is_empty() {
+			values.push(value);
+		}
+		data = rest;
+	}
+	values.join("; ")
+}
+
+/// Resolves the genre references in a TCON frame, like "(13)", "(13)Britpop" (a refinement) or "13" (version 2.4).
+pub fn genre(value: &str) -> String {
+	let named = |number: &str| number.parse::<usize>().ok().and_then(|number| GENRES.get(number).copied());
+	if let Some(name) = named(value) {
+		return name.to_string();
+	}
+	let mut names = Vec::new();
+	let mut rest = value;
+	while let Some(reference) = rest.strip_prefix('(').filter(|rest| !rest.starts_with('(')) {
+		let Some((reference, after)) = reference.split_once(')') else { break };
+		match reference {
+			"RX" => names.push("Remix"),
+			"CR" => names.push("Cover"),
+			_ => names.extend(named(reference)),
+		}
+		rest = after;
+	}
+	// "((" escapes a refinement that starts with a bracket.
+	let refinement = rest.strip_prefix('(').filter(|rest| rest.starts_with('(')).unwrap_or(rest).trim();
+	if !refinement.is_empty() {
+		refinement.to_string()
+	} else if !names.is_empty() {
+		names.join("; ")
+	} else {
+		value.to_string()
+	}
+}
+
+/// The ID3v1 genres, including Winamp's extensions.
+const GENRES: [&str; 148] = [
+	"Blues",
+	"Classic Rock",
+	"Country",
+	"Dance",
+	"Disco",
+	"Funk",
+	"Grunge",
+	"Hip-Hop",
+	"Jazz",
+	"Metal",
+	"New Age",
+	"Oldies",
+	"Other",
+	"Pop",
+	"R&B",
+	"Rap",
+	"Reggae",
+	"Rock",
+	"Techno",
+	"Industrial",
+	"Alternative",
+	"Ska",
+	"Death Metal",
+	"Pranks",
+	"Soundtrack",
+	"Euro-Techno",
+	"Ambient",
+	"Trip-Hop",
+	"Vocal",
+	"Jazz+Funk",
+	"Fusion",
+	"Trance",
+	"Classical",
+	"Instrumental",
+	"Acid",
+	"House",
+	"Game",
+	"Sound Clip",
+	"Gospel",
+	"Noise",
+	"Alternative Rock",
+	"Bass",
+	"Soul",
+	"Punk",
+	"Space",
+	"Meditative",
+	"Instrumental Pop",
+	"Instrumental Rock",
+	"Ethnic",
+	"Gothic",
+	"Darkwave",
+	"Techno-Industrial",
+	"Electronic",
+	"Pop-Folk",
+	"Eurodance",
+	"Dream",
+	"Southern Rock",
+	"Comedy",
+	"Cult",
+	"Gangsta",
+	"Top 40",
+	"Christian Rap",
+	"Pop/Funk",
+	"Jungle",
+	"Native American",
+	"Cabaret",
+	"New Wave",
+	"Psychedelic",
+	"Rave",
+	"Showtunes",
+	"Trailer",
+	"Lo-Fi",
+	"Tribal",
+	"Acid Punk",
+	"Acid Jazz",
+	"Polka",
+	"Retro",
+	"Musical",
+	"Rock & Roll",
+	"Hard Rock",
+	"Folk",
+	"Folk-Rock",
+	"National Folk",
+	"Swing",
+	"Fast Fusion",
+	"Bebop",
+	"Latin",
+	"Revival",
+	"Celtic",
+	"Bluegrass",
+	"Avantgarde",
+	"Gothic Rock",
+	"Progressive Rock",
+	"Psychedelic Rock",
+	"Symphonic Rock",
+	"Slow Rock",
+	"Big Band",
+	"Chorus",
+	"Easy Listening",
+	"Acoustic",
+	"Humour",
+	"Speech",
+	"Chanson",
+	"Opera",
+	"Chamber Music",
+	"Sonata",
+	"Symphony",
+	"Booty Bass",
+	"Primus",
+	"Porn Groove",
+	"Satire",
+	"Slow Jam",
+	"Club",
+	"Tango",
+	"Samba",
+	"Folklore",
+	"Ballad",
+	"Power Ballad",
+	"Rhythmic Soul",
+	"Freestyle",
+	"Duet",
+	"Punk Rock",
+	"Drum Solo",
+	"A Cappella",
+	"Euro-House",
+	"Dance Hall",
+	"Goa",
+	"Drum & Bass",
+	"Club-House",
+	"Hardcore",
+	"Terror",
+	"Indie",
+	"Britpop",
+	"Afro-Punk",
+	"Polsk Punk",
+	"Beat",
+	"Christian Gangsta Rap",
+	"Heavy Metal",
+	"Black Metal",
+	"Crossover",
+	"Contemporary Christian",
+	"Christian Rock",
+	"Merengue",
+	"Salsa",
+	"Thrash Metal",
+	"Anime",
+	"JPop",
+	"Synthpop",
+];
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+
+	/// Builds a tag from a version, header flags and frames (already in the version's frame format).
+	pub(crate) fn tag(version: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+		let size = body.len() as u32;
+		let mut data = vec![b'I', b'D', b'3', version, 0, flags];
+		data.extend([
+			(size >> 21) as u8 & 0x7f,
+			(size >> 14) as u8 & 0x7f,
+			(size >> 7) as u8 & 0x7f,
+			size as u8 & 0x7f,
+		]);
+		data.extend(body);
+		data
+	}
+
+	pub(crate) fn frame3(id: &str, format: u8, data: &[u8]) -> Vec<u8> {
+		let mut frame = id.as_bytes().to_vec();
+		frame.extend((data.len() as u32).to_be_bytes());
+		frame.extend([0, format]);
+		frame.extend(data);
+		frame
+	}
+
+	pub(crate) fn frame4(id: &str, format: u8, data: &[u8]) -> Vec<u8> {
+		let size = data.len() as u32;
+		let mut frame = id.as_bytes().to_vec();
+		frame.extend([
+			(size >> 21) as u8 & 0x7f,
+			(size >> 14) as u8 & 0x7f,
+			(size >> 7) as u8 & 0x7f,
+			size as u8 & 0x7f,
+		]);
+		frame.extend([0, format]);
+		frame.extend(data);
+		frame
+	}
+
+	fn field<'a>(fields: &'a [(String, String)], key: &str) -> Option<&'a str> {
+		fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+	}
+
+	#[test]
+	fn v1_1() {
+		let mut data = vec![0; ID3V1_LENGTH];
+		data[..3].copy_from_slice(b"TAG");
+		data[3..8].copy_from_slice(b"Title");
+		data[33..41].copy_from_slice(b"Caf\xe9 Bar");
+		data[93..97].copy_from_slice(b"1997");
+		data[97..104].copy_from_slice(b"Comment");
+		data[126] = 7;
+		data[127] = 17;
+		let fields = id3v1_fields(&data);
+		assert_eq!(field(&fields, "TITLE"), Some("Title"));
+		assert_eq!(field(&fields, "ARTIST"), Some("Café Bar"));
+		assert_eq!(field(&fields, "DATE"), Some("1997"));
+		assert_eq!(field(&fields, "COMMENT"), Some("Comment"));
+		assert_eq!(field(&fields, "TRACKNUMBER"), Some("7"));
+		assert_eq!(field(&fields, "GENRE"), Some("Rock"));
+		assert_eq!(field(&fields, "ALBUM"), None);
+	}
+
+	#[test]
+	fn v2_3_utf16_frames() {
+		// "Été" in UTF-16 with a little endian byte order mark.
+		let title = [1, 0xff, 0xfe, 0xc9, 0, b't', 0, 0xe9, 0, 0, 0];
+		let artist = [2, 0, b'B', 0, b'a', 0, b'n', 0, b'd'];
+		let comment = [0, b'e', b'n', b'g', b'i', b'T', b'u', b'n', b'N', b'O', b'R', b'M', 0, b'x', 0];
+		let plain_comment = [3, b'e', b'n', b'g', 0, b'N', b'i', b'c', b'e'];
+		let body = [
+			frame3("TIT2", 0, &title),
+			frame3("TPE1", 0, &artist),
+			frame3("COMM", 0, &comment),
+			frame3("COMM", 0, &plain_comment),
+			vec![0; 16],
+		]
+		.concat();
+		let tag = Id3v2Tag::parse(&tag(3, 0, &body)).unwrap();
+		assert_eq!(tag.version, 3);
+		let fields = tag.fields();
+		assert_eq!(field(&fields, "TIT2"), Some("Été"));
+		assert_eq!(field(&fields, "TPE1"), Some("Band"));
+		assert_eq!(field(&fields, "COMM:iTunNORM"), Some("x"));
+		assert_eq!(field(&fields, "COMM"), Some("Nice"));
+		assert_eq!(fields.len(), 4);
+	}
+
+	#[test]
+	fn v2_3_whole_tag_unsynchronisation() {
+		// The frame data is 0xff 0xe0, which is unsynchronised to 0xff 0x00 0xe0.
+		let mut frame = frame3("TXXX", 0, &[0, b'K', 0, 0xff, 0xe0]);
+		let at = frame.iter().position(|byte| *byte == 0xff).unwrap();
+		frame.insert(at + 1, 0);
+		let tag = Id3v2Tag::parse(&tag(3, 0x80, &frame)).unwrap();
+		assert_eq!(tag.frames[0].data, [0, b'K', 0, 0xff, 0xe0]);
+		assert_eq!(field(&tag.fields(), "TXXX:K"), Some("ÿà"));
+	}
+
+	#[test]
+	fn v2_4_frames() {
+		let unsynchronised = frame4("TPE1", 0x02, &[3, b'A', 0xff, 0, 0xe0]);
+		let with_length = frame4("TALB", 0x01, &[0, 0, 0, 4, 3, b'L', b'P', b'!']);
+		let values = frame4("TCON", 0, b"\x0313\0Britpop\0");
+		let compressed = frame4("TIT3", 0x08, &[0, 0, 0, 1, 0x78]);
+		let tag = Id3v2Tag::parse(&tag(4, 0, &[unsynchronised, with_length, values, compressed].concat())).unwrap();
+		assert_eq!(tag.frames.len(), 3);
+		assert_eq!(tag.frames[0].data, [3, b'A', 0xff, 0xe0]);
+		let fields = tag.fields();
+		assert_eq!(field(&fields, "TALB"), Some("LP!"));
+		assert_eq!(field(&fields, "TCON"), Some("13; Britpop"));
+	}
+
+	#[test]
+	fn v2_4_extended_header() {
+		let body = [vec![0, 0, 0, 6, 1, 0], frame4("TIT2", 0, b"\x00Song")].concat();
+		let tag = Id3v2Tag::parse(&tag(4, 0x40, &body)).unwrap();
+		assert_eq!(field(&tag.fields(), "TIT2"), Some("Song"));
+	}
+
+	#[test]
+	fn v2_2_frames() {
+		let body =
+			[b"TT2".to_vec(), vec![0, 0, 5, 0], b"Song".to_vec(), b"TRK".to_vec(), vec![0, 0, 4, 0], b"3/9".to_vec()]
+				.concat();
+		let data = tag(2, 0, &body);
+		assert_eq!(id3v2_length(&data), data.len());
+		let fields = Id3v2Tag::parse(&data).unwrap().fields();
+		assert_eq!(field(&fields, "TT2"), Some("Song"));
+		assert_eq!(field(&fields, "TRK"), Some("3/9"));
+	}
+
+	#[test]
+	fn not_a_tag() {
+		assert_eq!(Id3v2Tag::parse(b"ID3"), None);
+		assert_eq!(Id3v2Tag::parse(b"TAG\x03\x00\x00\x00\x00\x00\x00"), None);
+	}
+
+	#[test]
+	fn genres() {
+		assert_eq!(genre("17"), "Rock");
+		assert_eq!(genre("(17)"), "Rock");
+		assert_eq!(genre("(17)(RX)"), "Rock; Remix");
+		assert_eq!(genre("(13)Britpop"), "Britpop");
+		assert_eq!(genre("((Bracketed)"), "(Bracketed)");
+		assert_eq!(genre("Shoegaze"), "Shoegaze");
+		assert_eq!(genre("(999)"), "(999)");
+	}
+
+	#[test]
+	fn unsync() {
+		assert_eq!(remove_unsync(&[0xff, 0, 0, 0xff, 0, 0xff]).as_ref(), [0xff, 0, 0xff, 0xff]);
+		assert!(matches!(remove_unsync(&[1, 2, 3]), Cow::Borrowed(_)));
+	}
+}
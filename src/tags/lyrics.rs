@@ -0,0 +1,341 @@
+//! Lyrics, from ID3v2 SYLT and USLT frames or `.lrc` files.
+
+use std::{fs, io, path::Path, time::Duration};
+
+use super::{
+	id3::{self, Id3v2Frame, Id3v2Tag},
+	pairs::latin1,
+};
+
+/// A song's lyrics, which are either synced (each line has a start time, and maybe each word too) or plain text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lyrics {
+	/// The lines, in order of their start times if the lyrics are synced.
+	pub lines: Vec<LyricLine>,
+	pub synced: bool,
+	/// The ID tags of a `.lrc` file, like `("ar", "Artist")`. The offset tag has already been applied to the times.
+	pub metadata: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LyricLine {
+	/// When the line starts, which is zero for plain lyrics.
+	pub start: Duration,
+	pub text: String,
+	/// The timed words (or syllables) of the line, from enhanced `.lrc` files or syllable-by-syllable SYLT frames.
+	/// Empty if only the line is timed.
+	pub words: Vec<LyricWord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LyricWord {
+	pub start: Duration,
+	/// The word, including any spaces around it, so a line's words join up to its text.
+	pub text: String,
+}
+
+impl Lyrics {
+	/// Plain lyrics, with a line for every line of the text.
+	pub fn plain(text: &str) -> Self {
+		let lines = text
+			.lines()
+			.map(|line| LyricLine { start: Duration::ZERO, text: line.to_string(), words: Vec::new() })
+			.collect();
+		Lyrics { lines, synced: false, metadata: Vec::new() }
+	}
+
+	/// The lyrics in an ID3v2 tag: its first SYLT frame of lyrics with millisecond times, or else its first USLT frame.
+	pub fn from_id3v2(tag: &Id3v2Tag) -> Option<Self> {
+		let mut uslt = tag.frames.iter().filter(|frame| frame.id == "USLT" || frame.id == "ULT");
+		let sylt = tag.frames.iter().filter(|frame| frame.id == "SYLT" || frame.id == "SLT");
+		// Content type 1 is lyrics, rather than something like a transcription or chord names.
+		sylt.filter(|frame| frame.data.get(5) == Some(&1))
+			.find_map(Lyrics::from_sylt)
+			.or_else(|| uslt.find_map(Lyrics::from_uslt))
+	}
+
+	/// Parses a SYLT (or version 2.2 SLT) frame. Returns `None` for frames timed in MPEG frames rather than
+	/// milliseconds, since those can't be converted without decoding.
+	///
+	/// Frames that start some entries with a line break are timed by syllable, and are grouped into lines of words.
+	/// Otherwise each entry is a line.
+	pub fn from_sylt(frame: &Id3v2Frame) -> Option<Self> {
+		let (&encoding, data) = frame.data.split_first()?;
+		// The language, then the time format (2 is milliseconds) and content type.
+		if data.get(3) != Some(&2) {
+			return None;
+		}
+		let (_, mut data) = id3::split_terminated(encoding, data.get(5..)?);
+		let mut entries = Vec::new();
+		while !data.is_empty() {
+			let (text, rest) = id3::split_terminated(encoding, data);
+			let Some(time) = rest.get(..4) else { break };
+			let time = Duration::from_millis(u32::from_be_bytes(time.try_into().unwrap()) as u64);
+			entries.push((time, id3::decode_text(encoding, text)));
+			data = &rest[4..];
+		}
+
+		let by_syllable = entries.iter().skip(1).any(|(_, text)| text.starts_with(['\n', '\r']));
+		let mut lines: Vec<LyricLine> = Vec::new();
+		for (start, text) in entries {
+			if !by_syllable {
+				lines.push(LyricLine { start, text, words: Vec::new() });
+				continue;
+			}
+			let syllable = text.trim_start_matches(['\n', '\r']);
+			match lines.last_mut() {
+				Some(line) if syllable.len() == text.len() => line.text.push_str(syllable),
+				_ => lines.push(LyricLine { start, text: syllable.to_string(), words: Vec::new() }),
+			}
+			lines.last_mut().unwrap().words.push(LyricWord { start, text: syllable.to_string() });
+		}
+		lines.sort_by_key(|line| line.start);
+		Some(Lyrics { lines, synced: true, metadata: Vec::new() })
+	}
+
+	/// Parses a USLT (or version 2.2 ULT) frame.
+	pub fn from_uslt(frame: &Id3v2Frame) -> Option<Self> {
+		let (&encoding, data) = frame.data.split_first()?;
+		let (_, text) = id3::split_terminated(encoding, data.get(3..)?);
+		Some(Lyrics::plain(&id3::decode_text(encoding, text)))
+	}
+
+	/// Parses the text of a `.lrc` file, including enhanced word times (`<mm:ss.xx>`) and the offset tag. Lines can
+	/// have several times, like `[00:12.00][01:30.00]Chorus`. Text without any times is parsed as plain lyrics.
+	pub fn parse_lrc(text: &str) -> Self {
+		let mut lyrics = Lyrics { synced: true, ..Default::default() };
+		let mut offset = 0i64;
+		for line in text.lines() {
+			let mut rest = line.trim();
+			let mut starts = Vec::new();
+			while let Some((tag, after)) = rest.strip_prefix('[').and_then(|tag| tag.split_once(']')) {
+				match timestamp(tag) {
+					Some(start) => starts.push(start),
+					None if starts.is_empty() => {
+						if let Some((key, value)) = tag.split_once(':') {
+							let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+							if key == "offset" {
+								offset = value.trim_start_matches('+').parse().unwrap_or(0);
+							}
+							lyrics.metadata.push((key, value.to_string()));
+						}
+					}
+					None => break,
+				}
+				rest = after;
+			}
+			if starts.is_empty() {
+				continue;
+			}
+			let (line_text, timed) = words(rest);
+			// Word times are written for the first time, so they move along with the line for the others.
+			let first = starts[0];
+			for start in starts {
+				let words = timed
+					.iter()
+					.map(|(at, text)| LyricWord { start: (*at + start).saturating_sub(first), text: text.clone() })
+					.collect();
+				lyrics.lines.push(LyricLine { start, text: line_text.clone(), words });
+			}
+		}
+		if lyrics.lines.is_empty() {
+			return Lyrics { metadata: lyrics.metadata, ..Lyrics::plain(text) };
+		}
+
+		// A positive offset makes the lyrics appear sooner.
+		let shift = |start: &mut Duration| {
+			let millis = start.as_millis() as i64 - offset;
+			*start = Duration::from_millis(millis.max(0) as u64);
+		};
+		for line in &mut lyrics.lines {
+			shift(&mut line.start);
+			line.words.iter_mut().for_each(|word| shift(&mut word.start));
+		}
+		lyrics.lines.sort_by_key(|line| line.start);
+		lyrics
+	}
+
+	/// Reads a `.lrc` file, which is usually UTF-8 but sometimes Latin-1.
+	pub fn read_lrc(path: impl AsRef<Path>) -> io::Result<Self> {
+		let data = fs::read(path)?;
+		let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&data);
+		let text = match std::str::from_utf8(data) {
+			Ok(text) => text.to_string(),
+			Err(_) => latin1(data),
+		};
+		Ok(Lyrics::parse_lrc(&text))
+	}
+
+	/// The index of the line being sung at a time: the last one that has started.
+	pub fn line_at(&self, time: Duration) -> Option<usize> {
+		if !self.synced {
+			return None;
+		}
+		self.lines.partition_point(|line| line.start <= time).checked_sub(1)
+	}
+
+	/// The index of the word of a line being sung at a time.
+	pub fn word_at(&self, line: usize, time: Duration) -> Option<usize> {
+		let words = &self.lines.get(line)?.words;
+		words.partition_point(|word| word.start <= time).checked_sub(1)
+	}
+}
+
+/// Parses an `.lrc` time, like "01:02.50", "01:02:50", "01:02.500" or "01:02".
+fn timestamp(text: &str) -> Option<Duration> {
+	let (minutes, rest) = text.trim().split_once(':')?;
+	let (seconds, fraction) = match rest.split_once(['.', ':']) {
+		Some((seconds, fraction)) => (seconds, fraction),
+		None => (rest, ""),
+	};
+	let digits = |text: &str| !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit());
+	if !digits(minutes) || !digits(seconds) || !(fraction.is_empty() || digits(fraction)) {
+		return None;
+	}
+	let millis = match fraction.len() {
+		0 => 0,
+		// Hundredths are the most common, but any number of decimal places is read as a fraction.
+		length => fraction.get(..3.min(length))?.parse::<u64>().ok()? * 10u64.pow(3 - 3.min(length) as u32),
+	};
+	let seconds = minutes.parse::<u64>().ok()? * 60 + seconds.parse::<u64>().ok()?;
+	Some(Duration::from_millis(seconds * 1000 + millis))
+}
+
+/// Splits the text of an enhanced `.lrc` line into its words, returning the text without the times. Lines without
+/// word times have no words.
+fn words(line: &str) -> (String, Vec<(Duration, String)>) {
+	let mut words = Vec::new();
+	let mut text = String::new();
+	let mut rest = line;
+	let mut start = None;
+	loop {
+		let next = rest.find('<').and_then(|open| {
+			let close = open + rest[open..].find('>')?;
+			Some((open, close, timestamp(&rest[open + 1..close])?))
+		});
+		let (word, after) = match next {
+			Some((open, close, _)) => (&rest[..open], &rest[close + 1..]),
+			None => (rest, ""),
+		};
+		text.push_str(word);
+		if let Some(start) = start {
+			if !word.trim().is_empty() {
+				words.push((start, word.to_string()));
+			}
+		}
+		match next {
+			Some((_, _, time)) => start = Some(time),
+			None => break,
+		}
+		rest = after;
+	}
+	(text.trim().to_string(), words)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn ms(millis: u64) -> Duration {
+		Duration::from_millis(millis)
+	}
+
+	#[test]
+	fn lrc() {
+		let lyrics = Lyrics::parse_lrc(
+			"[ti:Song]\n[ar:Band]\n[00:12.00][01:30.50]Chorus\n\n[00:05.5]First line\r\n[00:20]No [brackets] here\njunk",
+		);
+		assert!(lyrics.synced);
+		assert_eq!(lyrics.metadata, [("ti".to_string(), "Song".to_string()), ("ar".to_string(), "Band".to_string())]);
+		let lines: Vec<_> = lyrics.lines.iter().map(|line| (line.start, line.text.as_str())).collect();
+		assert_eq!(
+			lines,
+			[(ms(5500), "First line"), (ms(12000), "Chorus"), (ms(20000), "No [brackets] here"), (ms(90500), "Chorus")]
+		);
+		assert_eq!(lyrics.line_at(ms(0)), None);
+		assert_eq!(lyrics.line_at(ms(12000)), Some(1));
+		assert_eq!(lyrics.line_at(ms(100000)), Some(3));
+	}
+
+	#[test]
+	fn enhanced_lrc_with_offset() {
+		let lyrics = Lyrics::parse_lrc("[offset:+500]\n[00:10.00]<00:10.00>Hel<00:10.40>lo <00:11.00>world<00:12.00>");
+		let line = &lyrics.lines[0];
+		assert_eq!(line.start, ms(9500));
+		assert_eq!(line.text, "Hello world");
+		let words: Vec<_> = line.words.iter().map(|word| (word.start, word.text.as_str())).collect();
+		assert_eq!(words, [(ms(9500), "Hel"), (ms(9900), "lo "), (ms(10500), "world")]);
+		assert_eq!(lyrics.word_at(0, ms(10000)), Some(1));
+		assert_eq!(lyrics.word_at(0, ms(9000)), None);
+	}
+
+	#[test]
+	fn enhanced_lrc_repeated() {
+		let lyrics = Lyrics::parse_lrc("[00:10.00][01:00.00]<00:10.00>La <00:10.50>la");
+		let words: Vec<_> =
+			lyrics.lines.iter().map(|line| line.words.iter().map(|word| word.start).collect::<Vec<_>>()).collect();
+		assert_eq!(words, [[ms(10000), ms(10500)], [ms(60000), ms(60500)]]);
+		assert_eq!(lyrics.word_at(1, ms(60600)), Some(1));
+	}
+
+	#[test]
+	fn plain_text() {
+		let lyrics = Lyrics::parse_lrc("Just\nwords");
+		assert!(!lyrics.synced);
+		assert_eq!(lyrics.lines.len(), 2);
+		assert_eq!(lyrics.line_at(ms(1000)), None);
+	}
+
+	#[test]
+	fn timestamps() {
+		assert_eq!(timestamp("01:02.50"), Some(ms(62500)));
+		assert_eq!(timestamp("01:02:05"), Some(ms(62050)));
+		assert_eq!(timestamp("00:00.1234"), Some(ms(123)));
+		assert_eq!(timestamp("1:02"), Some(ms(62000)));
+		assert_eq!(timestamp("ar:x"), None);
+		assert_eq!(timestamp("01:xx"), None);
+	}
+
+	fn sylt(entries: &[(&str, u32)]) -> Id3v2Frame {
+		let mut data = [&[3][..], b"eng", &[2, 1], b"\0"].concat();
+		for (text, time) in entries {
+			data.extend(text.as_bytes());
+			data.push(0);
+			data.extend(time.to_be_bytes());
+		}
+		Id3v2Frame { id: "SYLT".to_string(), data }
+	}
+
+	#[test]
+	fn sylt_lines() {
+		let lyrics = Lyrics::from_sylt(&sylt(&[("One", 1000), ("Two", 2000)])).unwrap();
+		let lines: Vec<_> = lyrics.lines.iter().map(|line| (line.start, line.text.as_str())).collect();
+		assert_eq!(lines, [(ms(1000), "One"), (ms(2000), "Two")]);
+		assert!(lyrics.lines[0].words.is_empty());
+	}
+
+	#[test]
+	fn sylt_syllables() {
+		let frame = sylt(&[("Hel", 1000), ("lo", 1200), ("\nA", 3000), ("gain", 3300)]);
+		let lyrics = Lyrics::from_sylt(&frame).unwrap();
+		assert_eq!(lyrics.lines.len(), 2);
+		assert_eq!((lyrics.lines[0].start, lyrics.lines[0].text.as_str()), (ms(1000), "Hello"));
+		assert_eq!((lyrics.lines[1].start, lyrics.lines[1].text.as_str()), (ms(3000), "Again"));
+		assert_eq!(lyrics.lines[1].words[1], LyricWord { start: ms(3300), text: "gain".to_string() });
+
+		let mut frames = sylt(&[("x", 0)]);
+		frames.data[4] = 1;
+		assert_eq!(Lyrics::from_sylt(&frames), None);
+	}
+
+	#[test]
+	fn id3v2_prefers_synced() {
+		let uslt = Id3v2Frame { id: "USLT".to_string(), data: [&[0][..], b"eng", b"\0", b"Line 1\nLine 2"].concat() };
+		let tag = Id3v2Tag { version: 3, frames: vec![uslt.clone()] };
+		let lyrics = Lyrics::from_id3v2(&tag).unwrap();
+		assert!(!lyrics.synced);
+		assert_eq!(lyrics.lines[1].text, "Line 2");
+		let tag = Id3v2Tag { version: 3, frames: vec![uslt, sylt(&[("Synced", 0)])] };
+		assert!(Lyrics::from_id3v2(&tag).unwrap().synced);
+	}
+}
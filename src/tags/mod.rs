@@ -0,0 +1,407 @@
+//! Reading and parsing the tags BASS finds in files, with `Channel::tags`.
+//!
+//! The parsing is pure Rust over the raw blocks from `BASS_ChannelGetTags`, so `Tags::parse` can be used on blocks
+//! read some other way.
+
+pub mod bext;
+pub mod chapter;
+pub mod id3;
+pub mod lyrics;
+pub mod mp4;
+pub mod pairs;
+pub mod picture;
+
+use std::{ffi::CStr, os::raw::c_char, slice};
+
+use bass_sys::*;
+
+use crate::{
+	bass::error::{BassError, BassErrorCode},
+	BassResult,
+};
+
+/// A kind of tag block BASS can return.
+///
+/// The order is the priority used by `Tags::parse` when more than one block has the same field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagKind {
+	Id3v2,
+	Ogg,
+	Ape,
+	Mp4,
+	Wma,
+	RiffInfo,
+	RiffBext,
+	Id3,
+}
+
+impl TagKind {
+	pub const ALL: [TagKind; 8] = [
+		TagKind::Id3v2,
+		TagKind::Ogg,
+		TagKind::Ape,
+		TagKind::Mp4,
+		TagKind::Wma,
+		TagKind::RiffInfo,
+		TagKind::RiffBext,
+		TagKind::Id3,
+	];
+
+	fn raw(self) -> DWORD {
+		match self {
+			TagKind::Id3v2 => BASS_TAG_ID3V2,
+			TagKind::Ogg => BASS_TAG_OGG,
+			TagKind::Ape => BASS_TAG_APE,
+			TagKind::Mp4 => BASS_TAG_MP4,
+			TagKind::Wma => BASS_TAG_WMA,
+			TagKind::RiffInfo => BASS_TAG_RIFF_INFO,
+			TagKind::RiffBext => BASS_TAG_RIFF_BEXT,
+			TagKind::Id3 => BASS_TAG_ID3,
+		}
+	}
+
+	/// Copies a block out of the memory BASS returned.
+	///
+	/// # Safety
+	///
+	/// `tags` must be a non-null pointer returned by `BASS_ChannelGetTags` for this kind.
+	unsafe fn copy(self, tags: *const u8) -> Vec<u8> {
+		let length = match self {
+			TagKind::Id3 => id3::ID3V1_LENGTH,
+			TagKind::Id3v2 => id3::id3v2_length(slice::from_raw_parts(tags, id3::ID3V2_HEADER_LENGTH)),
+			TagKind::RiffBext => {
+				let history = CStr::from_ptr(tags.add(bext::FIXED_LENGTH) as *const c_char);
+				bext::FIXED_LENGTH + history.to_bytes().len()
+			}
+			// A series of null-terminated strings, ending with an empty string.
+			TagKind::Ogg | TagKind::Ape | TagKind::Mp4 | TagKind::Wma | TagKind::RiffInfo => {
+				let mut length = 0;
+				loop {
+					let string = CStr::from_ptr(tags.add(length) as *const c_char).to_bytes().len();
+					if string == 0 {
+						break length;
+					}
+					length += string + 1;
+				}
+			}
+		};
+		slice::from_raw_parts(tags, length).to_vec()
+	}
+}
+
+/// A raw block of tags, from `Channel::raw_tags`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagBlock {
+	pub kind: TagKind,
+	pub data: Vec<u8>,
+}
+
+impl TagBlock {
+	pub fn new(kind: TagKind, data: impl Into<Vec<u8>>) -> Self {
+		TagBlock { kind, data: data.into() }
+	}
+
+	/// The fields in the block as key/value pairs. Keys are as they appear in the block (frame IDs for ID3v2), except
+	/// ID3v1, which uses Vorbis comment names.
+	pub fn fields(&self) -> Vec<(String, String)> {
+		match self.kind {
+			TagKind::Id3v2 => id3::Id3v2Tag::parse(&self.data).map(|tag| tag.fields()).unwrap_or_default(),
+			TagKind::Id3 => id3::id3v1_fields(&self.data),
+			TagKind::RiffBext => bext::fields(&self.data),
+			TagKind::RiffInfo => pairs::parse(&self.data, pairs::Encoding::Ansi),
+			TagKind::Ogg | TagKind::Ape | TagKind::Mp4 | TagKind::Wma => {
+				pairs::parse(&self.data, pairs::Encoding::Utf8)
+			}
+		}
+	}
+}
+
+/// Reads every kind of tag block the channel has.
+pub(crate) fn read(handle: DWORD, operation: &'static str) -> BassResult<Vec<TagBlock>> {
+	let mut blocks = Vec::new();
+	for kind in TagKind::ALL {
+		let tags = BASS_ChannelGetTags(handle, kind.raw());
+		if tags.is_null() {
+			// Not having a kind of tag is normal, but a bad handle isn't.
+			let error = BassError::get(operation).with_handle(handle);
+			if error.code() == BassErrorCode::BassErrorHandle {
+				return Err(error);
+			}
+			continue;
+		}
+		blocks.push(TagBlock { kind, data: unsafe { kind.copy(tags as *const u8) } });
+	}
+	Ok(blocks)
+}
+
+/// A field from one of the tag blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagField {
+	pub kind: TagKind,
+	pub key: String,
+	pub value: String,
+}
+
+/// The common fields of a file's tags, along with every field that was found.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tags {
+	pub title: Option<String>,
+	pub artist: Option<String>,
+	pub album: Option<String>,
+	pub album_artist: Option<String>,
+	pub track: Option<u32>,
+	pub track_total: Option<u32>,
+	pub disc: Option<u32>,
+	pub disc_total: Option<u32>,
+	pub year: Option<u32>,
+	pub genre: Option<String>,
+	pub comment: Option<String>,
+	pub isrc: Option<String>,
+	/// Every field of every block, in priority order.
+	pub fields: Vec<TagField>,
+}
+
+/// The common fields, which keys from the different formats are mapped to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Common {
+	Title,
+	Artist,
+	Album,
+	AlbumArtist,
+	Track,
+	TrackTotal,
+	Disc,
+	DiscTotal,
+	Year,
+	Genre,
+	Comment,
+	Isrc,
+}
+
+impl Common {
+	fn of(kind: TagKind, key: &str) -> Option<Common> {
+		let key = key.to_ascii_uppercase();
+		let common = match kind {
+			TagKind::Id3v2 => match key.as_str() {
+				"TIT2" | "TT2" => Common::Title,
+				"TPE1" | "TP1" => Common::Artist,
+				"TALB" | "TAL" => Common::Album,
+				"TPE2" | "TP2" => Common::AlbumArtist,
+				"TRCK" | "TRK" => Common::Track,
+				"TPOS" | "TPA" => Common::Disc,
+				"TDRC" | "TYER" | "TYE" => Common::Year,
+				"TCON" | "TCO" => Common::Genre,
+				"COMM" | "COM" => Common::Comment,
+				"TSRC" | "TRC" => Common::Isrc,
+				_ => return None,
+			},
+			TagKind::Wma => match key.as_str() {
+				"TITLE" => Common::Title,
+				"AUTHOR" => Common::Artist,
+				"WM/ALBUMTITLE" => Common::Album,
+				"WM/ALBUMARTIST" => Common::AlbumArtist,
+				"WM/TRACKNUMBER" => Common::Track,
+				"WM/PARTOFSET" => Common::Disc,
+				"WM/YEAR" => Common::Year,
+				"WM/GENRE" => Common::Genre,
+				"DESCRIPTION" => Common::Comment,
+				"WM/ISRC" => Common::Isrc,
+				_ => return None,
+			},
+			// RIFF INFO's "ISRC" is the source, not a recording code.
+			TagKind::RiffInfo => match key.as_str() {
+				"INAM" => Common::Title,
+				"IART" => Common::Artist,
+				"IPRD" => Common::Album,
+				"ITRK" | "IPRT" => Common::Track,
+				"ICRD" => Common::Year,
+				"IGNR" => Common::Genre,
+				"ICMT" => Common::Comment,
+				_ => return None,
+			},
+			TagKind::RiffBext => match key.as_str() {
+				"DESCRIPTION" => Common::Comment,
+				"ORIGINATIONDATE" => Common::Year,
+				_ => return None,
+			},
+			// Vorbis comments, along with the similar names APE, MP4 and ID3v1 (as named by `id3v1_fields`) use.
+			TagKind::Ogg | TagKind::Ape | TagKind::Mp4 | TagKind::Id3 => match key.as_str() {
+				"TITLE" => Common::Title,
+				"ARTIST" => Common::Artist,
+				"ALBUM" => Common::Album,
+				"ALBUMARTIST" | "ALBUM ARTIST" | "ALBUM_ARTIST" => Common::AlbumArtist,
+				"TRACKNUMBER" | "TRACK" => Common::Track,
+				"TRACKTOTAL" | "TOTALTRACKS" => Common::TrackTotal,
+				"DISCNUMBER" | "DISC" => Common::Disc,
+				"DISCTOTAL" | "TOTALDISCS" => Common::DiscTotal,
+				"DATE" | "YEAR" => Common::Year,
+				"GENRE" => Common::Genre,
+				"COMMENT" | "DESCRIPTION" => Common::Comment,
+				"ISRC" => Common::Isrc,
+				_ => return None,
+			},
+		};
+		Some(common)
+	}
+}
+
+impl Tags {
+	/// Parses tag blocks. When more than one block has a common field, the first in `TagKind` order is used.
+	pub fn parse<'a>(blocks: impl IntoIterator<Item = &'a TagBlock>) -> Self {
+		let mut blocks: Vec<&TagBlock> = blocks.into_iter().collect();
+		blocks.sort_by_key(|block| block.kind);
+		let mut tags = Tags::default();
+		for block in blocks {
+			for (key, value) in block.fields() {
+				if let Some(common) = Common::of(block.kind, &key) {
+					tags.apply(common, block.kind, &value);
+				}
+				tags.fields.push(TagField { kind: block.kind, key, value });
+			}
+		}
+		tags
+	}
+
+	/// The value of the first field with a key (compared case-insensitively).
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.fields.iter().find(|field| field.key.eq_ignore_ascii_case(key)).map(|field| field.value.as_str())
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.fields.is_empty()
+	}
+
+	fn apply(&mut self, common: Common, kind: TagKind, value: &str) {
+		let value = value.trim();
+		if value.is_empty() {
+			return;
+		}
+		let text = |field: &mut Option<String>| {
+			field.get_or_insert_with(|| value.to_string());
+		};
+		match common {
+			Common::Title => text(&mut self.title),
+			Common::Artist => text(&mut self.artist),
+			Common::Album => text(&mut self.album),
+			Common::AlbumArtist => text(&mut self.album_artist),
+			Common::Comment => text(&mut self.comment),
+			Common::Isrc => text(&mut self.isrc),
+			Common::Genre if self.genre.is_none() => {
+				self.genre = Some(match kind {
+					// Version 2.4 frames can have several genres, which are joined by this point.
+					TagKind::Id3v2 => value.split("; ").map(id3::genre).collect::<Vec<_>>().join("; "),
+					_ => value.to_string(),
+				});
+			}
+			Common::Genre => {}
+			Common::Track => number_of(value, &mut self.track, &mut self.track_total),
+			Common::Disc => number_of(value, &mut self.disc, &mut self.disc_total),
+			Common::TrackTotal => set_number(&mut self.track_total, value),
+			Common::DiscTotal => set_number(&mut self.disc_total, value),
+			Common::Year => {
+				if self.year.is_none() {
+					self.year = year(value);
+				}
+			}
+		}
+	}
+}
+
+/// Parses a number like "3" or "3/12" into a number and a total.
+fn number_of(value: &str, number: &mut Option<u32>, total: &mut Option<u32>) {
+	let (value, of) = value.split_once('/').unwrap_or((value, ""));
+	set_number(number, value);
+	set_number(total, of);
+}
+
+fn set_number(field: &mut Option<u32>, value: &str) {
+	if field.is_none() {
+		*field = value.trim().parse().ok().filter(|number| *number > 0);
+	}
+}
+
+/// The year from a date, which starts with the year in every format ("2004", "2004-05-06T07:08" or "2004:05:06").
+fn year(value: &str) -> Option<u32> {
+	let digits = value.trim().get(..4)?;
+	digits.chars().all(|c| c.is_ascii_digit()).then(|| digits.parse().ok()).flatten().filter(|year| *year > 0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn pairs(data: &[&str]) -> Vec<u8> {
+		data.iter().flat_map(|pair| pair.bytes().chain([0])).collect()
+	}
+
+	#[test]
+	fn common_fields_from_vorbis_comments() {
+		let block = TagBlock::new(
+			TagKind::Ogg,
+			pairs(&[
+				"TITLE=Song",
+				"artist=Band",
+				"TRACKNUMBER=3",
+				"TRACKTOTAL=12",
+				"DATE=2004-05-06",
+				"ISRC=GBAYE0000001",
+			]),
+		);
+		let tags = Tags::parse([&block]);
+		assert_eq!(tags.title.as_deref(), Some("Song"));
+		assert_eq!(tags.artist.as_deref(), Some("Band"));
+		assert_eq!((tags.track, tags.track_total), (Some(3), Some(12)));
+		assert_eq!(tags.year, Some(2004));
+		assert_eq!(tags.isrc.as_deref(), Some("GBAYE0000001"));
+		assert_eq!(tags.get("Artist"), Some("Band"));
+		assert_eq!(tags.fields.len(), 6);
+	}
+
+	#[test]
+	fn earlier_kinds_take_priority() {
+		let mut v1 = b"TAG".to_vec();
+		v1.extend(b"Old title".iter().copied().chain([0; 21]));
+		v1.resize(id3::ID3V1_LENGTH, 0);
+		v1[127] = 255;
+		let ape = TagBlock::new(TagKind::Ape, pairs(&["Title=New title", "Track=2/9"]));
+		let tags = Tags::parse([&TagBlock::new(TagKind::Id3, v1), &ape]);
+		assert_eq!(tags.title.as_deref(), Some("New title"));
+		assert_eq!(tags.fields[0].kind, TagKind::Ape);
+		assert_eq!((tags.track, tags.track_total), (Some(2), Some(9)));
+	}
+
+	#[test]
+	fn id3v2_genres() {
+		let body = id3::tests::frame4("TCON", 0, b"\x0313\0Britpop");
+		let tags = Tags::parse([&TagBlock::new(TagKind::Id3v2, id3::tests::tag(4, 0, &body))]);
+		assert_eq!(tags.genre.as_deref(), Some("Pop; Britpop"));
+	}
+
+	#[test]
+	fn riff_info_source_is_not_an_isrc() {
+		let block = TagBlock::new(TagKind::RiffInfo, pairs(&["INAM=Take 1", "ISRC=Tape", "ICRD=1999"]));
+		let tags = Tags::parse([&block]);
+		assert_eq!(tags.title.as_deref(), Some("Take 1"));
+		assert_eq!(tags.isrc, None);
+		assert_eq!(tags.year, Some(1999));
+	}
+
+	#[test]
+	fn wma_names() {
+		let block =
+			TagBlock::new(TagKind::Wma, pairs(&["Title=Song", "Author=Band", "WM/PartOfSet=1/2", "WM/Year=2010"]));
+		let tags = Tags::parse([&block]);
+		assert_eq!(tags.artist.as_deref(), Some("Band"));
+		assert_eq!((tags.disc, tags.disc_total), (Some(1), Some(2)));
+		assert_eq!(tags.year, Some(2010));
+	}
+
+	#[test]
+	fn years() {
+		assert_eq!(year("2004"), Some(2004));
+		assert_eq!(year("2004:05:06 07:08:09"), Some(2004));
+		assert_eq!(year("0000"), None);
+		assert_eq!(year("May 2004"), None);
+		assert_eq!(year("04"), None);
+	}
+}
@@ -0,0 +1,109 @@
+//! MP4 boxes ("atoms") that BASS doesn't return as tags, like cover art, read from the file's `moov` box.
+
+use std::{
+	fs::File,
+	io::{self, Read, Seek, SeekFrom},
+	path::Path,
+};
+
+/// Iterates over the boxes in some data, as (type, body) pairs. Iteration stops at a box that doesn't fit.
+pub fn boxes(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> + '_ {
+	let mut rest = data;
+	std::iter::from_fn(move || {
+		let (kind, header, size) = box_header(rest)?;
+		let body = rest.get(header..size)?;
+		rest = &rest[size..];
+		Some((kind, body))
+	})
+}
+
+/// The type, header length and total size of the box at the start of some data.
+fn box_header(data: &[u8]) -> Option<([u8; 4], usize, usize)> {
+	let size = u32::from_be_bytes(data.get(..4)?.try_into().unwrap()) as usize;
+	let kind = data.get(4..8)?.try_into().unwrap();
+	match size {
+		// Extends to the end of the data.
+		0 => Some((kind, 8, data.len())),
+		1 => {
+			let size = u64::from_be_bytes(data.get(8..16)?.try_into().unwrap());
+			Some((kind, 16, usize::try_from(size).ok().filter(|size| *size >= 16)?))
+		}
+		size if size >= 8 => Some((kind, 8, size)),
+		_ => None,
+	}
+}
+
+/// Finds a box by its path from the top of some data, like `[b"udta", b"meta", b"ilst"]` in a `moov` box. The version
+/// and flags of `meta`, which is a "full box", are skipped.
+pub fn find<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+	path.iter().try_fold(data, |data, kind| {
+		let (_, body) = boxes(data).find(|(found, _)| found == *kind)?;
+		Some(if *kind == b"meta" { body.get(4..)? } else { body })
+	})
+}
+
+/// Reads the body of the `moov` box of an MP4 file, without reading the (usually much bigger) media data. Returns
+/// `None` if the file isn't an MP4 file.
+pub fn read_moov(path: impl AsRef<Path>) -> io::Result<Option<Vec<u8>>> {
+	let mut file = File::open(path)?;
+	let length = file.metadata()?.len();
+	let mut position = 0;
+	let mut first = true;
+	while position + 8 <= length {
+		let mut buffer = [0; 16];
+		file.seek(SeekFrom::Start(position))?;
+		let read = file.read(&mut buffer)?;
+		let Some((kind, header, size)) = (match buffer[..4] {
+			// Extends to the end of the file rather than the buffer.
+			[0, 0, 0, 0] if read >= 8 => Some((buffer[4..8].try_into().unwrap(), 8, (length - position) as usize)),
+			_ => box_header(&buffer[..read]),
+		}) else {
+			break;
+		};
+		if position + size as u64 > length {
+			break;
+		}
+		if first && &kind != b"ftyp" {
+			return Ok(None);
+		}
+		first = false;
+		if &kind == b"moov" {
+			let mut body = vec![0; size - header];
+			file.seek(SeekFrom::Start(position + header as u64))?;
+			file.read_exact(&mut body)?;
+			return Ok(Some(body));
+		}
+		position += size as u64;
+	}
+	Ok(None)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+
+	/// Builds a box from a type and a body.
+	pub(crate) fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+		let mut data = (body.len() as u32 + 8).to_be_bytes().to_vec();
+		data.extend(kind);
+		data.extend(body);
+		data
+	}
+
+	#[test]
+	fn finds_nested_boxes() {
+		let ilst = mp4_box(b"ilst", &mp4_box(b"\xa9nam", b"x"));
+		let meta = mp4_box(b"meta", &[vec![0; 4], mp4_box(b"hdlr", &[0; 4]), ilst].concat());
+		let moov = [mp4_box(b"mvhd", &[0; 4]), mp4_box(b"udta", &meta)].concat();
+		let ilst = find(&moov, &[b"udta", b"meta", b"ilst"]).unwrap();
+		assert_eq!(boxes(ilst).collect::<Vec<_>>(), [(*b"\xa9nam", &b"x"[..])]);
+		assert_eq!(find(&moov, &[b"trak"]), None);
+	}
+
+	#[test]
+	fn stops_at_truncated_boxes() {
+		let mut data = mp4_box(b"free", &[1, 2]);
+		data.extend(mp4_box(b"skip", &[0; 8])[..10].iter());
+		assert_eq!(boxes(&data).count(), 1);
+	}
+}
@@ -0,0 +1,51 @@
+//! Tag blocks that BASS returns as a series of null-terminated "key=value" strings: OGG, APE, MP4, WMA and RIFF INFO.
+
+/// How the strings in a block are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+	Utf8,
+	/// The system code page, which is usually Latin-1 (or UTF-8, which is tried first).
+	Ansi,
+}
+
+/// Parses a block into key/value pairs. Strings without an `=` are skipped.
+pub fn parse(data: &[u8], encoding: Encoding) -> Vec<(String, String)> {
+	data.split(|byte| *byte == 0)
+		.filter(|string| !string.is_empty())
+		.filter_map(|string| {
+			let string = match encoding {
+				Encoding::Utf8 => String::from_utf8_lossy(string).into_owned(),
+				Encoding::Ansi => match std::str::from_utf8(string) {
+					Ok(string) => string.to_string(),
+					Err(_) => latin1(string),
+				},
+			};
+			let (key, value) = string.split_once('=')?;
+			Some((key.to_string(), value.to_string()))
+		})
+		.collect()
+}
+
+pub(crate) fn latin1(data: &[u8]) -> String {
+	data.iter().map(|byte| *byte as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pairs() {
+		let data = b"TITLE=A=B\0junk\0ARTIST=\0";
+		assert_eq!(
+			parse(data, Encoding::Utf8),
+			[("TITLE".to_string(), "A=B".to_string()), ("ARTIST".to_string(), String::new())]
+		);
+	}
+
+	#[test]
+	fn ansi_falls_back_to_latin1() {
+		assert_eq!(parse(b"INAM=Caf\xe9\0", Encoding::Ansi), [("INAM".to_string(), "Café".to_string())]);
+		assert_eq!(parse("INAM=Café\0".as_bytes(), Encoding::Ansi), [("INAM".to_string(), "Café".to_string())]);
+	}
+}
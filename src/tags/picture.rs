@@ -0,0 +1,416 @@
+//! Embedded pictures, like cover art: ID3v2 APIC/PIC frames, FLAC and Vorbis `METADATA_BLOCK_PICTURE`s, MP4 `covr`
+//! boxes and APE "Cover Art" items.
+
+use std::{ffi::CStr, os::raw::c_char, slice};
+
+use bass_sys::*;
+
+use super::{id3, mp4, pairs::latin1, TagBlock, TagKind};
+
+/// `BASS_TAG_APE_BINARY` + index: a binary APE item.
+const TAG_APE_BINARY: DWORD = DWORD(0x1000);
+/// `BASS_TAG_FLAC_PICTURE` + index: a picture from a FLAC file (with BASSFLAC).
+const TAG_FLAC_PICTURE: DWORD = DWORD(0x12000);
+
+#[repr(C)]
+struct ApeBinary {
+	key: *const c_char,
+	data: *const u8,
+	length: u32,
+}
+
+/// Only the picture type, MIME type, description and data are used.
+#[repr(C)]
+#[allow(dead_code)]
+struct FlacPicture {
+	apic: u32,
+	mime: *const c_char,
+	desc: *const c_char,
+	width: u32,
+	height: u32,
+	depth: u32,
+	colors: u32,
+	length: u32,
+	data: *const u8,
+}
+
+/// What a picture is of, with the types from ID3v2's APIC frame (which FLAC also uses).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PictureType {
+	Other,
+	/// A 32x32 PNG.
+	FileIcon,
+	OtherFileIcon,
+	FrontCover,
+	BackCover,
+	Leaflet,
+	/// The media itself, like a picture of the CD.
+	Media,
+	LeadArtist,
+	Artist,
+	Conductor,
+	Band,
+	Composer,
+	Lyricist,
+	RecordingLocation,
+	DuringRecording,
+	DuringPerformance,
+	ScreenCapture,
+	BrightColouredFish,
+	Illustration,
+	BandLogo,
+	PublisherLogo,
+	Unknown(u32),
+}
+
+impl PictureType {
+	const ALL: [PictureType; 21] = [
+		PictureType::Other,
+		PictureType::FileIcon,
+		PictureType::OtherFileIcon,
+		PictureType::FrontCover,
+		PictureType::BackCover,
+		PictureType::Leaflet,
+		PictureType::Media,
+		PictureType::LeadArtist,
+		PictureType::Artist,
+		PictureType::Conductor,
+		PictureType::Band,
+		PictureType::Composer,
+		PictureType::Lyricist,
+		PictureType::RecordingLocation,
+		PictureType::DuringRecording,
+		PictureType::DuringPerformance,
+		PictureType::ScreenCapture,
+		PictureType::BrightColouredFish,
+		PictureType::Illustration,
+		PictureType::BandLogo,
+		PictureType::PublisherLogo,
+	];
+
+	/// The names APE items use after "Cover Art ", in the same order.
+	const APE_NAMES: [&'static str; 21] = [
+		"(Other)",
+		"(Icon)",
+		"(Other Icon)",
+		"(Front)",
+		"(Back)",
+		"(Leaflet)",
+		"(Media)",
+		"(Lead Artist)",
+		"(Artist)",
+		"(Conductor)",
+		"(Band)",
+		"(Composer)",
+		"(Lyricist)",
+		"(Recording Location)",
+		"(During Recording)",
+		"(During Performance)",
+		"(Video Capture)",
+		"(A Bright Coloured Fish)",
+		"(Illustration)",
+		"(Band Logotype)",
+		"(Publisher Logotype)",
+	];
+
+	pub fn from_raw(raw: u32) -> Self {
+		PictureType::ALL.get(raw as usize).copied().unwrap_or(PictureType::Unknown(raw))
+	}
+
+	pub fn raw(self) -> u32 {
+		match self {
+			PictureType::Unknown(raw) => raw,
+			_ => PictureType::ALL.iter().position(|kind| *kind == self).unwrap() as u32,
+		}
+	}
+
+	/// The type of an APE item with a key like "Cover Art (Front)".
+	fn from_ape_key(key: &str) -> Option<Self> {
+		let name = key.get(..10).filter(|prefix| prefix.eq_ignore_ascii_case("Cover Art "))?;
+		let name = &key[name.len()..];
+		let index = PictureType::APE_NAMES.iter().position(|known| known.eq_ignore_ascii_case(name));
+		Some(index.map_or(PictureType::Other, |index| PictureType::ALL[index]))
+	}
+}
+
+/// An embedded picture.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Picture {
+	pub picture_type: PictureType,
+	/// The MIME type, like "image/jpeg". If the tag doesn't say, it is worked out from the data, or left empty.
+	pub mime_type: String,
+	pub description: String,
+	pub data: Vec<u8>,
+}
+
+impl std::fmt::Debug for Picture {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Picture")
+			.field("picture_type", &self.picture_type)
+			.field("mime_type", &self.mime_type)
+			.field("description", &self.description)
+			.field("data", &format_args!("[{} bytes]", self.data.len()))
+			.finish()
+	}
+}
+
+impl Picture {
+	/// Parses an ID3v2 APIC frame, or a PIC frame from version 2.2, which has a three letter image format instead of
+	/// a MIME type.
+	pub fn from_id3v2(frame: &id3::Id3v2Frame) -> Option<Self> {
+		let (&encoding, data) = frame.data.split_first()?;
+		let (mime_type, data) = match frame.id.as_str() {
+			"APIC" => {
+				let (mime_type, data) = id3::split_terminated(0, data);
+				(latin1(mime_type), data)
+			}
+			"PIC" => {
+				let format = latin1(data.get(..3)?);
+				let mime_type = match format.to_ascii_uppercase().as_str() {
+					"JPG" => "image/jpeg".to_string(),
+					"-->" => "-->".to_string(),
+					format => format!("image/{}", format.to_ascii_lowercase()),
+				};
+				(mime_type, &data[3..])
+			}
+			_ => return None,
+		};
+		let (&picture_type, data) = data.split_first()?;
+		let (description, data) = id3::split_terminated(encoding, data);
+		Some(Picture::new(
+			PictureType::from_raw(picture_type as u32),
+			mime_type,
+			id3::decode_text(encoding, description),
+			data,
+		))
+	}
+
+	/// Parses a FLAC `METADATA_BLOCK_PICTURE`, which Vorbis comments also use (base64 encoded).
+	pub fn from_flac_block(mut data: &[u8]) -> Option<Self> {
+		fn take<'a>(data: &mut &'a [u8], length: usize) -> Option<&'a [u8]> {
+			let taken = data.get(..length)?;
+			*data = &data[length..];
+			Some(taken)
+		}
+		fn number(data: &mut &[u8]) -> Option<u32> {
+			take(data, 4).map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+		}
+		let picture_type = PictureType::from_raw(number(&mut data)?);
+		let length = number(&mut data)? as usize;
+		let mime_type = latin1(take(&mut data, length)?);
+		let length = number(&mut data)? as usize;
+		let description = String::from_utf8_lossy(take(&mut data, length)?).into_owned();
+		// Width, height, colour depth and the number of colours.
+		take(&mut data, 16)?;
+		let length = number(&mut data)? as usize;
+		Some(Picture::new(picture_type, mime_type, description, take(&mut data, length)?))
+	}
+
+	/// Parses an APE item with a key like "Cover Art (Front)", which is a filename followed by the picture.
+	pub fn from_ape_item(key: &str, data: &[u8]) -> Option<Self> {
+		let picture_type = PictureType::from_ape_key(key)?;
+		let (filename, data) = id3::split_terminated(0, data);
+		Some(Picture::new(picture_type, String::new(), String::from_utf8_lossy(filename).into_owned(), data))
+	}
+
+	/// Parses the `data` boxes in an MP4 `covr` box, which are always front covers.
+	pub fn from_mp4_covr(covr: &[u8]) -> Vec<Self> {
+		mp4::boxes(covr)
+			.filter(|(kind, body)| kind == b"data" && body.len() > 8)
+			.map(|(_, body)| {
+				let mime_type = match u32::from_be_bytes(body[..4].try_into().unwrap()) & 0xffffff {
+					13 => "image/jpeg",
+					14 => "image/png",
+					27 => "image/bmp",
+					_ => "",
+				};
+				Picture::new(PictureType::FrontCover, mime_type.to_string(), String::new(), &body[8..])
+			})
+			.collect()
+	}
+
+	fn new(picture_type: PictureType, mime_type: String, description: String, data: &[u8]) -> Self {
+		let mime_type = if mime_type.is_empty() { sniff(data).to_string() } else { mime_type };
+		Picture { picture_type, mime_type, description, data: data.to_vec() }
+	}
+}
+
+/// Works out the MIME type of a picture from its first bytes.
+fn sniff(data: &[u8]) -> &'static str {
+	match data {
+		[0xff, 0xd8, 0xff, ..] => "image/jpeg",
+		[0x89, b'P', b'N', b'G', ..] => "image/png",
+		[b'G', b'I', b'F', b'8', ..] => "image/gif",
+		[b'B', b'M', ..] => "image/bmp",
+		[b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+		_ => "",
+	}
+}
+
+/// The pictures in tag blocks: ID3v2 APIC and PIC frames, and `METADATA_BLOCK_PICTURE` Vorbis comments.
+pub fn pictures<'a>(blocks: impl IntoIterator<Item = &'a TagBlock>) -> Vec<Picture> {
+	let mut pictures = Vec::new();
+	for block in blocks {
+		match block.kind {
+			TagKind::Id3v2 => {
+				let Some(tag) = id3::Id3v2Tag::parse(&block.data) else { continue };
+				pictures.extend(tag.frames.iter().filter_map(Picture::from_id3v2));
+			}
+			TagKind::Ogg => {
+				let pictures_in_block = block
+					.fields()
+					.into_iter()
+					.filter(|(key, _)| key.eq_ignore_ascii_case("METADATA_BLOCK_PICTURE"))
+					.filter_map(|(_, value)| Picture::from_flac_block(&base64(&value)?));
+				pictures.extend(pictures_in_block);
+			}
+			_ => {}
+		}
+	}
+	pictures
+}
+
+/// The pictures BASS returns separately from the tag blocks: binary APE items and FLAC pictures.
+pub(crate) fn read_binary(handle: DWORD) -> Vec<Picture> {
+	let mut pictures = Vec::new();
+	for index in 0.. {
+		let item = BASS_ChannelGetTags(handle, DWORD(TAG_APE_BINARY.0 + index)) as *const ApeBinary;
+		if item.is_null() {
+			break;
+		}
+		let item = unsafe { &*item };
+		let key = unsafe { CStr::from_ptr(item.key) }.to_string_lossy();
+		let data = unsafe { slice::from_raw_parts(item.data, item.length as usize) };
+		pictures.extend(Picture::from_ape_item(&key, data));
+	}
+	for index in 0.. {
+		let picture = BASS_ChannelGetTags(handle, DWORD(TAG_FLAC_PICTURE.0 + index)) as *const FlacPicture;
+		if picture.is_null() {
+			break;
+		}
+		let picture = unsafe { &*picture };
+		let text = |text: *const c_char| match text.is_null() {
+			true => String::new(),
+			false => unsafe { CStr::from_ptr(text) }.to_string_lossy().into_owned(),
+		};
+		let data = unsafe { slice::from_raw_parts(picture.data, picture.length as usize) };
+		let picture_type = PictureType::from_raw(picture.apic);
+		pictures.push(Picture::new(picture_type, text(picture.mime), text(picture.desc), data));
+	}
+	pictures
+}
+
+/// The cover art in the `moov` box of an MP4 file.
+pub fn mp4_pictures(moov: &[u8]) -> Vec<Picture> {
+	mp4::find(moov, &[b"udta", b"meta", b"ilst", b"covr"]).map(Picture::from_mp4_covr).unwrap_or_default()
+}
+
+/// Decodes standard base64, ignoring whitespace. Returns `None` if there are any other characters.
+fn base64(text: &str) -> Option<Vec<u8>> {
+	let mut output = Vec::with_capacity(text.len() / 4 * 3);
+	let mut bits = 0u32;
+	let mut count = 0;
+	for byte in text.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+		let value = match byte {
+			b'A'..=b'Z' => byte - b'A',
+			b'a'..=b'z' => byte - b'a' + 26,
+			b'0'..=b'9' => byte - b'0' + 52,
+			b'+' => 62,
+			b'/' => 63,
+			b'=' => break,
+			_ => return None,
+		};
+		bits = (bits << 6) | value as u32;
+		count += 6;
+		if count >= 8 {
+			count -= 8;
+			output.push((bits >> count) as u8);
+		}
+	}
+	Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tags::mp4::tests::mp4_box;
+
+	const JPEG: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 1, 2, 3];
+
+	fn flac_block(picture_type: u32, mime_type: &str, description: &str, data: &[u8]) -> Vec<u8> {
+		let mut block = picture_type.to_be_bytes().to_vec();
+		block.extend((mime_type.len() as u32).to_be_bytes());
+		block.extend(mime_type.as_bytes());
+		block.extend((description.len() as u32).to_be_bytes());
+		block.extend(description.as_bytes());
+		block.extend([0; 16]);
+		block.extend((data.len() as u32).to_be_bytes());
+		block.extend(data);
+		block
+	}
+
+	#[test]
+	fn apic_frames() {
+		let apic = id3::Id3v2Frame {
+			id: "APIC".to_string(),
+			data: [&[1][..], b"image/jpeg\0", &[4], &[0xff, 0xfe, b'B', 0, 0, 0], JPEG].concat(),
+		};
+		let picture = Picture::from_id3v2(&apic).unwrap();
+		assert_eq!(picture.picture_type, PictureType::BackCover);
+		assert_eq!(picture.mime_type, "image/jpeg");
+		assert_eq!(picture.description, "B");
+		assert_eq!(picture.data, JPEG);
+
+		let pic = id3::Id3v2Frame { id: "PIC".to_string(), data: [&[0][..], b"PNG", &[3], b"\0", &[9]].concat() };
+		let picture = Picture::from_id3v2(&pic).unwrap();
+		assert_eq!(picture.picture_type, PictureType::FrontCover);
+		assert_eq!(picture.mime_type, "image/png");
+		assert_eq!(picture.data, [9]);
+	}
+
+	#[test]
+	fn vorbis_comment_pictures() {
+		let block = flac_block(3, "image/jpeg", "Cover", JPEG);
+		assert_eq!(Picture::from_flac_block(&block[..block.len() - 1]), None);
+		let encoded = "AAAAAwAAAAppbWFnZS9qcGVnAAAABUNvdmVyAAAAAAAAAAAAAAAAAAAAAAAAAAf/2P/gAQID";
+		assert_eq!(base64(encoded).as_deref(), Some(&block[..]));
+		let comments = format!("TITLE=Song\0METADATA_BLOCK_PICTURE={encoded}\0");
+		let pictures = pictures([&TagBlock::new(TagKind::Ogg, comments.into_bytes())]);
+		assert_eq!(pictures.len(), 1);
+		assert_eq!(pictures[0].picture_type, PictureType::FrontCover);
+		assert_eq!(pictures[0].description, "Cover");
+		assert_eq!(pictures[0].data, JPEG);
+	}
+
+	#[test]
+	fn ape_items() {
+		let picture = Picture::from_ape_item("Cover Art (Front)", &[b"cover.jpg\0", JPEG].concat()).unwrap();
+		assert_eq!(picture.picture_type, PictureType::FrontCover);
+		assert_eq!(picture.mime_type, "image/jpeg");
+		assert_eq!(picture.description, "cover.jpg");
+		assert_eq!(
+			Picture::from_ape_item("cover art (band logotype)", b"\0").unwrap().picture_type,
+			PictureType::BandLogo
+		);
+		assert_eq!(Picture::from_ape_item("Lyrics", b"\0"), None);
+	}
+
+	#[test]
+	fn mp4_covr() {
+		let data = |kind: u8, image: &[u8]| mp4_box(b"data", &[&[0, 0, 0, kind, 0, 0, 0, 0][..], image].concat());
+		let covr = mp4_box(b"covr", &[data(14, &[1]), data(0, JPEG)].concat());
+		let meta = mp4_box(b"meta", &[vec![0; 4], mp4_box(b"ilst", &covr)].concat());
+		let pictures = mp4_pictures(&mp4_box(b"udta", &meta));
+		assert_eq!(pictures.len(), 2);
+		assert_eq!(pictures[0].mime_type, "image/png");
+		assert_eq!(pictures[1].mime_type, "image/jpeg");
+		assert_eq!(pictures[1].data, JPEG);
+	}
+
+	#[test]
+	fn picture_types() {
+		assert_eq!(PictureType::from_raw(20), PictureType::PublisherLogo);
+		assert_eq!(PictureType::from_raw(21), PictureType::Unknown(21));
+		assert_eq!(PictureType::FrontCover.raw(), 3);
+	}
+}
@@ -1,6 +1,6 @@
-use crate::bass::error::BassErrorCode;
+use crate::bass::error::BassError;
 
-pub type BassResult<T> = Result<T, BassErrorCode>;
+pub type BassResult<T> = Result<T, BassError>;
 
 pub use bass_sys::{BASS_SAMPLE, HCHANNEL, HDSP, HFX, HMUSIC, HPLUGIN, HRECORD, HSAMPLE, HSTREAM, HSYNC};
 